@@ -2,8 +2,9 @@
 //!
 //! Provides pre-defined profiles and custom profile building.
 
+use crate::cooling::expression::ChannelExpr;
 use crate::error::Result;
-use crate::protocol::{CURVE_POINTS, interpolate_profile};
+use crate::protocol::{CURVE_POINTS, Channel, interpolate_profile};
 
 // =============================================================================
 // Speed Profiles
@@ -20,6 +21,8 @@ pub enum SpeedProfile {
     Fixed(u8),
     /// Custom temperature/duty curve.
     Custom(Vec<(u8, u8)>),
+    /// Profile expression (see [`crate::cooling::expression`]).
+    Expression(ChannelExpr),
 }
 
 impl SpeedProfile {
@@ -30,6 +33,8 @@ impl SpeedProfile {
             SpeedProfile::Performance => interpolate_profile(&PROFILE_PERFORMANCE),
             SpeedProfile::Fixed(duty) => Ok([*duty; CURVE_POINTS]),
             SpeedProfile::Custom(points) => interpolate_profile(points),
+            // Like Silent/Performance above, built-in curves use the fan variant
+            SpeedProfile::Expression(expr) => expr.device_table(Channel::Fan),
         }
     }
 
@@ -40,6 +45,7 @@ impl SpeedProfile {
             SpeedProfile::Performance => "Performance",
             SpeedProfile::Fixed(_) => "Fixed",
             SpeedProfile::Custom(_) => "Custom",
+            SpeedProfile::Expression(_) => "Expression",
         }
     }
 }
@@ -48,6 +54,7 @@ impl std::fmt::Display for SpeedProfile {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             SpeedProfile::Fixed(duty) => write!(f, "Fixed ({}%)", duty),
+            SpeedProfile::Expression(expr) => write!(f, "{}", expr),
             _ => write!(f, "{}", self.name()),
         }
     }
@@ -119,6 +126,17 @@ impl LcdProfile {
         mode: 2,
         bucket: 0,
     };
+
+    /// Look up a preset by name: "off", "night", "day" or "max" (case-insensitive).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "off" => Some(Self::OFF),
+            "night" => Some(Self::NIGHT),
+            "day" => Some(Self::DAY),
+            "max" => Some(Self::MAX),
+            _ => None,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -147,4 +165,18 @@ mod tests {
         assert_eq!(curve[0], 30);
         assert_eq!(curve[39], 100);
     }
+
+    #[test]
+    fn test_expression_profile() {
+        let expr = crate::cooling::parse_channel_expr("offset(5)+fixed(60)+clamp(max=50)").unwrap();
+        let curve = SpeedProfile::Expression(expr).to_duty_curve().unwrap();
+        assert!(curve.iter().all(|&d| d == 50));
+    }
+
+    #[test]
+    fn test_lcd_profile_from_name() {
+        assert_eq!(LcdProfile::from_name("Night"), Some(LcdProfile::NIGHT));
+        assert_eq!(LcdProfile::from_name("day"), Some(LcdProfile::DAY));
+        assert_eq!(LcdProfile::from_name("dusk"), None);
+    }
 }
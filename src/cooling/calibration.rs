@@ -0,0 +1,258 @@
+//! Calibration sweep and fan degradation detection.
+//!
+//! The sweep steps a channel from 100% down to its minimum duty, waits for the
+//! RPM to settle at each step, then steps back up from the stall point to find
+//! the start duty. The result is stored with [`crate::storage::save_calibration`].
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::device::KrakenZ63;
+use crate::error::{KrakenError, Result};
+use crate::protocol::Channel;
+use crate::storage::{CalibrationPoint, ChannelCalibration};
+
+/// Number of consecutive readings that must agree before RPM counts as settled.
+const SETTLE_SAMPLES: usize = 3;
+
+/// Sweep settings.
+#[derive(Debug, Clone)]
+pub struct CalibrationOptions {
+    /// Duty step of the main sweep (%)
+    pub step: u8,
+    /// Duty step when searching for the start duty (%)
+    pub start_step: u8,
+    /// Give up waiting for a step to settle after this long
+    pub settle_timeout: Duration,
+    /// Time between status reads while settling
+    pub sample_interval: Duration,
+    /// Readings within this many RPM of each other count as settled
+    pub tolerance_rpm: u16,
+    /// Set to abort the sweep (e.g. from a Ctrl+C handler)
+    pub cancel: Option<Arc<AtomicBool>>,
+}
+
+impl Default for CalibrationOptions {
+    fn default() -> Self {
+        Self {
+            step: 10,
+            start_step: 2,
+            settle_timeout: Duration::from_secs(20),
+            sample_interval: Duration::from_millis(1000),
+            tolerance_rpm: 30,
+            cancel: None,
+        }
+    }
+}
+
+/// Duties visited by the main sweep, from 100% down to the channel minimum.
+pub fn sweep_duties(channel: Channel, step: u8) -> Vec<u8> {
+    let step = step.max(1);
+    let min = channel.min_duty();
+    let mut duties: Vec<u8> = (min..=channel.max_duty())
+        .rev()
+        .step_by(step as usize)
+        .collect();
+    if duties.last() != Some(&min) {
+        duties.push(min);
+    }
+    duties
+}
+
+/// Returns true if the last [`SETTLE_SAMPLES`] readings are within `tolerance` RPM.
+pub fn is_settled(samples: &[u16], tolerance: u16) -> bool {
+    if samples.len() < SETTLE_SAMPLES {
+        return false;
+    }
+    let recent = &samples[samples.len() - SETTLE_SAMPLES..];
+    let min = recent.iter().min().copied().unwrap_or(0);
+    let max = recent.iter().max().copied().unwrap_or(0);
+    max - min <= tolerance
+}
+
+/// Set a duty and wait for the channel RPM to settle.
+///
+/// Returns the mean of the settled readings, or of the last readings on timeout.
+///
+/// # Errors
+/// Returns `InvalidInput` if the sweep is cancelled through
+/// [`CalibrationOptions::cancel`].
+pub fn measure_rpm(
+    kraken: &KrakenZ63,
+    channel: Channel,
+    duty: u8,
+    options: &CalibrationOptions,
+) -> Result<u16> {
+    kraken.set_channel_speed(channel, duty)?;
+
+    let deadline = Instant::now() + options.settle_timeout;
+    let mut samples = Vec::new();
+
+    loop {
+        std::thread::sleep(options.sample_interval);
+        if options
+            .cancel
+            .as_ref()
+            .is_some_and(|c| c.load(Ordering::SeqCst))
+        {
+            return Err(KrakenError::InvalidInput("Calibration cancelled".into()));
+        }
+        samples.push(kraken.get_status()?.rpm(channel));
+
+        if is_settled(&samples, options.tolerance_rpm) || Instant::now() >= deadline {
+            break;
+        }
+    }
+
+    let recent = &samples[samples.len().saturating_sub(SETTLE_SAMPLES)..];
+    let mean = recent.iter().map(|&r| r as u32).sum::<u32>() / recent.len() as u32;
+    Ok(mean as u16)
+}
+
+/// Run the calibration sweep on a channel.
+///
+/// `on_point` is called after each measurement (for progress output).
+/// The channel is left at the last duty measured; callers should restore
+/// their own settings afterwards.
+pub fn calibrate_channel(
+    kraken: &KrakenZ63,
+    channel: Channel,
+    options: &CalibrationOptions,
+    mut on_point: impl FnMut(CalibrationPoint),
+) -> Result<ChannelCalibration> {
+    let mut points = Vec::new();
+
+    for duty in sweep_duties(channel, options.step) {
+        let rpm = measure_rpm(kraken, channel, duty, options)?;
+        let point = CalibrationPoint { duty, rpm };
+        on_point(point);
+        points.push(point);
+    }
+
+    // If the rotor stalled, step up from the stall point until it starts again
+    let mut start_duty = None;
+    if let Some(stall) = points.iter().filter(|p| p.rpm == 0).map(|p| p.duty).max() {
+        let mut duty = stall;
+        while duty < channel.max_duty() {
+            duty = duty
+                .saturating_add(options.start_step.max(1))
+                .min(channel.max_duty());
+            let rpm = measure_rpm(kraken, channel, duty, options)?;
+            on_point(CalibrationPoint { duty, rpm });
+            if rpm > 0 {
+                start_duty = Some(duty);
+                break;
+            }
+        }
+    }
+
+    Ok(ChannelCalibration::from_points(points, start_duty))
+}
+
+// =============================================================================
+// Degradation Detection
+// =============================================================================
+
+/// Detects a channel running persistently slower than its calibration.
+///
+/// RPM lags duty changes, so a shortfall must persist for several readings
+/// before it is reported. Each degradation is reported once, until the
+/// channel recovers.
+#[derive(Debug, Clone)]
+pub struct DegradationMonitor {
+    /// Shortfall (%) below the calibrated RPM that counts as degraded
+    threshold_pct: f32,
+    /// Consecutive degraded readings required
+    required: u32,
+    count: u32,
+    reported: bool,
+}
+
+impl DegradationMonitor {
+    /// Create a monitor that reports after `required` readings more than
+    /// `threshold_pct` below the calibrated RPM.
+    pub fn new(threshold_pct: f32, required: u32) -> Self {
+        Self {
+            threshold_pct,
+            required: required.max(1),
+            count: 0,
+            reported: false,
+        }
+    }
+
+    /// Feed a reading. Returns the deviation (%) when degradation is first detected.
+    pub fn update(&mut self, calibration: &ChannelCalibration, duty: u8, rpm: u16) -> Option<f32> {
+        let deviation = calibration.deviation(duty, rpm);
+
+        match deviation {
+            Some(d) if d <= -self.threshold_pct => {
+                self.count += 1;
+                if self.count >= self.required && !self.reported {
+                    self.reported = true;
+                    return deviation;
+                }
+            }
+            _ => {
+                self.count = 0;
+                self.reported = false;
+            }
+        }
+
+        None
+    }
+}
+
+impl Default for DegradationMonitor {
+    fn default() -> Self {
+        Self::new(20.0, 5)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sweep_duties() {
+        assert_eq!(sweep_duties(Channel::Fan, 25), vec![100, 75, 50, 25, 0]);
+        assert_eq!(sweep_duties(Channel::Pump, 30), vec![100, 70, 40, 20]);
+    }
+
+    #[test]
+    fn test_is_settled() {
+        assert!(!is_settled(&[1000, 1000], 20));
+        assert!(!is_settled(&[500, 900, 1000, 1100], 20));
+        assert!(is_settled(&[500, 990, 1000, 1005], 20));
+    }
+
+    #[test]
+    fn test_degradation_monitor() {
+        let cal = ChannelCalibration::from_points(
+            vec![
+                CalibrationPoint { duty: 0, rpm: 0 },
+                CalibrationPoint {
+                    duty: 100,
+                    rpm: 2000,
+                },
+            ],
+            Some(10),
+        );
+        let mut monitor = DegradationMonitor::new(20.0, 3);
+
+        // Within tolerance
+        assert_eq!(monitor.update(&cal, 50, 950), None);
+
+        // Must persist before reporting, then reports once
+        assert_eq!(monitor.update(&cal, 50, 700), None);
+        assert_eq!(monitor.update(&cal, 50, 700), None);
+        assert_eq!(monitor.update(&cal, 50, 700).map(f32::round), Some(-30.0));
+        assert_eq!(monitor.update(&cal, 50, 700), None);
+
+        // Recovery re-arms
+        assert_eq!(monitor.update(&cal, 50, 1000), None);
+        monitor.update(&cal, 50, 700);
+        monitor.update(&cal, 50, 700);
+        assert!(monitor.update(&cal, 50, 700).is_some());
+    }
+}
@@ -3,6 +3,8 @@
 //! This module provides the logic for interpolating duty cycles from
 //! temperature curves, supporting both liquid and CPU temperature sources.
 
+use super::curve::Curve;
+
 /// Temperature source for calculating duty cycle
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TempSource {
@@ -33,52 +35,23 @@ impl std::fmt::Display for TempSource {
 /// Interpolate duty cycle from a temperature curve.
 ///
 /// Returns duty percentage (0-100) for the given temperature.
-/// Uses linear interpolation between curve points.
+/// Uses linear interpolation between curve points; see [`Curve`] for the
+/// other interpolations and how duplicate temperatures are merged.
 ///
 /// # Arguments
 /// * `curve` - Slice of (temperature, duty) points
 /// * `temp` - Current temperature in Celsius
 ///
 /// # Returns
-/// Duty cycle percentage (0-100)
+/// Duty cycle percentage (0-100), or 50% if the curve is empty or invalid
 pub fn interpolate_duty(curve: &[(u8, u8)], temp: u8) -> u8 {
-    if curve.is_empty() {
-        return 50; // Default 50% if no curve defined
-    }
-
-    // Sort by temperature (should already be sorted, but ensure consistency)
-    let mut sorted: Vec<_> = curve.to_vec();
-    sorted.sort_by_key(|(t, _)| *t);
-
-    // Below minimum temp → use minimum duty
-    if temp <= sorted[0].0 {
-        return sorted[0].1;
-    }
-
-    // Above maximum temp → use maximum duty
-    if temp >= sorted.last().unwrap().0 {
-        return sorted.last().unwrap().1;
-    }
-
-    // Find the two surrounding points and interpolate
-    for window in sorted.windows(2) {
-        let (t1, d1) = window[0];
-        let (t2, d2) = window[1];
-
-        if temp >= t1 && temp <= t2 {
-            // Linear interpolation
-            let ratio = (temp - t1) as f32 / (t2 - t1) as f32;
-            let duty = d1 as f32 + ratio * (d2 as f32 - d1 as f32);
-            return duty.round() as u8;
-        }
-    }
-
-    50 // Fallback (should not reach here)
+    Curve::linear(curve).map_or(50, |curve| curve.duty(temp))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::protocol::commands::interpolate_profile;
 
     #[test]
     fn test_interpolate_exact_point() {
@@ -115,6 +88,17 @@ mod tests {
         assert_eq!(interpolate_duty(&curve, 50), 50); // Default fallback
     }
 
+    #[test]
+    fn test_duplicate_temperature() {
+        // Merged into the highest duty, as for the device table
+        let curve = vec![(20, 25), (40, 50), (40, 60), (60, 100)];
+        assert_eq!(interpolate_duty(&curve, 40), 60);
+        assert_eq!(interpolate_duty(&curve, 50), 80);
+
+        let profile = interpolate_profile(&[(20, 25), (40, 50), (40, 60), (59, 100)]).unwrap();
+        assert_eq!(profile[20], 60);
+    }
+
     #[test]
     fn test_temp_source_from_str() {
         assert_eq!(TempSource::from("Liquid"), TempSource::Liquid);
@@ -0,0 +1,325 @@
+//! Temperature/duty curves.
+//!
+//! [`Curve`] is the one curve type behind both the 40-point table sent to the
+//! device and software control in the daemons. The points are evaluated with
+//! a selectable [`Interpolation`]; temperatures outside the points hold the
+//! duty of the nearest end point.
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{KrakenError, Result};
+use crate::protocol::{CRITICAL_TEMPERATURE, CURVE_POINTS, MIN_CURVE_TEMP};
+
+/// Bisection steps when solving a Bezier curve for a temperature.
+const BEZIER_ITERATIONS: usize = 32;
+
+/// How a curve is evaluated between its points.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Interpolation {
+    /// Straight lines between points
+    #[default]
+    Linear,
+    /// Hold each point's duty until the next point
+    Step,
+    /// Monotone cubic spline (Fritsch-Carlson): smooth, never overshoots
+    MonotoneCubic,
+    /// One Bezier curve using the points as control points: passes through
+    /// the first and last point and is pulled towards the others
+    Bezier,
+}
+
+impl Interpolation {
+    /// Get interpolation name for display.
+    pub fn name(self) -> &'static str {
+        match self {
+            Interpolation::Linear => "linear",
+            Interpolation::Step => "step",
+            Interpolation::MonotoneCubic => "monotone cubic",
+            Interpolation::Bezier => "bezier",
+        }
+    }
+}
+
+impl std::fmt::Display for Interpolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// A temperature/duty curve with its interpolation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Curve {
+    points: Vec<(u8, u8)>,
+    interpolation: Interpolation,
+}
+
+impl Curve {
+    /// Create a curve from (temperature, duty) points in any order.
+    ///
+    /// Points at the same temperature are merged into one with the highest
+    /// of their duties.
+    ///
+    /// # Errors
+    /// Returns `InvalidProfile` if there are no points or a duty is above 100%.
+    pub fn new(points: &[(u8, u8)], interpolation: Interpolation) -> Result<Self> {
+        if points.is_empty() {
+            return Err(KrakenError::InvalidProfile("Curve cannot be empty".into()));
+        }
+        if let Some(&(temp, duty)) = points.iter().find(|&&(_, duty)| duty > 100) {
+            return Err(KrakenError::InvalidProfile(format!(
+                "Duty {}% at {}°C is above 100%",
+                duty, temp
+            )));
+        }
+
+        let mut sorted = points.to_vec();
+        sorted.sort_by_key(|&(temp, _)| temp);
+        sorted.dedup_by(|point, kept| {
+            let same = point.0 == kept.0;
+            if same {
+                kept.1 = kept.1.max(point.1);
+            }
+            same
+        });
+
+        Ok(Self {
+            points: sorted,
+            interpolation,
+        })
+    }
+
+    /// Create a linearly interpolated curve.
+    pub fn linear(points: &[(u8, u8)]) -> Result<Self> {
+        Self::new(points, Interpolation::Linear)
+    }
+
+    /// The points, sorted by temperature.
+    pub fn points(&self) -> &[(u8, u8)] {
+        &self.points
+    }
+
+    pub fn interpolation(&self) -> Interpolation {
+        self.interpolation
+    }
+
+    /// Unrounded duty at a temperature.
+    pub fn value_at(&self, temp: f32) -> f32 {
+        let (first_t, first_d) = self.points[0];
+        let (last_t, last_d) = self.points[self.points.len() - 1];
+        if temp <= first_t as f32 {
+            return first_d as f32;
+        }
+        if temp >= last_t as f32 {
+            return last_d as f32;
+        }
+
+        match self.interpolation {
+            Interpolation::Linear => linear(&self.points, temp),
+            Interpolation::Step => step(&self.points, temp),
+            Interpolation::MonotoneCubic => monotone_cubic(&self.points, temp),
+            Interpolation::Bezier => bezier(&self.points, temp),
+        }
+    }
+
+    /// Duty (0-100%) at a temperature.
+    pub fn duty(&self, temp: u8) -> u8 {
+        self.value_at(temp as f32).round().clamp(0.0, 100.0) as u8
+    }
+
+    /// The 40-point device table for 20-59°C.
+    ///
+    /// # Errors
+    /// Returns `InvalidTemperature` if a point lies outside 20-59°C.
+    pub fn table(&self) -> Result<[u8; CURVE_POINTS]> {
+        if let Some(&(temp, _)) = self
+            .points
+            .iter()
+            .find(|&&(temp, _)| !(MIN_CURVE_TEMP..=CRITICAL_TEMPERATURE).contains(&temp))
+        {
+            return Err(KrakenError::InvalidTemperature(temp));
+        }
+
+        let mut table = [0; CURVE_POINTS];
+        for (duty, temp) in table.iter_mut().zip(MIN_CURVE_TEMP..=CRITICAL_TEMPERATURE) {
+            *duty = self.duty(temp);
+        }
+        Ok(table)
+    }
+}
+
+// =============================================================================
+// Interpolation
+// =============================================================================
+//
+// Each function gets at least two sorted points and a temperature strictly
+// between the first and last point.
+
+/// Index of the segment containing `temp`.
+fn segment(points: &[(u8, u8)], temp: f32) -> usize {
+    points
+        .windows(2)
+        .position(|w| temp <= w[1].0 as f32)
+        .unwrap_or(points.len() - 2)
+}
+
+fn linear(points: &[(u8, u8)], temp: f32) -> f32 {
+    let k = segment(points, temp);
+    let ((t1, d1), (t2, d2)) = (points[k], points[k + 1]);
+    let ratio = (temp - t1 as f32) / (t2 - t1) as f32;
+    d1 as f32 + ratio * (d2 as f32 - d1 as f32)
+}
+
+fn step(points: &[(u8, u8)], temp: f32) -> f32 {
+    points
+        .iter()
+        .rev()
+        .find(|&&(t, _)| t as f32 <= temp)
+        .map_or(points[0].1, |&(_, duty)| duty) as f32
+}
+
+/// Fritsch-Carlson monotone cubic: tangents are limited so the curve never
+/// leaves the range of the two points around it.
+fn monotone_cubic(points: &[(u8, u8)], temp: f32) -> f32 {
+    let xs: Vec<f32> = points.iter().map(|&(t, _)| t as f32).collect();
+    let ys: Vec<f32> = points.iter().map(|&(_, d)| d as f32).collect();
+    let n = xs.len();
+
+    let slopes: Vec<f32> = (0..n - 1)
+        .map(|k| (ys[k + 1] - ys[k]) / (xs[k + 1] - xs[k]))
+        .collect();
+
+    let mut tangents = vec![0.0; n];
+    tangents[0] = slopes[0];
+    tangents[n - 1] = slopes[n - 2];
+    for k in 1..n - 1 {
+        if slopes[k - 1] * slopes[k] > 0.0 {
+            tangents[k] = (slopes[k - 1] + slopes[k]) / 2.0;
+        }
+    }
+    for k in 0..n - 1 {
+        if slopes[k] == 0.0 {
+            tangents[k] = 0.0;
+            tangents[k + 1] = 0.0;
+            continue;
+        }
+        let a = tangents[k] / slopes[k];
+        let b = tangents[k + 1] / slopes[k];
+        let s = a * a + b * b;
+        if s > 9.0 {
+            let tau = 3.0 / s.sqrt();
+            tangents[k] = tau * a * slopes[k];
+            tangents[k + 1] = tau * b * slopes[k];
+        }
+    }
+
+    let k = segment(points, temp);
+    let h = xs[k + 1] - xs[k];
+    let s = (temp - xs[k]) / h;
+    let (s2, s3) = (s * s, s * s * s);
+
+    (2.0 * s3 - 3.0 * s2 + 1.0) * ys[k]
+        + (s3 - 2.0 * s2 + s) * h * tangents[k]
+        + (-2.0 * s3 + 3.0 * s2) * ys[k + 1]
+        + (s3 - s2) * h * tangents[k + 1]
+}
+
+/// Point on the Bezier curve at parameter `t` (de Casteljau).
+fn bezier_point(points: &[(u8, u8)], t: f32) -> (f32, f32) {
+    let mut work: Vec<(f32, f32)> = points.iter().map(|&(x, y)| (x as f32, y as f32)).collect();
+    for level in 1..work.len() {
+        for i in 0..work.len() - level {
+            work[i] = (
+                work[i].0 + t * (work[i + 1].0 - work[i].0),
+                work[i].1 + t * (work[i + 1].1 - work[i].1),
+            );
+        }
+    }
+    work[0]
+}
+
+/// Bezier curve through the points as control points. Temperatures strictly
+/// increase along the control points, so x(t) is monotone and can be solved
+/// by bisection.
+fn bezier(points: &[(u8, u8)], temp: f32) -> f32 {
+    let (mut lo, mut hi) = (0.0f32, 1.0f32);
+    for _ in 0..BEZIER_ITERATIONS {
+        let mid = (lo + hi) / 2.0;
+        if bezier_point(points, mid).0 < temp {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    bezier_point(points, (lo + hi) / 2.0).1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POINTS: [(u8, u8); 5] = [(20, 25), (40, 25), (45, 50), (55, 80), (59, 100)];
+
+    fn table(interpolation: Interpolation) -> [u8; CURVE_POINTS] {
+        Curve::new(&POINTS, interpolation).unwrap().table().unwrap()
+    }
+
+    #[test]
+    fn test_points_are_hit() {
+        for interpolation in [
+            Interpolation::Linear,
+            Interpolation::Step,
+            Interpolation::MonotoneCubic,
+        ] {
+            let table = table(interpolation);
+            for (temp, duty) in POINTS {
+                assert_eq!(table[(temp - 20) as usize], duty, "{}", interpolation);
+            }
+        }
+
+        // Bezier only passes through the end points
+        let bezier = table(Interpolation::Bezier);
+        assert_eq!((bezier[0], bezier[CURVE_POINTS - 1]), (25, 100));
+    }
+
+    #[test]
+    fn test_interpolations_differ() {
+        let curve = |interpolation| Curve::new(&POINTS, interpolation).unwrap();
+
+        assert_eq!(curve(Interpolation::Linear).duty(50), 65);
+        assert_eq!(curve(Interpolation::Step).duty(50), 50);
+        assert_eq!(curve(Interpolation::Step).duty(54), 50);
+        // Flat 20-40°C section stays flat with the spline, not with Bezier
+        assert_eq!(curve(Interpolation::MonotoneCubic).duty(35), 25);
+        assert!(curve(Interpolation::Bezier).duty(35) > 25);
+    }
+
+    #[test]
+    fn test_no_overshoot() {
+        for interpolation in [Interpolation::MonotoneCubic, Interpolation::Bezier] {
+            let table = table(interpolation);
+            assert!(table.windows(2).all(|w| w[0] <= w[1]), "{}", interpolation);
+            assert!(table.iter().all(|&d| (25..=100).contains(&d)));
+        }
+    }
+
+    #[test]
+    fn test_duplicate_temperatures_merge() {
+        let curve = Curve::linear(&[(40, 60), (20, 25), (40, 50), (60, 100)]).unwrap();
+        assert_eq!(curve.points(), &[(20, 25), (40, 60), (60, 100)]);
+        assert_eq!(curve.duty(50), 80);
+    }
+
+    #[test]
+    fn test_invalid_curves() {
+        assert!(Curve::linear(&[]).is_err());
+        assert!(Curve::linear(&[(30, 140)]).is_err());
+        // Outside the device range: fine for software control, not for the table
+        let wide = Curve::linear(&[(10, 20), (70, 100)]).unwrap();
+        assert_eq!(wide.duty(40), 60);
+        assert!(matches!(
+            wide.table(),
+            Err(KrakenError::InvalidTemperature(10))
+        ));
+    }
+}
@@ -0,0 +1,878 @@
+//! Profile expression language.
+//!
+//! A one-line way to write a cooling profile, usable anywhere a profile name
+//! is accepted (`--profile`, `startup.cooling_profile`, schedules, process
+//! rules) and as a channel `expression` in defaults.json:
+//!
+//! ```text
+//! spline(20:30,40:50,59:100)
+//! max(cpu,liquid)+offset(5)+curve(20:30,59:100)+clamp(min=30)
+//! pump=curve(20:70,59:100); fan=step(20:0,40:40,50:70,59:100)
+//! ```
+//!
+//! An expression is a `+`-separated list of terms with exactly one curve:
+//!
+//! | Term | Meaning |
+//! |------|---------|
+//! | `curve(T:D,...)` | Linear interpolation between points (20-59°C, 0-100%) |
+//! | `step(T:D,...)` | Hold each point's duty until the next point |
+//! | `spline(T:D,...)` | Monotone cubic spline (no overshoot between points) |
+//! | `bezier(T:D,...)` | Bezier curve with the points as control points |
+//! | `silent`, `performance` | Built-in curves for the channel |
+//! | `fixed(D)` | Same duty at every temperature |
+//! | `liquid`, `cpu`, `max(...)`, `min(...)` | Temperature source (default: the daemon's `--source`) |
+//! | `offset(N)` | Add N °C to the source temperature before the curve |
+//! | `clamp(min=D,max=D)` | Limit the resulting duty |
+//!
+//! Without `pump=`/`fan=` prefixes the expression drives both channels. Pump
+//! duties are always raised to the pump minimum.
+
+use std::fmt;
+
+use super::controller::TempSource;
+use super::curve::{Curve, Interpolation};
+use crate::config::{
+    PROFILE_PERFORMANCE, PROFILE_PUMP_PERFORMANCE, PROFILE_PUMP_SILENT, PROFILE_SILENT,
+};
+use crate::error::{KrakenError, Result};
+use crate::protocol::{CRITICAL_TEMPERATURE, CURVE_POINTS, Channel, MIN_CURVE_TEMP};
+
+/// Largest temperature offset accepted by `offset(N)` (°C).
+const MAX_OFFSET: i16 = 50;
+
+// =============================================================================
+// Syntax Tree
+// =============================================================================
+
+/// Expression keyword for an interpolation.
+fn keyword(interpolation: Interpolation) -> &'static str {
+    match interpolation {
+        Interpolation::Linear => "curve",
+        Interpolation::Step => "step",
+        Interpolation::MonotoneCubic => "spline",
+        Interpolation::Bezier => "bezier",
+    }
+}
+
+/// The curve of a channel expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CurveExpr {
+    /// Explicit points with their interpolation
+    Curve(Curve),
+    /// Built-in Silent curve
+    Silent,
+    /// Built-in Performance curve
+    Performance,
+    /// Same duty at every temperature
+    Fixed(u8),
+}
+
+/// Temperature input of a channel expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SourceExpr {
+    Liquid,
+    Cpu,
+    /// Hottest of several sources
+    Max(Vec<SourceExpr>),
+    /// Coolest of several sources
+    Min(Vec<SourceExpr>),
+}
+
+/// Temperatures available to expressions; `None` if a sensor is missing.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Temperatures {
+    pub liquid: Option<f32>,
+    pub cpu: Option<f32>,
+}
+
+impl SourceExpr {
+    /// Read this source, or `None` if a sensor it needs is missing.
+    pub fn temperature(&self, temps: &Temperatures) -> Option<f32> {
+        match self {
+            SourceExpr::Liquid => temps.liquid,
+            SourceExpr::Cpu => temps.cpu,
+            SourceExpr::Max(sources) => sources
+                .iter()
+                .map(|s| s.temperature(temps))
+                .try_fold(f32::MIN, |acc, t| t.map(|t| acc.max(t))),
+            SourceExpr::Min(sources) => sources
+                .iter()
+                .map(|s| s.temperature(temps))
+                .try_fold(f32::MAX, |acc, t| t.map(|t| acc.min(t))),
+        }
+    }
+
+    fn is_liquid(&self) -> bool {
+        match self {
+            SourceExpr::Liquid => true,
+            SourceExpr::Cpu => false,
+            SourceExpr::Max(sources) | SourceExpr::Min(sources) => {
+                sources.iter().all(SourceExpr::is_liquid)
+            }
+        }
+    }
+}
+
+impl From<TempSource> for SourceExpr {
+    fn from(source: TempSource) -> Self {
+        match source {
+            TempSource::Liquid => SourceExpr::Liquid,
+            TempSource::Cpu => SourceExpr::Cpu,
+        }
+    }
+}
+
+/// Expression for one channel: source, offset, curve and output clamp.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChannelExpr {
+    pub curve: CurveExpr,
+    /// Temperature source; `None` uses the caller's default source
+    pub source: Option<SourceExpr>,
+    /// Added to the source temperature (°C)
+    pub offset: i16,
+    pub clamp_min: Option<u8>,
+    pub clamp_max: Option<u8>,
+}
+
+impl ChannelExpr {
+    /// Expression with only a curve.
+    pub fn from_curve(curve: CurveExpr) -> Self {
+        Self {
+            curve,
+            source: None,
+            offset: 0,
+            clamp_min: None,
+            clamp_max: None,
+        }
+    }
+
+    /// True if this is just a curve (no source, offset or clamp).
+    pub fn is_curve_only(&self) -> bool {
+        self.source.is_none()
+            && self.offset == 0
+            && self.clamp_min.is_none()
+            && self.clamp_max.is_none()
+    }
+
+    /// The curve's points for a channel (built-in curves depend on the channel).
+    pub fn points(&self, channel: Channel) -> Vec<(u8, u8)> {
+        match (&self.curve, channel) {
+            (CurveExpr::Curve(curve), _) => curve.points().to_vec(),
+            (CurveExpr::Silent, Channel::Pump) => PROFILE_PUMP_SILENT.to_vec(),
+            (CurveExpr::Silent, Channel::Fan) => PROFILE_SILENT.to_vec(),
+            (CurveExpr::Performance, Channel::Pump) => PROFILE_PUMP_PERFORMANCE.to_vec(),
+            (CurveExpr::Performance, Channel::Fan) => PROFILE_PERFORMANCE.to_vec(),
+            (CurveExpr::Fixed(duty), _) => vec![(MIN_CURVE_TEMP, *duty)],
+        }
+    }
+
+    /// Source temperature plus offset, or `None` if a sensor is missing.
+    pub fn input_temperature(&self, temps: &Temperatures, default: TempSource) -> Option<f32> {
+        let temp = match &self.source {
+            Some(source) => source.temperature(temps)?,
+            None => SourceExpr::from(default).temperature(temps)?,
+        };
+        Some(temp + self.offset as f32)
+    }
+
+    /// Duty at a curve input temperature (offset already applied).
+    pub fn duty_at(&self, channel: Channel, temp: u8) -> u8 {
+        let duty = match &self.curve {
+            CurveExpr::Fixed(duty) => *duty,
+            CurveExpr::Curve(curve) => curve.duty(temp),
+            CurveExpr::Silent | CurveExpr::Performance => Curve::linear(&self.points(channel))
+                .expect("built-in curves are valid")
+                .duty(temp),
+        };
+
+        let duty = duty
+            .max(self.clamp_min.unwrap_or(0))
+            .min(self.clamp_max.unwrap_or(100));
+        duty.clamp(channel.min_duty(), channel.max_duty())
+    }
+
+    /// Duty for the current temperatures, or `None` if a sensor is missing.
+    pub fn duty(&self, channel: Channel, temps: &Temperatures, default: TempSource) -> Option<u8> {
+        let temp = self.input_temperature(temps, default)?;
+        Some(self.duty_at(channel, temp.round().clamp(0.0, 255.0) as u8))
+    }
+
+    /// Duties at source temperatures 20-59°C (offset applied).
+    pub fn table(&self, channel: Channel) -> [u8; CURVE_POINTS] {
+        let mut table = [0; CURVE_POINTS];
+        for (duty, temp) in table.iter_mut().zip(MIN_CURVE_TEMP..=CRITICAL_TEMPERATURE) {
+            let input = (temp as i16 + self.offset).clamp(0, 255) as u8;
+            *duty = self.duty_at(channel, input);
+        }
+        table
+    }
+
+    /// The 40-point table for the device's own liquid-temperature curve.
+    ///
+    /// # Errors
+    /// Returns `InvalidProfile` if the expression reads a source other than
+    /// the liquid temperature, which the device cannot follow by itself.
+    pub fn device_table(&self, channel: Channel) -> Result<[u8; CURVE_POINTS]> {
+        if self.source.as_ref().is_some_and(|s| !s.is_liquid()) {
+            return Err(KrakenError::InvalidProfile(format!(
+                "'{}' reads a non-liquid temperature; the device curve can only follow the \
+                 liquid temperature (use cooling-daemon or start instead)",
+                self
+            )));
+        }
+        Ok(self.table(channel))
+    }
+}
+
+/// Channel expressions of a profile; a channel without one is not controlled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProfileExpr {
+    pub pump: Option<ChannelExpr>,
+    pub fan: Option<ChannelExpr>,
+}
+
+impl ProfileExpr {
+    /// The expression for a channel.
+    pub fn channel(&self, channel: Channel) -> Option<&ChannelExpr> {
+        match channel {
+            Channel::Pump => self.pump.as_ref(),
+            Channel::Fan => self.fan.as_ref(),
+        }
+    }
+}
+
+// =============================================================================
+// Formatting
+// =============================================================================
+
+fn write_points(f: &mut fmt::Formatter<'_>, points: &[(u8, u8)]) -> fmt::Result {
+    for (i, (temp, duty)) in points.iter().enumerate() {
+        if i > 0 {
+            write!(f, ",")?;
+        }
+        write!(f, "{}:{}", temp, duty)?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for SourceExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (name, sources) = match self {
+            SourceExpr::Liquid => return write!(f, "liquid"),
+            SourceExpr::Cpu => return write!(f, "cpu"),
+            SourceExpr::Max(sources) => ("max", sources),
+            SourceExpr::Min(sources) => ("min", sources),
+        };
+        write!(f, "{}(", name)?;
+        for (i, source) in sources.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{}", source)?;
+        }
+        write!(f, ")")
+    }
+}
+
+impl fmt::Display for CurveExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CurveExpr::Curve(curve) => {
+                write!(f, "{}(", keyword(curve.interpolation()))?;
+                write_points(f, curve.points())?;
+                write!(f, ")")
+            }
+            CurveExpr::Silent => write!(f, "silent"),
+            CurveExpr::Performance => write!(f, "performance"),
+            CurveExpr::Fixed(duty) => write!(f, "fixed({})", duty),
+        }
+    }
+}
+
+impl fmt::Display for ChannelExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(source) = &self.source {
+            write!(f, "{}+", source)?;
+        }
+        if self.offset != 0 {
+            write!(f, "offset({})+", self.offset)?;
+        }
+        write!(f, "{}", self.curve)?;
+        match (self.clamp_min, self.clamp_max) {
+            (Some(min), Some(max)) => write!(f, "+clamp(min={},max={})", min, max),
+            (Some(min), None) => write!(f, "+clamp(min={})", min),
+            (None, Some(max)) => write!(f, "+clamp(max={})", max),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for ProfileExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.pump, &self.fan) {
+            (Some(pump), Some(fan)) if pump == fan => write!(f, "{}", pump),
+            (Some(pump), Some(fan)) => write!(f, "pump={}; fan={}", pump, fan),
+            (Some(pump), None) => write!(f, "pump={}", pump),
+            (None, Some(fan)) => write!(f, "fan={}", fan),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+// =============================================================================
+// Parse Errors
+// =============================================================================
+
+/// Expression syntax error, pointing at the offending column.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// 1-based column of the error
+    pub column: usize,
+    pub message: String,
+    input: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} at column {}", self.message, self.column)?;
+        writeln!(f, "  {}", self.input)?;
+        write!(f, "  {}^", " ".repeat(self.column - 1))
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl From<ParseError> for KrakenError {
+    fn from(e: ParseError) -> Self {
+        KrakenError::InvalidProfile(e.to_string())
+    }
+}
+
+// =============================================================================
+// Lexer
+// =============================================================================
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Ident(String),
+    Number(u32),
+    Symbol(char),
+    End,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Ident(name) => write!(f, "'{}'", name),
+            Token::Number(n) => write!(f, "'{}'", n),
+            Token::Symbol(c) => write!(f, "'{}'", c),
+            Token::End => write!(f, "end of input"),
+        }
+    }
+}
+
+/// Token and its 1-based column.
+type Spanned = (Token, usize);
+
+fn tokenize(input: &str) -> std::result::Result<Vec<Spanned>, ParseError> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let column = i + 1;
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let ident: String = chars[start..i].iter().collect();
+            tokens.push((Token::Ident(ident.to_lowercase()), column));
+        } else if c.is_ascii_digit() {
+            let start = i;
+            while i < chars.len() && chars[i].is_ascii_digit() {
+                i += 1;
+            }
+            let digits: String = chars[start..i].iter().collect();
+            let value = digits.parse().map_err(|_| ParseError {
+                column,
+                message: format!("number '{}' is too large", digits),
+                input: input.to_string(),
+            })?;
+            tokens.push((Token::Number(value), column));
+        } else if "():,+-=;".contains(c) {
+            tokens.push((Token::Symbol(c), column));
+            i += 1;
+        } else {
+            return Err(ParseError {
+                column,
+                message: format!("unexpected character '{}'", c),
+                input: input.to_string(),
+            });
+        }
+    }
+
+    tokens.push((Token::End, chars.len() + 1));
+    Ok(tokens)
+}
+
+// =============================================================================
+// Parser
+// =============================================================================
+
+struct Parser<'a> {
+    input: &'a str,
+    tokens: Vec<Spanned>,
+    pos: usize,
+}
+
+type ParseResult<T> = std::result::Result<T, ParseError>;
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a str) -> ParseResult<Self> {
+        Ok(Self {
+            input,
+            tokens: tokenize(input)?,
+            pos: 0,
+        })
+    }
+
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos].0
+    }
+
+    fn peek_at(&self, offset: usize) -> &Token {
+        let index = (self.pos + offset).min(self.tokens.len() - 1);
+        &self.tokens[index].0
+    }
+
+    fn column(&self) -> usize {
+        self.tokens[self.pos].1
+    }
+
+    fn next(&mut self) -> Spanned {
+        let token = self.tokens[self.pos].clone();
+        if self.pos < self.tokens.len() - 1 {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn error_at(&self, column: usize, message: impl Into<String>) -> ParseError {
+        ParseError {
+            column,
+            message: message.into(),
+            input: self.input.to_string(),
+        }
+    }
+
+    fn unexpected(&self, expected: &str) -> ParseError {
+        self.error_at(
+            self.column(),
+            format!("expected {}, found {}", expected, self.peek()),
+        )
+    }
+
+    fn eat(&mut self, symbol: char) -> bool {
+        if *self.peek() == Token::Symbol(symbol) {
+            self.next();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, symbol: char) -> ParseResult<()> {
+        if self.eat(symbol) {
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("'{}'", symbol)))
+        }
+    }
+
+    fn number(&mut self, what: &str, max: u32) -> ParseResult<(u32, usize)> {
+        match self.next() {
+            (Token::Number(n), column) if n <= max => Ok((n, column)),
+            (Token::Number(n), column) => Err(self.error_at(
+                column,
+                format!("{} {} is above the maximum of {}", what, n, max),
+            )),
+            (token, column) => {
+                Err(self.error_at(column, format!("expected {}, found {}", what, token)))
+            }
+        }
+    }
+
+    fn duty(&mut self) -> ParseResult<u8> {
+        Ok(self.number("duty", 100)?.0 as u8)
+    }
+
+    fn profile(&mut self) -> ParseResult<ProfileExpr> {
+        let assigned = matches!(self.peek(), Token::Ident(name) if name == "pump" || name == "fan")
+            && *self.peek_at(1) == Token::Symbol('=');
+
+        let profile = if assigned {
+            let mut profile = ProfileExpr {
+                pump: None,
+                fan: None,
+            };
+            loop {
+                let (token, column) = self.next();
+                let slot = match &token {
+                    Token::Ident(name) if name == "pump" => &mut profile.pump,
+                    Token::Ident(name) if name == "fan" => &mut profile.fan,
+                    _ => {
+                        return Err(self.error_at(
+                            column,
+                            format!("expected 'pump' or 'fan', found {}", token),
+                        ));
+                    }
+                };
+                if slot.is_some() {
+                    return Err(self.error_at(column, format!("{} is given twice", token)));
+                }
+                self.expect('=')?;
+                *slot = Some(self.channel()?);
+
+                if !self.eat(';') || *self.peek() == Token::End {
+                    break;
+                }
+            }
+            profile
+        } else {
+            let channel = self.channel()?;
+            ProfileExpr {
+                pump: Some(channel.clone()),
+                fan: Some(channel),
+            }
+        };
+
+        if *self.peek() != Token::End {
+            return Err(self.unexpected(if assigned { "';' or end" } else { "'+' or end" }));
+        }
+        Ok(profile)
+    }
+
+    fn channel(&mut self) -> ParseResult<ChannelExpr> {
+        let start = self.column();
+        let mut curve = None;
+        let mut source = None;
+        let mut offset = None;
+        let mut clamp: Option<(Option<u8>, Option<u8>)> = None;
+
+        loop {
+            let column = self.column();
+            let Token::Ident(name) = self.peek().clone() else {
+                return Err(self.unexpected("a curve, source, offset(...) or clamp(...)"));
+            };
+
+            let duplicate = match name.as_str() {
+                "curve" | "step" | "spline" | "bezier" | "silent" | "performance" | "fixed" => {
+                    curve.replace(self.curve()?).is_some()
+                }
+                "liquid" | "cpu" | "max" | "min" => source.replace(self.source()?).is_some(),
+                "offset" => offset.replace(self.offset()?).is_some(),
+                "clamp" => clamp.replace(self.clamp()?).is_some(),
+                _ => {
+                    return Err(self.error_at(
+                        column,
+                        format!(
+                            "unknown term '{}'; expected curve, step, spline, bezier, silent, \
+                             performance, fixed, liquid, cpu, max, min, offset or clamp",
+                            name
+                        ),
+                    ));
+                }
+            };
+            if duplicate {
+                let what = match name.as_str() {
+                    "offset" | "clamp" => name.as_str(),
+                    "liquid" | "cpu" | "max" | "min" => "temperature source",
+                    _ => "curve",
+                };
+                return Err(self.error_at(column, format!("second {} in one expression", what)));
+            }
+
+            if !self.eat('+') {
+                break;
+            }
+        }
+
+        let Some(curve) = curve else {
+            return Err(self.error_at(
+                start,
+                "expression has no curve; add curve(...), step(...), spline(...), \
+                 bezier(...), silent, performance or fixed(...)",
+            ));
+        };
+        let (clamp_min, clamp_max) = clamp.unwrap_or_default();
+        Ok(ChannelExpr {
+            curve,
+            source,
+            offset: offset.unwrap_or(0),
+            clamp_min,
+            clamp_max,
+        })
+    }
+
+    fn curve(&mut self) -> ParseResult<CurveExpr> {
+        let (token, _) = self.next();
+        let interpolation = match &token {
+            Token::Ident(name) if name == "silent" => return Ok(CurveExpr::Silent),
+            Token::Ident(name) if name == "performance" => return Ok(CurveExpr::Performance),
+            Token::Ident(name) if name == "fixed" => {
+                self.expect('(')?;
+                let duty = self.duty()?;
+                self.expect(')')?;
+                return Ok(CurveExpr::Fixed(duty));
+            }
+            Token::Ident(name) if name == "step" => Interpolation::Step,
+            Token::Ident(name) if name == "spline" => Interpolation::MonotoneCubic,
+            Token::Ident(name) if name == "bezier" => Interpolation::Bezier,
+            _ => Interpolation::Linear,
+        };
+        let start = self.column();
+
+        self.expect('(')?;
+        let mut points: Vec<(u8, u8)> = Vec::new();
+        loop {
+            let (temp, column) = self.number("temperature", CRITICAL_TEMPERATURE as u32)?;
+            let temp = temp as u8;
+            if temp < MIN_CURVE_TEMP {
+                return Err(self.error_at(
+                    column,
+                    format!(
+                        "temperature {}°C is below the curve start of {}°C",
+                        temp, MIN_CURVE_TEMP
+                    ),
+                ));
+            }
+            if points.iter().any(|&(t, _)| t == temp) {
+                return Err(self.error_at(column, format!("temperature {}°C given twice", temp)));
+            }
+            self.expect(':')?;
+            points.push((temp, self.duty()?));
+
+            if !self.eat(',') {
+                break;
+            }
+        }
+        self.expect(')')?;
+
+        Curve::new(&points, interpolation)
+            .map(CurveExpr::Curve)
+            .map_err(|e| self.error_at(start, e.to_string()))
+    }
+
+    fn source(&mut self) -> ParseResult<SourceExpr> {
+        let (token, column) = self.next();
+        let combine: fn(Vec<SourceExpr>) -> SourceExpr = match &token {
+            Token::Ident(name) if name == "liquid" => return Ok(SourceExpr::Liquid),
+            Token::Ident(name) if name == "cpu" => return Ok(SourceExpr::Cpu),
+            Token::Ident(name) if name == "max" => SourceExpr::Max,
+            Token::Ident(name) if name == "min" => SourceExpr::Min,
+            _ => {
+                return Err(self.error_at(
+                    column,
+                    format!(
+                        "expected liquid, cpu, max(...) or min(...), found {}",
+                        token
+                    ),
+                ));
+            }
+        };
+
+        self.expect('(')?;
+        let mut sources = vec![self.source()?];
+        while self.eat(',') {
+            sources.push(self.source()?);
+        }
+        self.expect(')')?;
+        Ok(combine(sources))
+    }
+
+    fn offset(&mut self) -> ParseResult<i16> {
+        self.next();
+        self.expect('(')?;
+        let negative = self.eat('-');
+        if !negative {
+            self.eat('+');
+        }
+        let (value, _) = self.number("offset", MAX_OFFSET as u32)?;
+        self.expect(')')?;
+        Ok(if negative {
+            -(value as i16)
+        } else {
+            value as i16
+        })
+    }
+
+    fn clamp(&mut self) -> ParseResult<(Option<u8>, Option<u8>)> {
+        let (_, start) = self.next();
+        self.expect('(')?;
+        let (mut min, mut max) = (None, None);
+        loop {
+            let (token, column) = self.next();
+            let slot = match &token {
+                Token::Ident(name) if name == "min" => &mut min,
+                Token::Ident(name) if name == "max" => &mut max,
+                _ => {
+                    return Err(
+                        self.error_at(column, format!("expected 'min' or 'max', found {}", token))
+                    );
+                }
+            };
+            if slot.is_some() {
+                return Err(self.error_at(column, format!("{} is given twice", token)));
+            }
+            self.expect('=')?;
+            *slot = Some(self.duty()?);
+
+            if !self.eat(',') {
+                break;
+            }
+        }
+        self.expect(')')?;
+
+        if let (Some(lo), Some(hi)) = (min, max)
+            && lo > hi
+        {
+            return Err(self.error_at(start, format!("clamp min {}% is above max {}%", lo, hi)));
+        }
+        Ok((min, max))
+    }
+}
+
+/// Returns true if `text` is a profile expression rather than a profile name.
+pub fn is_expression(text: &str) -> bool {
+    text.contains(['(', '=', '+'])
+}
+
+/// Parse a profile expression.
+///
+/// Without `pump=`/`fan=` prefixes the expression is used for both channels.
+///
+/// # Example
+/// ```
+/// use nzxt_rust_devices::cooling::expression::parse_profile_expr;
+///
+/// let profile = parse_profile_expr("pump=fixed(70); fan=spline(20:30,59:100)").unwrap();
+/// assert_eq!(profile.to_string(), "pump=fixed(70); fan=spline(20:30,59:100)");
+///
+/// let err = parse_profile_expr("curve(20:30,40 50)").unwrap_err();
+/// assert_eq!(err.column, 16);
+/// ```
+pub fn parse_profile_expr(input: &str) -> std::result::Result<ProfileExpr, ParseError> {
+    Parser::new(input)?.profile()
+}
+
+/// Parse an expression for a single channel (no `pump=`/`fan=` prefix).
+pub fn parse_channel_expr(input: &str) -> std::result::Result<ChannelExpr, ParseError> {
+    let mut parser = Parser::new(input)?;
+    let channel = parser.channel()?;
+    if *parser.peek() != Token::End {
+        return Err(parser.unexpected("'+' or end"));
+    }
+    Ok(channel)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_format() {
+        let expr = parse_channel_expr(
+            "MAX(cpu, liquid) + offset(5) + curve(40:50, 20:30, 59:100) + clamp(min=30)",
+        )
+        .unwrap();
+        assert_eq!(
+            expr.source,
+            Some(SourceExpr::Max(vec![SourceExpr::Cpu, SourceExpr::Liquid]))
+        );
+        assert_eq!(expr.offset, 5);
+        assert_eq!(expr.clamp_min, Some(30));
+        assert_eq!(
+            expr.points(Channel::Fan),
+            vec![(20, 30), (40, 50), (59, 100)]
+        );
+        // Canonical form parses back to the same expression
+        let text = expr.to_string();
+        assert_eq!(
+            text,
+            "max(cpu,liquid)+offset(5)+curve(20:30,40:50,59:100)+clamp(min=30)"
+        );
+        assert_eq!(parse_channel_expr(&text).unwrap(), expr);
+
+        let profile = parse_profile_expr("pump=silent; fan=offset(-3)+fixed(40);").unwrap();
+        assert_eq!(profile.pump.unwrap().curve, CurveExpr::Silent);
+        assert_eq!(profile.fan.unwrap().offset, -3);
+
+        let both = parse_profile_expr("step(20:30,50:80)").unwrap();
+        assert_eq!(both.pump, both.fan);
+    }
+
+    #[test]
+    fn test_parse_errors_point_at_column() {
+        let column = |input: &str| parse_profile_expr(input).unwrap_err().column;
+
+        assert_eq!(column("curve(20:30,40 50)"), 16); // missing ':'
+        assert_eq!(column("curve(20:30,70:100)"), 13); // temperature range
+        assert_eq!(column("curve(20:30,20:40)"), 13); // duplicate temperature
+        assert_eq!(column("curve(20:130)"), 10); // duty range
+        assert_eq!(column("cpu+offset(5)"), 1); // no curve
+        assert_eq!(column("silent+spline(20:30)"), 8); // second curve
+        assert_eq!(column("silent+gpu"), 8); // unknown term
+        assert_eq!(column("silent+clamp(min=60,max=40)"), 8);
+        assert_eq!(column("pump=silent; pump=fixed(60)"), 14);
+        assert_eq!(column("silent)"), 7);
+        assert_eq!(column("silent # note"), 8);
+
+        let message = parse_profile_expr("curve(20:30,40 50)")
+            .unwrap_err()
+            .to_string();
+        assert!(message.contains("expected ':', found '50' at column 16"));
+        assert!(message.ends_with(&format!("  {}^", " ".repeat(15))));
+    }
+
+    #[test]
+    fn test_evaluation() {
+        let temps = Temperatures {
+            liquid: Some(30.0),
+            cpu: Some(52.4),
+        };
+        let expr = parse_channel_expr("max(cpu,liquid)+offset(5)+step(20:10,40:40,55:80)").unwrap();
+        // 52.4 + 5 rounds to 57°C, holding the 55°C step
+        assert_eq!(
+            expr.duty(Channel::Fan, &temps, TempSource::Liquid),
+            Some(80)
+        );
+        // Pump duty raised to the pump minimum
+        assert_eq!(expr.duty_at(Channel::Pump, 20), 20);
+        // Missing sensor
+        let no_cpu = Temperatures { cpu: None, ..temps };
+        assert_eq!(expr.duty(Channel::Fan, &no_cpu, TempSource::Liquid), None);
+        assert!(expr.device_table(Channel::Fan).is_err());
+
+        let clamped =
+            parse_channel_expr("offset(10)+curve(20:20,59:100)+clamp(min=30,max=90)").unwrap();
+        let table = clamped.device_table(Channel::Fan).unwrap();
+        assert_eq!(table[0], 41); // curve at 30°C
+        assert_eq!(table[CURVE_POINTS - 1], 90); // clamped
+        assert_eq!(clamped.duty_at(Channel::Fan, 20), 30);
+    }
+
+    #[test]
+    fn test_spline_is_monotone() {
+        let expr = parse_channel_expr("spline(20:25,45:25,50:55,55:75,58:90,59:100)").unwrap();
+        let table = expr.table(Channel::Fan);
+        assert!(table.windows(2).all(|w| w[0] <= w[1]));
+        // Flat section stays flat, points are hit exactly
+        assert_eq!(table[..26], [25; 26]);
+        assert_eq!(table[35], 75);
+        assert_eq!(table[CURVE_POINTS - 1], 100);
+    }
+}
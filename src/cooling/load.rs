@@ -0,0 +1,336 @@
+//! Load-aware feed-forward cooling.
+//!
+//! Temperature lags load, so a curve driven by liquid or CPU temperature ramps
+//! late. This module samples CPU utilisation (`sysinfo`) and package power
+//! (Linux RAPL, `/sys/class/powercap`) and adds duty while the load is above
+//! its recent baseline. The boost decays as the baseline catches up, by which
+//! time the temperature curve has taken over.
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use sysinfo::System;
+
+use crate::protocol::Channel;
+use crate::storage::LoadControlConfig;
+
+const POWERCAP_DIR: &str = "/sys/class/powercap";
+
+// =============================================================================
+// Load Sampling
+// =============================================================================
+
+/// A single load measurement.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LoadSample {
+    /// Global CPU utilisation (0-100%).
+    pub cpu_usage: f32,
+    /// CPU package power in watts, if RAPL is available.
+    pub package_power_w: Option<f32>,
+}
+
+impl LoadSample {
+    /// Combined load in percent: the higher of CPU utilisation and package
+    /// power relative to `tdp_watts` (ignored if 0).
+    pub fn load_percent(&self, tdp_watts: f32) -> f32 {
+        let power = match self.package_power_w {
+            Some(watts) if tdp_watts > 0.0 => watts / tdp_watts * 100.0,
+            _ => 0.0,
+        };
+        self.cpu_usage.max(power).clamp(0.0, 100.0)
+    }
+}
+
+/// Reads package energy counters from a RAPL powercap zone.
+#[derive(Debug)]
+struct RaplReader {
+    energy_path: PathBuf,
+    /// Counter wrap-around value in microjoules.
+    max_energy_uj: u64,
+    last: Option<(u64, Instant)>,
+}
+
+impl RaplReader {
+    fn open(zone: &Path) -> Option<Self> {
+        let energy_path = zone.join("energy_uj");
+        // energy_uj is root-only on recent kernels; check we can read it
+        read_u64(&energy_path)?;
+        Some(Self {
+            energy_path,
+            max_energy_uj: read_u64(&zone.join("max_energy_range_uj")).unwrap_or(u64::MAX),
+            last: None,
+        })
+    }
+
+    /// Average package power since the previous read.
+    fn read_watts(&mut self) -> Option<f32> {
+        let energy = read_u64(&self.energy_path)?;
+        let now = Instant::now();
+        let previous = self.last.replace((energy, now));
+
+        let (last_energy, last_time) = previous?;
+        let elapsed = now.duration_since(last_time).as_secs_f32();
+        if elapsed <= 0.0 {
+            return None;
+        }
+
+        let delta = if energy >= last_energy {
+            energy - last_energy
+        } else {
+            // Counter wrapped
+            self.max_energy_uj - last_energy + energy
+        };
+        Some(delta as f32 / 1_000_000.0 / elapsed)
+    }
+}
+
+fn read_u64(path: &Path) -> Option<u64> {
+    std::fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+/// Find the first RAPL zone whose name starts with "package".
+pub fn find_rapl_package_zone() -> Option<PathBuf> {
+    let mut zones: Vec<PathBuf> = std::fs::read_dir(POWERCAP_DIR)
+        .ok()?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .collect();
+    zones.sort();
+
+    zones.into_iter().find(|zone| {
+        std::fs::read_to_string(zone.join("name"))
+            .map(|name| name.trim().starts_with("package"))
+            .unwrap_or(false)
+    })
+}
+
+/// Samples CPU utilisation and package power.
+pub struct LoadMonitor {
+    system: System,
+    rapl: Option<RaplReader>,
+}
+
+impl LoadMonitor {
+    /// Create a monitor. RAPL is used when a readable package zone exists.
+    pub fn new() -> Self {
+        let mut system = System::new();
+        // CPU usage is a diff between refreshes; prime the first sample
+        system.refresh_cpu_usage();
+
+        let mut rapl = find_rapl_package_zone().and_then(|zone| RaplReader::open(&zone));
+        if let Some(reader) = rapl.as_mut() {
+            reader.read_watts();
+        }
+
+        Self { system, rapl }
+    }
+
+    /// Returns true if package power is being read.
+    pub fn has_package_power(&self) -> bool {
+        self.rapl.is_some()
+    }
+
+    /// Take a new sample.
+    pub fn sample(&mut self) -> LoadSample {
+        self.system.refresh_cpu_usage();
+        LoadSample {
+            cpu_usage: self.system.global_cpu_usage(),
+            package_power_w: self.rapl.as_mut().and_then(|r| r.read_watts()),
+        }
+    }
+}
+
+impl Default for LoadMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// =============================================================================
+// Feed-forward
+// =============================================================================
+
+/// Duty boost per channel from the feed-forward controller.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LoadBoost {
+    /// Combined load (0-100%) used for this boost.
+    pub load: f32,
+    /// Duty % to add to the pump.
+    pub pump: u8,
+    /// Duty % to add to the fan.
+    pub fan: u8,
+}
+
+impl LoadBoost {
+    /// Duty boost for a channel.
+    pub fn for_channel(&self, channel: Channel) -> u8 {
+        match channel {
+            Channel::Pump => self.pump,
+            Channel::Fan => self.fan,
+        }
+    }
+
+    /// Add the channel's boost to a curve duty, clamped to the channel limits.
+    pub fn apply(&self, channel: Channel, duty: u8) -> u8 {
+        duty.saturating_add(self.for_channel(channel))
+            .clamp(channel.min_duty(), channel.max_duty())
+    }
+}
+
+/// Feed-forward controller: boosts duty while load is above a slow baseline.
+#[derive(Debug, Clone)]
+pub struct FeedForward {
+    pump_gain: f32,
+    fan_gain: f32,
+    time_constant: f32,
+    baseline: Option<f32>,
+}
+
+impl FeedForward {
+    /// Create a controller from config.
+    pub fn new(config: &LoadControlConfig) -> Self {
+        Self {
+            pump_gain: config.pump_gain.max(0.0),
+            fan_gain: config.fan_gain.max(0.0),
+            time_constant: config.time_constant_secs.max(0.0),
+            baseline: None,
+        }
+    }
+
+    /// Feed a load measurement taken `dt` after the previous one.
+    ///
+    /// The boost is proportional to the load above the baseline; the baseline
+    /// then moves toward the load with the configured time constant.
+    pub fn update(&mut self, load: f32, dt: Duration) -> LoadBoost {
+        let baseline = *self.baseline.get_or_insert(load);
+        let excess = (load - baseline).max(0.0);
+
+        let alpha = if self.time_constant > 0.0 {
+            1.0 - (-dt.as_secs_f32() / self.time_constant).exp()
+        } else {
+            1.0
+        };
+        self.baseline = Some(baseline + alpha * (load - baseline));
+
+        let boost = |gain: f32| (gain * excess).round().clamp(0.0, 100.0) as u8;
+        LoadBoost {
+            load,
+            pump: boost(self.pump_gain),
+            fan: boost(self.fan_gain),
+        }
+    }
+}
+
+/// Load monitor and feed-forward controller combined, for daemon loops.
+pub struct LoadFeedForward {
+    monitor: LoadMonitor,
+    controller: FeedForward,
+    tdp_watts: f32,
+    last_tick: Instant,
+}
+
+impl LoadFeedForward {
+    /// Create from config.
+    pub fn new(config: &LoadControlConfig) -> Self {
+        Self {
+            monitor: LoadMonitor::new(),
+            controller: FeedForward::new(config),
+            tdp_watts: config.tdp_watts,
+            last_tick: Instant::now(),
+        }
+    }
+
+    /// Returns true if package power is being read.
+    pub fn has_package_power(&self) -> bool {
+        self.monitor.has_package_power()
+    }
+
+    /// Sample the load and compute this tick's boost.
+    pub fn tick(&mut self) -> LoadBoost {
+        let now = Instant::now();
+        let dt = now.duration_since(self.last_tick);
+        self.last_tick = now;
+
+        let load = self.monitor.sample().load_percent(self.tdp_watts);
+        self.controller.update(load, dt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(pump_gain: f32, fan_gain: f32, time_constant_secs: f32) -> LoadControlConfig {
+        LoadControlConfig {
+            enabled: true,
+            pump_gain,
+            fan_gain,
+            time_constant_secs,
+            tdp_watts: 100.0,
+        }
+    }
+
+    #[test]
+    fn test_load_percent() {
+        let sample = LoadSample {
+            cpu_usage: 40.0,
+            package_power_w: Some(80.0),
+        };
+        assert_eq!(sample.load_percent(100.0), 80.0);
+        assert_eq!(sample.load_percent(0.0), 40.0);
+        assert_eq!(sample.load_percent(50.0), 100.0);
+    }
+
+    #[test]
+    fn test_steady_load_no_boost() {
+        let mut ff = FeedForward::new(&config(0.5, 1.0, 30.0));
+        let dt = Duration::from_secs(2);
+        assert_eq!(ff.update(50.0, dt).fan, 0);
+        assert_eq!(ff.update(50.0, dt).fan, 0);
+    }
+
+    #[test]
+    fn test_load_jump_boosts_then_decays() {
+        let mut ff = FeedForward::new(&config(0.5, 1.0, 10.0));
+        let dt = Duration::from_secs(2);
+        ff.update(10.0, dt);
+
+        let jump = ff.update(70.0, dt);
+        assert_eq!(jump.fan, 60);
+        assert_eq!(jump.pump, 30);
+
+        let mut last = jump.fan;
+        for _ in 0..20 {
+            let boost = ff.update(70.0, dt);
+            assert!(boost.fan <= last);
+            last = boost.fan;
+        }
+        assert!(last < 5);
+    }
+
+    #[test]
+    fn test_load_drop_no_boost() {
+        let mut ff = FeedForward::new(&config(0.5, 1.0, 10.0));
+        let dt = Duration::from_secs(2);
+        ff.update(90.0, dt);
+        assert_eq!(
+            ff.update(10.0, dt),
+            LoadBoost {
+                load: 10.0,
+                pump: 0,
+                fan: 0
+            }
+        );
+    }
+
+    #[test]
+    fn test_boost_apply_clamps() {
+        let boost = LoadBoost {
+            load: 100.0,
+            pump: 30,
+            fan: 50,
+        };
+        assert_eq!(boost.apply(Channel::Pump, 80), 100);
+        assert_eq!(boost.apply(Channel::Fan, 20), 70);
+        assert_eq!(LoadBoost::default().apply(Channel::Pump, 0), 20);
+    }
+}
@@ -2,6 +2,30 @@
 //!
 //! Provides temperature-based fan/pump curve interpolation and control logic.
 
+pub mod calibration;
 mod controller;
+pub mod curve;
+pub mod expression;
+pub mod load;
+pub mod process_rules;
+pub mod rpm_target;
+pub mod schedule;
+pub mod simulate;
+pub mod validate;
+pub mod zero_rpm;
 
+pub use calibration::{CalibrationOptions, DegradationMonitor, calibrate_channel};
 pub use controller::{TempSource, interpolate_duty};
+pub use curve::{Curve, Interpolation};
+pub use expression::{
+    ChannelExpr, ParseError, ProfileExpr, Temperatures, parse_channel_expr, parse_profile_expr,
+};
+pub use load::{FeedForward, LoadBoost, LoadFeedForward, LoadMonitor, LoadSample};
+pub use process_rules::{ProcessMatch, ProcessMatcher, ProcessWatcher};
+pub use rpm_target::RpmController;
+pub use schedule::{ScheduleState, Scheduler};
+pub use simulate::{
+    ChannelSummary, SimulationResult, SimulationStep, TraceSample, parse_trace, simulate,
+};
+pub use validate::{Issue, Severity, Validation, validate_channel, validate_profile};
+pub use zero_rpm::{StallDetector, ZeroRpmController};
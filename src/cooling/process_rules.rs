@@ -0,0 +1,326 @@
+//! Process-triggered profile switching.
+//!
+//! Watches running processes (via `sysinfo`) and selects a cooling profile while
+//! a process matching one of the `process_rules` from config.json is running.
+
+use std::time::{Duration, Instant};
+
+use regex::{Regex, RegexBuilder};
+use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
+
+use crate::error::{KrakenError, Result};
+use crate::storage::ProcessRule;
+
+/// A rule currently selected by the watcher.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessMatch {
+    /// Rule name
+    pub rule: String,
+    /// Cooling profile to apply
+    pub profile: String,
+}
+
+/// A validated process rule.
+#[derive(Debug, Clone)]
+struct CompiledProcessRule {
+    name: String,
+    process: Option<Regex>,
+    cmdline: Option<Regex>,
+    profile: String,
+    priority: i32,
+    debounce: Duration,
+}
+
+impl CompiledProcessRule {
+    fn matches(&self, name: &str, cmdline: &str) -> bool {
+        self.process.as_ref().is_none_or(|re| re.is_match(name))
+            && self.cmdline.as_ref().is_none_or(|re| re.is_match(cmdline))
+    }
+}
+
+/// Matches process lists against process rules.
+#[derive(Debug, Clone, Default)]
+pub struct ProcessMatcher {
+    rules: Vec<CompiledProcessRule>,
+}
+
+impl ProcessMatcher {
+    /// Compile config rules.
+    ///
+    /// # Errors
+    /// Returns `InvalidInput` if a rule has no pattern or an invalid regex.
+    pub fn new(rules: &[ProcessRule]) -> Result<Self> {
+        let rules = rules.iter().map(compile_rule).collect::<Result<Vec<_>>>()?;
+        Ok(Self { rules })
+    }
+
+    /// Returns true if no rules are configured.
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Find the highest-priority rule matching any of the given `(name, cmdline)` pairs.
+    ///
+    /// Ties go to the rule listed first. Returns the rule index.
+    pub fn best_match<'a>(
+        &self,
+        processes: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> Option<usize> {
+        let mut matched = vec![false; self.rules.len()];
+        for (name, cmdline) in processes {
+            for (i, rule) in self.rules.iter().enumerate() {
+                if !matched[i] && rule.matches(name, cmdline) {
+                    matched[i] = true;
+                }
+            }
+        }
+
+        // max_by_key returns the last maximum, so iterate in reverse to prefer earlier rules
+        (0..self.rules.len())
+            .rev()
+            .filter(|&i| matched[i])
+            .max_by_key(|&i| self.rules[i].priority)
+    }
+}
+
+/// Polls the process list and reports debounced profile changes.
+pub struct ProcessWatcher {
+    matcher: ProcessMatcher,
+    system: System,
+    /// Currently applied rule index
+    active: Option<usize>,
+    /// Candidate rule index and when it was first seen
+    pending: Option<(Option<usize>, Instant)>,
+}
+
+impl ProcessWatcher {
+    /// Create a watcher for the given rules.
+    ///
+    /// # Errors
+    /// Returns `InvalidInput` if a rule is invalid.
+    pub fn new(rules: &[ProcessRule]) -> Result<Self> {
+        Ok(Self {
+            matcher: ProcessMatcher::new(rules)?,
+            system: System::new(),
+            active: None,
+            pending: None,
+        })
+    }
+
+    /// Returns true if no rules are configured.
+    pub fn is_empty(&self) -> bool {
+        self.matcher.is_empty()
+    }
+
+    /// The rule currently applied, if any.
+    pub fn current(&self) -> Option<ProcessMatch> {
+        self.active.map(|i| {
+            let rule = &self.matcher.rules[i];
+            ProcessMatch {
+                rule: rule.name.clone(),
+                profile: rule.profile.clone(),
+            }
+        })
+    }
+
+    /// Refresh the process list and evaluate the rules.
+    ///
+    /// Returns `Some(new_match)` when the applied rule changed (`Some(None)` means
+    /// the matched processes exited and the daemon should revert).
+    pub fn poll(&mut self) -> Option<Option<ProcessMatch>> {
+        if self.is_empty() {
+            return None;
+        }
+
+        self.system.refresh_processes_specifics(
+            ProcessesToUpdate::All,
+            true,
+            ProcessRefreshKind::nothing().with_cmd(UpdateKind::OnlyIfNotSet),
+        );
+
+        let processes: Vec<(String, String)> = self
+            .system
+            .processes()
+            .values()
+            .map(|p| {
+                let cmdline = p
+                    .cmd()
+                    .iter()
+                    .map(|arg| arg.to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                (p.name().to_string_lossy().into_owned(), cmdline)
+            })
+            .collect();
+
+        let best = self
+            .matcher
+            .best_match(processes.iter().map(|(n, c)| (n.as_str(), c.as_str())));
+        self.update(best, Instant::now())
+    }
+
+    /// Debounce a new best match. A change is applied once it has been stable
+    /// for the longer debounce of the old and new rule.
+    fn update(&mut self, best: Option<usize>, now: Instant) -> Option<Option<ProcessMatch>> {
+        if best == self.active {
+            self.pending = None;
+            return None;
+        }
+
+        let since = match self.pending {
+            Some((target, since)) if target == best => since,
+            _ => {
+                self.pending = Some((best, now));
+                now
+            }
+        };
+
+        let debounce = |idx: Option<usize>| {
+            idx.map(|i| self.matcher.rules[i].debounce)
+                .unwrap_or_default()
+        };
+        let delay = debounce(best).max(debounce(self.active));
+
+        if now.duration_since(since) >= delay {
+            self.active = best;
+            self.pending = None;
+            return Some(self.current());
+        }
+
+        None
+    }
+}
+
+fn compile_rule(rule: &ProcessRule) -> Result<CompiledProcessRule> {
+    if rule.process.is_none() && rule.cmdline.is_none() {
+        return Err(KrakenError::InvalidInput(format!(
+            "Process rule '{}': set 'process' and/or 'cmdline'",
+            rule.name
+        )));
+    }
+
+    let invalid =
+        |e: regex::Error| KrakenError::InvalidInput(format!("Process rule '{}': {}", rule.name, e));
+
+    let process = rule
+        .process
+        .as_deref()
+        .map(glob_to_regex)
+        .transpose()
+        .map_err(invalid)?;
+    let cmdline = rule
+        .cmdline
+        .as_deref()
+        .map(Regex::new)
+        .transpose()
+        .map_err(invalid)?;
+
+    Ok(CompiledProcessRule {
+        name: rule.name.clone(),
+        process,
+        cmdline,
+        profile: rule.profile.clone(),
+        priority: rule.priority,
+        debounce: Duration::from_secs(rule.debounce_secs),
+    })
+}
+
+/// Convert a shell-style glob (`*`, `?`) into an anchored, case-insensitive regex.
+fn glob_to_regex(glob: &str) -> std::result::Result<Regex, regex::Error> {
+    let mut pattern = String::from("^");
+    for c in glob.chars() {
+        match c {
+            '*' => pattern.push_str(".*"),
+            '?' => pattern.push('.'),
+            c => pattern.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    pattern.push('$');
+    RegexBuilder::new(&pattern).case_insensitive(true).build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(
+        name: &str,
+        process: Option<&str>,
+        cmdline: Option<&str>,
+        priority: i32,
+    ) -> ProcessRule {
+        ProcessRule {
+            name: name.into(),
+            process: process.map(String::from),
+            cmdline: cmdline.map(String::from),
+            profile: format!("{}-profile", name),
+            priority,
+            debounce_secs: 0,
+        }
+    }
+
+    #[test]
+    fn test_glob_matching() {
+        let matcher = ProcessMatcher::new(&[rule("steam", Some("steam_app_*"), None, 0)]).unwrap();
+        assert_eq!(matcher.best_match([("steam_app_570", "")]), Some(0));
+        assert_eq!(matcher.best_match([("Steam_App_1", "")]), Some(0));
+        assert_eq!(matcher.best_match([("steam", "")]), None);
+    }
+
+    #[test]
+    fn test_cmdline_regex() {
+        let matcher =
+            ProcessMatcher::new(&[rule("render", None, Some(r"blender .*-b"), 0)]).unwrap();
+        assert_eq!(
+            matcher.best_match([("blender", "blender scene.blend -b -a")]),
+            Some(0)
+        );
+        assert_eq!(
+            matcher.best_match([("blender", "blender scene.blend")]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_priority() {
+        let matcher = ProcessMatcher::new(&[
+            rule("low", Some("blender"), None, 1),
+            rule("high", Some("steam_app_*"), None, 10),
+            rule("also-high", Some("blender"), None, 10),
+        ])
+        .unwrap();
+        let procs = [("blender", ""), ("steam_app_1", "")];
+        assert_eq!(matcher.best_match(procs), Some(1));
+    }
+
+    #[test]
+    fn test_invalid_rules() {
+        assert!(ProcessMatcher::new(&[rule("empty", None, None, 0)]).is_err());
+        assert!(ProcessMatcher::new(&[rule("bad", None, Some("("), 0)]).is_err());
+    }
+
+    #[test]
+    fn test_debounce() {
+        let mut r = rule("render", Some("blender"), None, 0);
+        r.debounce_secs = 5;
+        let mut watcher = ProcessWatcher::new(&[r]).unwrap();
+        let t0 = Instant::now();
+
+        // Match must persist for 5s before switching
+        assert_eq!(watcher.update(Some(0), t0), None);
+        assert_eq!(watcher.update(Some(0), t0 + Duration::from_secs(3)), None);
+        let applied = watcher.update(Some(0), t0 + Duration::from_secs(5));
+        assert_eq!(applied.unwrap().unwrap().profile, "render-profile");
+
+        // A brief exit does not revert
+        assert_eq!(watcher.update(None, t0 + Duration::from_secs(6)), None);
+        assert_eq!(watcher.update(Some(0), t0 + Duration::from_secs(7)), None);
+
+        // A sustained exit reverts
+        assert_eq!(watcher.update(None, t0 + Duration::from_secs(8)), None);
+        assert_eq!(
+            watcher.update(None, t0 + Duration::from_secs(13)),
+            Some(None)
+        );
+    }
+}
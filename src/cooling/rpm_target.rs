@@ -0,0 +1,215 @@
+//! RPM-target closed-loop control.
+//!
+//! The device only accepts duty percentages, so holding an RPM requires a
+//! feedback loop on the `pump_rpm`/`fan_rpm` readings. The controller adjusts
+//! duty in proportion to the RPM error, scaled by the channel's RPM-per-duty
+//! slope (from the calibration table if available).
+
+use crate::error::{KrakenError, Result};
+use crate::protocol::Channel;
+use crate::storage::ChannelCalibration;
+
+/// Highest RPM target accepted without a calibration.
+pub const MAX_TARGET_RPM: u16 = 5000;
+
+/// Assumed RPM per duty % when no calibration exists.
+const DEFAULT_RPM_PER_DUTY: f32 = 25.0;
+/// Fraction of the estimated correction applied per tick.
+const GAIN: f32 = 0.6;
+/// Largest duty change per tick (%).
+const MAX_STEP: f32 = 10.0;
+/// Readings stuck at a duty limit before the target is reported unreachable.
+const UNREACHABLE_TICKS: u32 = 5;
+
+/// Closed-loop controller holding a channel at a target RPM.
+#[derive(Debug, Clone)]
+pub struct RpmController {
+    channel: Channel,
+    target: u16,
+    duty: f32,
+    /// Duty at which the rotor starts (fan); the controller never goes below it
+    min_duty: u8,
+    /// RPM per duty %, used to convert the RPM error to a duty step
+    rpm_per_duty: f32,
+    /// Errors within this many RPM are ignored
+    tolerance: u16,
+    /// Consecutive readings at a duty limit without reaching the target
+    saturated: u32,
+}
+
+impl RpmController {
+    /// Create a controller for `target` RPM.
+    ///
+    /// With a calibration the target is checked against the measured range and
+    /// the starting duty is taken from the table; otherwise the controller
+    /// starts at 50% and discovers the range at run time.
+    ///
+    /// # Errors
+    /// Returns `InvalidInput` if the target is 0, above [`MAX_TARGET_RPM`], or
+    /// outside the calibrated range.
+    pub fn new(
+        channel: Channel,
+        target: u16,
+        calibration: Option<&ChannelCalibration>,
+    ) -> Result<Self> {
+        if target == 0 || target > MAX_TARGET_RPM {
+            return Err(KrakenError::InvalidInput(format!(
+                "{} RPM target {} out of range (1-{})",
+                channel, target, MAX_TARGET_RPM
+            )));
+        }
+
+        let mut min_duty = channel.min_duty();
+        let mut duty = 50.0_f32.max(min_duty as f32);
+        let mut rpm_per_duty = DEFAULT_RPM_PER_DUTY;
+
+        if let Some(cal) = calibration.filter(|c| !c.points.is_empty()) {
+            if target > cal.max_rpm {
+                return Err(KrakenError::InvalidInput(format!(
+                    "{} cannot reach {} RPM: calibrated maximum is {} RPM",
+                    channel, target, cal.max_rpm
+                )));
+            }
+
+            if let Some(start) = cal.start_duty {
+                min_duty = min_duty.max(start);
+            }
+            let floor = cal.expected_rpm(min_duty).unwrap_or(0);
+            if target < floor {
+                return Err(KrakenError::InvalidInput(format!(
+                    "{} cannot hold {} RPM: slowest calibrated speed is {} RPM at {}%",
+                    channel, target, floor, min_duty
+                )));
+            }
+
+            if let Some(seed) = cal.duty_for_rpm(target) {
+                duty = seed.max(min_duty) as f32;
+            }
+
+            // Average slope over the spinning part of the table
+            let spinning: Vec<_> = cal.points.iter().filter(|p| p.rpm > 0).collect();
+            if let (Some(lo), Some(hi)) = (spinning.first(), spinning.last())
+                && hi.duty > lo.duty
+            {
+                rpm_per_duty = (hi.rpm - lo.rpm) as f32 / (hi.duty - lo.duty) as f32;
+            }
+        }
+
+        Ok(Self {
+            channel,
+            target,
+            duty,
+            min_duty,
+            rpm_per_duty: rpm_per_duty.max(1.0),
+            tolerance: (target / 50).max(20),
+            saturated: 0,
+        })
+    }
+
+    /// Target RPM.
+    pub fn target(&self) -> u16 {
+        self.target
+    }
+
+    /// Duty currently commanded.
+    pub fn duty(&self) -> u8 {
+        self.duty.round() as u8
+    }
+
+    /// Feed the measured RPM and return the next duty to command.
+    ///
+    /// # Errors
+    /// Returns `InvalidInput` once the controller has been pinned at 100% (or
+    /// at its minimum duty) for several readings without reaching the target.
+    pub fn update(&mut self, rpm: u16) -> Result<u8> {
+        let error = self.target as f32 - rpm as f32;
+        let max = self.channel.max_duty() as f32;
+        let min = self.min_duty as f32;
+
+        if error.abs() <= self.tolerance as f32 {
+            self.saturated = 0;
+            return Ok(self.duty());
+        }
+
+        let at_max = self.duty >= max && error > 0.0;
+        let at_min = self.duty <= min && error < 0.0;
+        if at_max || at_min {
+            self.saturated += 1;
+            if self.saturated >= UNREACHABLE_TICKS {
+                return Err(KrakenError::InvalidInput(if at_max {
+                    format!(
+                        "{} cannot reach {} RPM: {} RPM at 100% duty",
+                        self.channel, self.target, rpm
+                    )
+                } else {
+                    format!(
+                        "{} cannot go down to {} RPM: {} RPM at its minimum {}% duty",
+                        self.channel, self.target, rpm, self.min_duty
+                    )
+                }));
+            }
+        } else {
+            self.saturated = 0;
+        }
+
+        let step = (GAIN * error / self.rpm_per_duty).clamp(-MAX_STEP, MAX_STEP);
+        self.duty = (self.duty + step).clamp(min, max);
+        Ok(self.duty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::CalibrationPoint;
+
+    fn pump_calibration() -> ChannelCalibration {
+        let points = [(20, 1000), (60, 2000), (100, 2800)]
+            .into_iter()
+            .map(|(duty, rpm)| CalibrationPoint { duty, rpm })
+            .collect();
+        ChannelCalibration::from_points(points, None)
+    }
+
+    /// Simulated pump following the calibration table exactly.
+    fn simulate(controller: &mut RpmController, cal: &ChannelCalibration, ticks: usize) -> u16 {
+        let mut rpm = 0;
+        for _ in 0..ticks {
+            let duty = controller.update(rpm).unwrap();
+            rpm = cal.expected_rpm(duty).unwrap();
+        }
+        rpm
+    }
+
+    #[test]
+    fn test_converges_to_target() {
+        let cal = pump_calibration();
+        let mut controller = RpmController::new(Channel::Pump, 2400, Some(&cal)).unwrap();
+        assert_eq!(controller.duty(), 80); // seeded from calibration
+
+        let rpm = simulate(&mut controller, &cal, 20);
+        assert!(rpm.abs_diff(2400) <= 48, "settled at {} RPM", rpm);
+
+        // Converges without calibration too
+        let mut controller = RpmController::new(Channel::Pump, 1500, None).unwrap();
+        let rpm = simulate(&mut controller, &cal, 30);
+        assert!(rpm.abs_diff(1500) <= 30, "settled at {} RPM", rpm);
+    }
+
+    #[test]
+    fn test_target_bounds() {
+        let cal = pump_calibration();
+        assert!(RpmController::new(Channel::Pump, 0, None).is_err());
+        assert!(RpmController::new(Channel::Pump, 6000, None).is_err());
+        assert!(RpmController::new(Channel::Pump, 3000, Some(&cal)).is_err());
+        assert!(RpmController::new(Channel::Pump, 800, Some(&cal)).is_err());
+    }
+
+    #[test]
+    fn test_unreachable_target_errors() {
+        let mut controller = RpmController::new(Channel::Fan, 2500, None).unwrap();
+        let result = (0..50).try_for_each(|_| controller.update(1800).map(|_| ()));
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains("cannot reach 2500 RPM"), "{}", err);
+    }
+}
@@ -0,0 +1,288 @@
+//! Time-of-day and weekday based profile scheduling.
+//!
+//! Evaluates the `schedules` rules from config.json and resolves which cooling
+//! profile, LCD preset and fan duty cap should be active at a given moment.
+
+use chrono::{Datelike, Duration, NaiveDateTime, NaiveTime, Weekday};
+
+use crate::error::{KrakenError, Result};
+use crate::storage::ScheduleRule;
+
+/// Settings resolved from all rules active at a given moment.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScheduleState {
+    /// Cooling profile override (None = use the configured profile)
+    pub cooling_profile: Option<String>,
+    /// LCD preset override (None = leave LCD as configured)
+    pub lcd_profile: Option<String>,
+    /// Fan duty cap (None = no cap)
+    pub max_fan_duty: Option<u8>,
+    /// Names of the rules that are currently active
+    pub active_rules: Vec<String>,
+}
+
+/// A validated schedule rule.
+#[derive(Debug, Clone)]
+struct CompiledRule {
+    name: String,
+    /// Empty = every day
+    days: Vec<Weekday>,
+    start: NaiveTime,
+    /// None = end of day
+    end: Option<NaiveTime>,
+    cooling_profile: Option<String>,
+    lcd_profile: Option<String>,
+    max_fan_duty: Option<u8>,
+}
+
+impl CompiledRule {
+    fn applies_on(&self, day: Weekday) -> bool {
+        self.days.is_empty() || self.days.contains(&day)
+    }
+
+    fn is_active(&self, now: NaiveDateTime) -> bool {
+        let time = now.time();
+        let today = now.weekday();
+
+        match self.end {
+            // Whole day, or until end of day
+            None => time >= self.start && self.applies_on(today),
+            Some(end) if end == self.start => self.applies_on(today),
+            Some(end) if self.start < end => {
+                time >= self.start && time < end && self.applies_on(today)
+            }
+            // Window wraps past midnight: the early part belongs to the previous day
+            Some(end) => {
+                let yesterday = (now - Duration::days(1)).weekday();
+                (time >= self.start && self.applies_on(today))
+                    || (time < end && self.applies_on(yesterday))
+            }
+        }
+    }
+}
+
+/// Evaluates schedule rules and reports when the resolved state changes.
+#[derive(Debug, Clone, Default)]
+pub struct Scheduler {
+    rules: Vec<CompiledRule>,
+    current: Option<ScheduleState>,
+}
+
+impl Scheduler {
+    /// Build a scheduler from config rules.
+    ///
+    /// # Errors
+    /// Returns `InvalidInput` if a rule has a malformed time, day or duty.
+    pub fn new(rules: &[ScheduleRule]) -> Result<Self> {
+        let rules = rules.iter().map(compile_rule).collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            rules,
+            current: None,
+        })
+    }
+
+    /// Returns true if no rules are configured.
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Resolve the settings active at `now`.
+    ///
+    /// Rules are checked in order; for each setting the first active rule
+    /// that defines it wins.
+    pub fn evaluate(&self, now: NaiveDateTime) -> ScheduleState {
+        let mut state = ScheduleState::default();
+
+        for rule in self.rules.iter().filter(|r| r.is_active(now)) {
+            state.active_rules.push(rule.name.clone());
+            if state.cooling_profile.is_none() {
+                state.cooling_profile = rule.cooling_profile.clone();
+            }
+            if state.lcd_profile.is_none() {
+                state.lcd_profile = rule.lcd_profile.clone();
+            }
+            if state.max_fan_duty.is_none() {
+                state.max_fan_duty = rule.max_fan_duty;
+            }
+        }
+
+        state
+    }
+
+    /// Evaluate the rules and return the new state if it changed since the last poll.
+    ///
+    /// The first call always returns the current state.
+    pub fn poll(&mut self, now: NaiveDateTime) -> Option<ScheduleState> {
+        let state = self.evaluate(now);
+        if self.current.as_ref() == Some(&state) {
+            return None;
+        }
+        self.current = Some(state.clone());
+        Some(state)
+    }
+}
+
+fn compile_rule(rule: &ScheduleRule) -> Result<CompiledRule> {
+    let mut days = Vec::new();
+    for day in &rule.days {
+        days.extend(parse_days(day).ok_or_else(|| {
+            KrakenError::InvalidInput(format!(
+                "Schedule '{}': unknown day '{}'. Use mon..sun, weekdays or weekend",
+                rule.name, day
+            ))
+        })?);
+    }
+
+    let start = match &rule.start {
+        Some(s) => parse_time(&rule.name, s)?,
+        None => NaiveTime::MIN,
+    };
+    let end = rule
+        .end
+        .as_deref()
+        .map(|s| parse_time(&rule.name, s))
+        .transpose()?;
+
+    if let Some(duty) = rule.max_fan_duty
+        && duty > 100
+    {
+        return Err(KrakenError::InvalidInput(format!(
+            "Schedule '{}': max_fan_duty {}% is above 100%",
+            rule.name, duty
+        )));
+    }
+
+    if let Some(lcd) = &rule.lcd_profile
+        && crate::config::LcdProfile::from_name(lcd).is_none()
+    {
+        return Err(KrakenError::InvalidInput(format!(
+            "Schedule '{}': unknown LCD profile '{}'. Use: off, night, day, max",
+            rule.name, lcd
+        )));
+    }
+
+    Ok(CompiledRule {
+        name: rule.name.clone(),
+        days,
+        start,
+        end,
+        cooling_profile: rule.cooling_profile.clone(),
+        lcd_profile: rule.lcd_profile.clone(),
+        max_fan_duty: rule.max_fan_duty,
+    })
+}
+
+fn parse_time(rule: &str, s: &str) -> Result<NaiveTime> {
+    NaiveTime::parse_from_str(s.trim(), "%H:%M").map_err(|_| {
+        KrakenError::InvalidInput(format!(
+            "Schedule '{}': invalid time '{}'. Use HH:MM",
+            rule, s
+        ))
+    })
+}
+
+fn parse_days(s: &str) -> Option<Vec<Weekday>> {
+    use Weekday::*;
+    match s.trim().to_lowercase().as_str() {
+        "weekdays" => Some(vec![Mon, Tue, Wed, Thu, Fri]),
+        "weekend" | "weekends" => Some(vec![Sat, Sun]),
+        other => other.parse::<Weekday>().ok().map(|d| vec![d]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn rule(name: &str, days: &[&str], start: Option<&str>, end: Option<&str>) -> ScheduleRule {
+        ScheduleRule {
+            name: name.into(),
+            days: days.iter().map(|d| d.to_string()).collect(),
+            start: start.map(String::from),
+            end: end.map(String::from),
+            cooling_profile: None,
+            lcd_profile: None,
+            max_fan_duty: None,
+        }
+    }
+
+    /// 2024-01-05 is a Friday.
+    fn at(day: u32, hour: u32, min: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 1, day)
+            .unwrap()
+            .and_hms_opt(hour, min, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_overnight_window() {
+        let mut night = rule("night", &[], Some("23:00"), Some("07:00"));
+        night.cooling_profile = Some("silent".into());
+        night.lcd_profile = Some("night".into());
+        let scheduler = Scheduler::new(&[night]).unwrap();
+
+        assert_eq!(
+            scheduler.evaluate(at(5, 23, 30)).cooling_profile.as_deref(),
+            Some("silent")
+        );
+        assert_eq!(
+            scheduler.evaluate(at(6, 6, 59)).lcd_profile.as_deref(),
+            Some("night")
+        );
+        assert!(scheduler.evaluate(at(6, 7, 0)).active_rules.is_empty());
+        assert!(scheduler.evaluate(at(5, 12, 0)).active_rules.is_empty());
+    }
+
+    #[test]
+    fn test_overnight_window_uses_start_day() {
+        // Friday night only: Saturday 02:00 is still part of it, Friday 02:00 is not
+        let scheduler =
+            Scheduler::new(&[rule("fri-night", &["fri"], Some("22:00"), Some("04:00"))]).unwrap();
+        assert!(!scheduler.evaluate(at(6, 2, 0)).active_rules.is_empty());
+        assert!(scheduler.evaluate(at(5, 2, 0)).active_rules.is_empty());
+    }
+
+    #[test]
+    fn test_days_filter() {
+        let mut weekend = rule("weekend", &["weekend"], None, None);
+        weekend.max_fan_duty = Some(60);
+        let scheduler = Scheduler::new(&[weekend]).unwrap();
+
+        assert_eq!(scheduler.evaluate(at(6, 12, 0)).max_fan_duty, Some(60)); // Saturday
+        assert_eq!(scheduler.evaluate(at(5, 12, 0)).max_fan_duty, None); // Friday
+    }
+
+    #[test]
+    fn test_first_rule_wins_per_setting() {
+        let mut office = rule("office", &["weekdays"], Some("09:00"), Some("18:00"));
+        office.cooling_profile = Some("performance".into());
+        let mut always = rule("always", &[], None, None);
+        always.cooling_profile = Some("silent".into());
+        always.max_fan_duty = Some(80);
+        let scheduler = Scheduler::new(&[office, always]).unwrap();
+
+        let state = scheduler.evaluate(at(5, 10, 0));
+        assert_eq!(state.cooling_profile.as_deref(), Some("performance"));
+        assert_eq!(state.max_fan_duty, Some(80));
+        assert_eq!(state.active_rules, vec!["office", "always"]);
+    }
+
+    #[test]
+    fn test_poll_reports_changes_only() {
+        let mut scheduler = Scheduler::new(&[rule("am", &[], None, Some("12:00"))]).unwrap();
+        assert!(scheduler.poll(at(5, 8, 0)).is_some());
+        assert!(scheduler.poll(at(5, 9, 0)).is_none());
+        assert!(scheduler.poll(at(5, 13, 0)).is_some());
+    }
+
+    #[test]
+    fn test_invalid_rules() {
+        assert!(Scheduler::new(&[rule("bad", &[], Some("25:00"), None)]).is_err());
+        assert!(Scheduler::new(&[rule("bad", &["someday"], None, None)]).is_err());
+
+        let mut lcd = rule("bad", &[], None, None);
+        lcd.lcd_profile = Some("disco".into());
+        assert!(Scheduler::new(&[lcd]).is_err());
+    }
+}
@@ -0,0 +1,541 @@
+//! Offline replay of temperature traces through the duty pipeline.
+//!
+//! A trace is a CSV or JSONL file of timestamped temperatures (and optionally
+//! CPU load). Each sample goes through the same steps as a daemon tick, in
+//! the same order: curve interpolation, load feed-forward boost, then the
+//! zero-RPM fan gate. Schedules, process rules and RPM targets depend on the
+//! live system and are not simulated.
+
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use super::controller::TempSource;
+use super::expression::{ChannelExpr, Temperatures};
+use super::load::{FeedForward, LoadBoost};
+use super::zero_rpm::ZeroRpmController;
+use crate::error::{KrakenError, Result};
+use crate::protocol::Channel;
+use crate::storage::{CoolingProfile, LoadControlConfig};
+
+/// Pump duty when a profile has no pump curve (as in the daemons).
+const DEFAULT_PUMP_DUTY: u8 = 70;
+/// Fan duty when a profile has no fan curve (as in the daemons).
+const DEFAULT_FAN_DUTY: u8 = 50;
+
+// =============================================================================
+// Traces
+// =============================================================================
+
+/// One sample of a temperature trace.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TraceSample {
+    /// Seconds since the start of the trace
+    pub time: f64,
+    /// Liquid temperature (°C)
+    pub liquid_temp: Option<f32>,
+    /// CPU temperature (°C)
+    pub cpu_temp: Option<f32>,
+    /// CPU load (0-100%) for the feed-forward boost
+    pub cpu_load: Option<f32>,
+}
+
+impl TraceSample {
+    /// Temperature for a source.
+    pub fn temperature(&self, source: TempSource) -> Option<f32> {
+        match source {
+            TempSource::Liquid => self.liquid_temp,
+            TempSource::Cpu => self.cpu_temp,
+        }
+    }
+}
+
+/// Column names accepted for each field (case-insensitive).
+const TIME_KEYS: [&str; 4] = ["time", "t", "timestamp", "elapsed"];
+const LIQUID_KEYS: [&str; 5] = [
+    "liquid_temp",
+    "liquid",
+    "liquid_temp_c",
+    "temp",
+    "temperature",
+];
+const CPU_KEYS: [&str; 3] = ["cpu_temp", "cpu", "cpu_temp_c"];
+const LOAD_KEYS: [&str; 3] = ["cpu_load", "load", "cpu_usage"];
+
+/// Parse a CSV (with header row) or JSONL trace.
+///
+/// Timestamps may be seconds or RFC 3339 date-times; they are made relative
+/// to the first sample. Without a time column, samples are `interval` seconds
+/// apart. A generic `temp`/`temperature` column is read as the liquid
+/// temperature.
+///
+/// # Errors
+/// Returns `InvalidInput` for malformed rows, decreasing timestamps or a
+/// trace without samples.
+pub fn parse_trace(content: &str, interval: f64) -> Result<Vec<TraceSample>> {
+    let mut lines = content
+        .lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty() && !l.trim_start().starts_with('#'));
+
+    let Some((_, first)) = lines.clone().next() else {
+        return Err(KrakenError::InvalidInput("Trace is empty".into()));
+    };
+
+    let rows: Vec<(usize, BTreeMap<String, String>)> = if first.trim_start().starts_with('{') {
+        lines
+            .map(|(i, line)| parse_json_row(line).map(|row| (i + 1, row)))
+            .collect::<Result<_>>()?
+    } else {
+        let (_, header) = lines.next().unwrap_or_default();
+        let columns: Vec<String> = header.split(',').map(|c| c.trim().to_lowercase()).collect();
+        lines
+            .map(|(i, line)| {
+                let values = split_csv(line);
+                if values.len() != columns.len() {
+                    return Err(KrakenError::InvalidInput(format!(
+                        "Trace line {}: expected {} columns, found {}",
+                        i + 1,
+                        columns.len(),
+                        values.len()
+                    )));
+                }
+                let row = columns.iter().cloned().zip(values).collect();
+                Ok((i + 1, row))
+            })
+            .collect::<Result<_>>()?
+    };
+
+    let mut samples = Vec::with_capacity(rows.len());
+    let mut start = None;
+    for (index, (line, row)) in rows.iter().enumerate() {
+        let number = |keys: &[&str]| -> Result<Option<f32>> {
+            match lookup(row, keys) {
+                None => Ok(None),
+                Some(v) => v
+                    .parse()
+                    .ok()
+                    .filter(|n: &f32| n.is_finite())
+                    .map(Some)
+                    .ok_or_else(|| {
+                        KrakenError::InvalidInput(format!(
+                            "Trace line {}: invalid number '{}'",
+                            line, v
+                        ))
+                    }),
+            }
+        };
+
+        let time = match lookup(row, &TIME_KEYS) {
+            Some(value) => {
+                let t = parse_time(value).ok_or_else(|| {
+                    KrakenError::InvalidInput(format!(
+                        "Trace line {}: invalid time '{}'",
+                        line, value
+                    ))
+                })?;
+                t - *start.get_or_insert(t)
+            }
+            None => index as f64 * interval,
+        };
+
+        if let Some(last) = samples.last().map(|s: &TraceSample| s.time)
+            && time < last
+        {
+            return Err(KrakenError::InvalidInput(format!(
+                "Trace line {}: time goes backwards",
+                line
+            )));
+        }
+
+        samples.push(TraceSample {
+            time,
+            liquid_temp: number(&LIQUID_KEYS)?,
+            cpu_temp: number(&CPU_KEYS)?,
+            cpu_load: number(&LOAD_KEYS)?,
+        });
+    }
+
+    if samples.is_empty() {
+        return Err(KrakenError::InvalidInput("Trace has no samples".into()));
+    }
+    Ok(samples)
+}
+
+/// Split a CSV line; fields may be quoted, with `""` for a literal quote.
+fn split_csv(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field).trim().to_string()),
+            c => field.push(c),
+        }
+    }
+    fields.push(field.trim().to_string());
+    fields
+}
+
+fn parse_json_row(line: &str) -> Result<BTreeMap<String, String>> {
+    let value: serde_json::Value = serde_json::from_str(line)
+        .map_err(|e| KrakenError::InvalidInput(format!("Invalid JSONL trace line: {}", e)))?;
+    let object = value
+        .as_object()
+        .ok_or_else(|| KrakenError::InvalidInput("JSONL trace lines must be objects".into()))?;
+
+    Ok(object
+        .iter()
+        .filter(|(_, v)| !v.is_null())
+        .map(|(k, v)| {
+            let v = match v {
+                serde_json::Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            (k.to_lowercase(), v)
+        })
+        .collect())
+}
+
+fn lookup<'a>(row: &'a BTreeMap<String, String>, keys: &[&str]) -> Option<&'a str> {
+    keys.iter()
+        .find_map(|k| row.get(*k))
+        .map(String::as_str)
+        .filter(|v| !v.is_empty())
+}
+
+/// Seconds as a number, or an RFC 3339 date-time.
+fn parse_time(value: &str) -> Option<f64> {
+    match value.parse::<f64>() {
+        Ok(seconds) => Some(seconds).filter(|s| s.is_finite()),
+        Err(_) => chrono::DateTime::parse_from_rfc3339(value)
+            .ok()
+            .map(|t| t.timestamp_millis() as f64 / 1000.0),
+    }
+}
+
+// =============================================================================
+// Simulation
+// =============================================================================
+
+/// Duties commanded for one trace sample.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SimulationStep {
+    /// Seconds since the start of the trace
+    pub time: f64,
+    /// Temperature the curves were evaluated at (°C)
+    pub temperature: u8,
+    pub pump_duty: u8,
+    pub fan_duty: u8,
+    /// Feed-forward boost applied (zero if disabled)
+    pub boost: LoadBoost,
+}
+
+/// Statistics for one channel over a simulation.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ChannelSummary {
+    /// Seconds spent at each duty
+    pub time_at_duty: BTreeMap<u8, f64>,
+    /// Number of times the commanded duty changed
+    pub changes: u32,
+    /// Time-weighted mean duty
+    pub mean_duty: f32,
+    pub min_duty: u8,
+    pub max_duty: u8,
+}
+
+impl ChannelSummary {
+    /// Seconds spent in each `width`-% band, keyed by the band's lower bound.
+    pub fn time_in_bands(&self, width: u8) -> BTreeMap<u8, f64> {
+        let width = width.max(1);
+        let mut bands = BTreeMap::new();
+        for (&duty, &secs) in &self.time_at_duty {
+            *bands.entry(duty / width * width).or_insert(0.0) += secs;
+        }
+        bands
+    }
+}
+
+/// Result of replaying a trace with one profile.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulationResult {
+    pub steps: Vec<SimulationStep>,
+    pub pump: ChannelSummary,
+    pub fan: ChannelSummary,
+    /// Samples skipped because a temperature the profile reads was missing
+    pub skipped: usize,
+}
+
+impl SimulationResult {
+    /// Summary for a channel.
+    pub fn summary(&self, channel: Channel) -> &ChannelSummary {
+        match channel {
+            Channel::Pump => &self.pump,
+            Channel::Fan => &self.fan,
+        }
+    }
+}
+
+/// Replay `trace` through a profile's curves.
+///
+/// With `load_control` set, samples with a `cpu_load` value get the same
+/// feed-forward boost the daemons add.
+///
+/// # Errors
+/// Returns `InvalidProfile` for invalid curves, expressions or zero-RPM
+/// settings and `InvalidInput` if no sample has the temperatures needed.
+pub fn simulate(
+    profile: &CoolingProfile,
+    trace: &[TraceSample],
+    source: TempSource,
+    load_control: Option<&LoadControlConfig>,
+) -> Result<SimulationResult> {
+    let pump_expr = profile.channel_expr(Channel::Pump)?;
+    let fan_expr = profile.channel_expr(Channel::Fan)?;
+    let mut zero_rpm = profile
+        .zero_rpm
+        .as_ref()
+        .map(ZeroRpmController::new)
+        .transpose()?;
+    let mut feed_forward = load_control.map(FeedForward::new);
+
+    let epoch = Instant::now();
+    let mut steps = Vec::with_capacity(trace.len());
+    let mut skipped = 0;
+    let mut last_time = None;
+
+    for sample in trace {
+        let Some(temp) = sample.temperature(source) else {
+            skipped += 1;
+            continue;
+        };
+        let temperature = temp.round().clamp(0.0, 255.0) as u8;
+
+        let temps = Temperatures {
+            liquid: sample.liquid_temp,
+            cpu: sample.cpu_temp,
+        };
+        let channel_duty = |expr: &Option<ChannelExpr>, channel, default| match expr {
+            Some(expr) => expr.duty(channel, &temps, source),
+            None => Some(default),
+        };
+        // An expression may read a source this sample does not have
+        let (Some(pump), Some(fan)) = (
+            channel_duty(&pump_expr, Channel::Pump, DEFAULT_PUMP_DUTY),
+            channel_duty(&fan_expr, Channel::Fan, DEFAULT_FAN_DUTY),
+        ) else {
+            skipped += 1;
+            continue;
+        };
+
+        let dt = Duration::from_secs_f64(sample.time - last_time.unwrap_or(sample.time));
+        last_time = Some(sample.time);
+        let boost = match (feed_forward.as_mut(), sample.cpu_load) {
+            (Some(ff), Some(load)) => ff.update(load, dt),
+            _ => LoadBoost::default(),
+        };
+        let pump = boost.apply(Channel::Pump, pump);
+        let fan = boost.apply(Channel::Fan, fan);
+
+        let now = epoch + Duration::from_secs_f64(sample.time);
+        let fan = match zero_rpm.as_mut() {
+            Some(zero_rpm) => zero_rpm.update(temperature, fan, now),
+            None => fan,
+        };
+
+        steps.push(SimulationStep {
+            time: sample.time,
+            temperature,
+            pump_duty: pump,
+            fan_duty: fan,
+            boost,
+        });
+    }
+
+    if steps.is_empty() {
+        return Err(KrakenError::InvalidInput(format!(
+            "Trace has no samples with the temperatures the profile needs (source: {})",
+            source
+        )));
+    }
+
+    Ok(SimulationResult {
+        pump: summarize(&steps, |s| s.pump_duty),
+        fan: summarize(&steps, |s| s.fan_duty),
+        steps,
+        skipped,
+    })
+}
+
+/// Each step lasts until the next one; the last lasts as long as the one before.
+fn summarize(steps: &[SimulationStep], duty: impl Fn(&SimulationStep) -> u8) -> ChannelSummary {
+    let mut summary = ChannelSummary {
+        min_duty: u8::MAX,
+        ..Default::default()
+    };
+    let mut weighted = 0.0;
+    let mut total = 0.0;
+    let mut last_dt = 0.0;
+
+    for (i, step) in steps.iter().enumerate() {
+        let dt = match steps.get(i + 1) {
+            Some(next) => next.time - step.time,
+            None => last_dt,
+        };
+        last_dt = dt;
+
+        let d = duty(step);
+        *summary.time_at_duty.entry(d).or_insert(0.0) += dt;
+        weighted += d as f64 * dt;
+        total += dt;
+        summary.min_duty = summary.min_duty.min(d);
+        summary.max_duty = summary.max_duty.max(d);
+        if i > 0 && duty(&steps[i - 1]) != d {
+            summary.changes += 1;
+        }
+    }
+
+    summary.mean_duty = if total > 0.0 {
+        (weighted / total) as f32
+    } else {
+        steps.iter().map(|s| duty(s) as f32).sum::<f32>() / steps.len() as f32
+    };
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{ChannelSetting, CoolingMode, Threshold, ZeroRpmConfig};
+
+    fn profile(fan: &[(u8, u8)]) -> CoolingProfile {
+        let mode = |points: &[(u8, u8)]| CoolingMode {
+            mode_type: Some("Custom".into()),
+            fixed_percentage: None,
+            custom_thresholds: Some(
+                points
+                    .iter()
+                    .map(|&(temperature, fan_percentage)| Threshold {
+                        temperature,
+                        fan_percentage,
+                    })
+                    .collect(),
+            ),
+            temperature_option: None,
+            interpolation: None,
+        };
+        CoolingProfile {
+            id: "test".into(),
+            origin_id: None,
+            name: None,
+            zero_rpm: None,
+            channel_settings: vec![
+                ChannelSetting {
+                    channel_name: "pump".into(),
+                    mode: Some(mode(&[(20, 60), (59, 100)])),
+                    expression: None,
+                },
+                ChannelSetting {
+                    channel_name: "fan".into(),
+                    mode: Some(mode(fan)),
+                    expression: None,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_parse_csv_and_jsonl() {
+        let csv = "time,liquid_temp,cpu_temp\n0,30,45\n2,31.5,50\n\n4,32,\n";
+        let trace = parse_trace(csv, 1.0).unwrap();
+        assert_eq!(trace.len(), 3);
+        assert_eq!(trace[1].liquid_temp, Some(31.5));
+        assert_eq!(trace[2].cpu_temp, None);
+
+        let jsonl = r#"{"timestamp":"2026-01-01T10:00:00Z","temp":30}
+{"timestamp":"2026-01-01T10:00:05Z","temp":35,"cpu_load":80}"#;
+        let trace = parse_trace(jsonl, 1.0).unwrap();
+        assert_eq!(trace[1].time, 5.0);
+        assert_eq!(trace[1].liquid_temp, Some(35.0));
+        assert_eq!(trace[1].cpu_load, Some(80.0));
+
+        // Without a time column samples are `interval` apart
+        let trace = parse_trace("liquid\n30\n31\n", 2.0).unwrap();
+        assert_eq!(trace[1].time, 2.0);
+
+        assert!(parse_trace("time,liquid\n5,30\n1,31\n", 1.0).is_err());
+        assert!(parse_trace("time,liquid\n0,hot\n", 1.0).is_err());
+        assert!(parse_trace("time,liquid\n0,NaN\n", 1.0).is_err());
+        assert!(parse_trace("time,liquid\ninf,30\n", 1.0).is_err());
+    }
+
+    #[test]
+    fn test_simulate_summary() {
+        let trace: Vec<_> = [30.0, 30.0, 40.0, 40.0, 30.0]
+            .iter()
+            .enumerate()
+            .map(|(i, &t)| TraceSample {
+                time: i as f64 * 10.0,
+                liquid_temp: Some(t),
+                ..Default::default()
+            })
+            .collect();
+
+        let result = simulate(
+            &profile(&[(30, 20), (40, 60)]),
+            &trace,
+            TempSource::Liquid,
+            None,
+        )
+        .unwrap();
+        let fan: Vec<_> = result.steps.iter().map(|s| s.fan_duty).collect();
+        assert_eq!(fan, vec![20, 20, 60, 60, 20]);
+        assert_eq!(result.fan.changes, 2);
+        assert_eq!(result.fan.time_at_duty[&20], 30.0);
+        assert_eq!(result.fan.time_at_duty[&60], 20.0);
+        assert_eq!(result.fan.mean_duty, 36.0);
+        assert_eq!(result.fan.time_in_bands(50)[&0], 30.0);
+
+        // A channel expression replaces the stored curve
+        let mut stepped = profile(&[(30, 20), (40, 60)]);
+        stepped.channel_settings[1].expression = Some("offset(10)+step(20:20,45:60)".into());
+        let result = simulate(&stepped, &trace, TempSource::Liquid, None).unwrap();
+        let fan: Vec<_> = result.steps.iter().map(|s| s.fan_duty).collect();
+        assert_eq!(fan, vec![20, 20, 60, 60, 20]);
+
+        // No CPU temperatures in the trace
+        assert!(simulate(&profile(&[]), &trace, TempSource::Cpu, None).is_err());
+        stepped.channel_settings[1].expression = Some("cpu+silent".into());
+        assert!(simulate(&stepped, &trace, TempSource::Liquid, None).is_err());
+    }
+
+    #[test]
+    fn test_simulate_zero_rpm() {
+        let mut quiet = profile(&[(20, 30), (59, 100)]);
+        quiet.zero_rpm = Some(ZeroRpmConfig {
+            stop_temperature: 32,
+            start_temperature: 36,
+            kick_duty: 80,
+            kick_secs: 3,
+            min_on_secs: 0,
+        });
+        let trace: Vec<_> = [30.0, 34.0, 37.0, 37.0]
+            .iter()
+            .enumerate()
+            .map(|(i, &t)| TraceSample {
+                time: i as f64 * 4.0,
+                liquid_temp: Some(t),
+                ..Default::default()
+            })
+            .collect();
+
+        let result = simulate(&quiet, &trace, TempSource::Liquid, None).unwrap();
+        let fan: Vec<_> = result.steps.iter().map(|s| s.fan_duty).collect();
+        assert_eq!(fan, vec![0, 0, 80, 61]); // off, off, kick, curve
+    }
+}
@@ -0,0 +1,382 @@
+//! Profile validation.
+//!
+//! Checks the curves of a profile for mistakes that the device or the daemons
+//! would otherwise accept silently. Errors make a profile unusable; warnings
+//! are reported but the profile still loads.
+//!
+//! | Check | Severity |
+//! |-------|----------|
+//! | Empty curve, duplicate temperature, point outside 20-59°C, duty above 100% | error |
+//! | Duty drops as the temperature rises | error |
+//! | Pump duty below the 20% minimum | error (warning in expressions, which raise it) |
+//! | Fan not at 100% by the critical temperature (59°C) | warning |
+
+use std::fmt;
+
+use super::curve::Curve;
+use super::expression::{ChannelExpr, CurveExpr, parse_channel_expr};
+use crate::error::{KrakenError, Result};
+use crate::protocol::{CRITICAL_TEMPERATURE, CURVE_POINTS, Channel, MIN_CURVE_TEMP};
+use crate::storage::CoolingProfile;
+
+/// How serious an [`Issue`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// Reported, the profile still loads
+    Warning,
+    /// The profile is rejected
+    Error,
+}
+
+/// One problem found in a channel of a profile.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+    pub severity: Severity,
+    pub channel: Channel,
+    pub message: String,
+}
+
+impl Issue {
+    fn new(severity: Severity, channel: Channel, message: String) -> Self {
+        Self {
+            severity,
+            channel,
+            message,
+        }
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.channel, self.message)
+    }
+}
+
+/// All issues found in a profile.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Validation {
+    pub issues: Vec<Issue>,
+}
+
+impl Validation {
+    pub fn errors(&self) -> impl Iterator<Item = &Issue> {
+        self.issues.iter().filter(|i| i.severity == Severity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &Issue> {
+        self.issues
+            .iter()
+            .filter(|i| i.severity == Severity::Warning)
+    }
+
+    /// True if there are no errors (warnings are allowed).
+    pub fn is_valid(&self) -> bool {
+        self.errors().next().is_none()
+    }
+
+    /// Turn errors into a `Result`, returning the warnings if there are none.
+    ///
+    /// # Errors
+    /// Returns `InvalidProfile` listing every error of the profile.
+    pub fn into_result(self, profile: &str) -> Result<Vec<Issue>> {
+        if !self.is_valid() {
+            let errors: Vec<String> = self.errors().map(Issue::to_string).collect();
+            return Err(KrakenError::InvalidProfile(format!(
+                "Profile '{}' is invalid: {}",
+                profile,
+                errors.join("; ")
+            )));
+        }
+        Ok(self
+            .issues
+            .into_iter()
+            .filter(|i| i.severity == Severity::Warning)
+            .collect())
+    }
+}
+
+/// Validate the pump and fan settings of a stored profile.
+///
+/// Channel expressions are checked instead of the CAM curve stored next to
+/// them. Other channels are ignored, as they are by the daemons.
+pub fn validate_profile(profile: &CoolingProfile) -> Validation {
+    let mut issues = Vec::new();
+
+    for channel in [Channel::Pump, Channel::Fan] {
+        let Some(setting) = profile
+            .channel_settings
+            .iter()
+            .find(|c| c.channel_name.eq_ignore_ascii_case(&channel.to_string()))
+        else {
+            continue;
+        };
+
+        if let Some(expression) = &setting.expression {
+            match parse_channel_expr(expression) {
+                Ok(expr) => issues.extend(validate_channel(channel, &expr)),
+                Err(e) => issues.push(Issue::new(
+                    Severity::Error,
+                    channel,
+                    format!("invalid expression '{}': {}", expression, e.message),
+                )),
+            }
+            continue;
+        }
+
+        let points = profile.curve(&channel.to_string());
+        let fixed = setting.mode.as_ref().and_then(|m| m.fixed_percentage);
+        if !points.is_empty() {
+            let errors = validate_points(channel, &points, Some(Severity::Error));
+            if !errors.is_empty() {
+                issues.extend(errors);
+                continue;
+            }
+            let interpolation = setting
+                .mode
+                .as_ref()
+                .and_then(|m| m.interpolation)
+                .unwrap_or_default();
+            if let Ok(curve) = Curve::new(&points, interpolation) {
+                let expr = ChannelExpr::from_curve(CurveExpr::Curve(curve));
+                issues.extend(validate_channel(channel, &expr));
+            }
+        } else if let Some(duty) = fixed {
+            issues.extend(validate_fixed(channel, duty, Severity::Error));
+        }
+    }
+
+    Validation { issues }
+}
+
+/// Validate a channel expression.
+///
+/// Pump duties below the minimum are warnings here: expressions document
+/// that they are raised to the pump minimum.
+pub fn validate_channel(channel: Channel, expr: &ChannelExpr) -> Vec<Issue> {
+    // A clamp at or above the minimum keeps every duty in range
+    let floor = expr.clamp_min.unwrap_or(0);
+    let below_min = (floor < channel.min_duty()).then_some(Severity::Warning);
+
+    let mut issues = match &expr.curve {
+        CurveExpr::Curve(curve) => validate_points(channel, curve.points(), below_min),
+        CurveExpr::Fixed(duty) => validate_fixed(channel, (*duty).max(floor), Severity::Warning),
+        CurveExpr::Silent | CurveExpr::Performance => Vec::new(),
+    };
+
+    if let Some(max) = expr.clamp_max
+        && max < channel.min_duty()
+    {
+        issues.push(Issue::new(
+            Severity::Warning,
+            channel,
+            format!(
+                "clamp max {}% is below the {}% {} minimum and is raised to it",
+                max,
+                channel.min_duty(),
+                channel.to_string().to_lowercase()
+            ),
+        ));
+    }
+
+    if !matches!(expr.curve, CurveExpr::Fixed(_))
+        && issues.iter().all(|i| i.severity != Severity::Error)
+    {
+        issues.extend(check_full_speed(channel, expr));
+    }
+    issues
+}
+
+/// Check raw (temperature, duty) points in any order.
+///
+/// `below_min` is the severity of duties below the channel minimum, or
+/// `None` to skip that check.
+pub fn validate_points(
+    channel: Channel,
+    points: &[(u8, u8)],
+    below_min: Option<Severity>,
+) -> Vec<Issue> {
+    let error = |message: String| Issue::new(Severity::Error, channel, message);
+
+    if points.is_empty() {
+        return vec![error("curve has no points".into())];
+    }
+
+    let mut issues = Vec::new();
+    let mut sorted = points.to_vec();
+    sorted.sort_by_key(|&(temp, _)| temp);
+
+    for (i, &(temp, duty)) in sorted.iter().enumerate() {
+        if !(MIN_CURVE_TEMP..=CRITICAL_TEMPERATURE).contains(&temp) {
+            issues.push(error(format!(
+                "point {}°C is outside {}-{}°C",
+                temp, MIN_CURVE_TEMP, CRITICAL_TEMPERATURE
+            )));
+        }
+        if i > 0 && sorted[i - 1].0 == temp {
+            issues.push(error(format!("more than one point at {}°C", temp)));
+        }
+        if duty > 100 {
+            issues.push(error(format!("duty {}% at {}°C is above 100%", duty, temp)));
+        } else if let Some(severity) = below_min
+            && duty < channel.min_duty()
+        {
+            issues.push(Issue::new(
+                severity,
+                channel,
+                format!(
+                    "duty {}% at {}°C is below the {}% {} minimum",
+                    duty,
+                    temp,
+                    channel.min_duty(),
+                    channel.to_string().to_lowercase()
+                ),
+            ));
+        }
+    }
+
+    for pair in sorted.windows(2) {
+        let ((t1, d1), (t2, d2)) = (pair[0], pair[1]);
+        if t1 != t2 && d2 < d1 {
+            issues.push(error(format!(
+                "duty drops from {}% at {}°C to {}% at {}°C",
+                d1, t1, d2, t2
+            )));
+        }
+    }
+
+    issues
+}
+
+fn validate_fixed(channel: Channel, duty: u8, below_min: Severity) -> Vec<Issue> {
+    if duty > 100 {
+        return vec![Issue::new(
+            Severity::Error,
+            channel,
+            format!("fixed duty {}% is above 100%", duty),
+        )];
+    }
+    if duty < channel.min_duty() {
+        return vec![Issue::new(
+            below_min,
+            channel,
+            format!(
+                "fixed duty {}% is below the {}% {} minimum",
+                duty,
+                channel.min_duty(),
+                channel.to_string().to_lowercase()
+            ),
+        )];
+    }
+    Vec::new()
+}
+
+/// The fan should run at full speed by the critical liquid temperature.
+fn check_full_speed(channel: Channel, expr: &ChannelExpr) -> Option<Issue> {
+    if channel != Channel::Fan {
+        return None;
+    }
+    let duty = expr.table(channel)[CURVE_POINTS - 1];
+    (duty < 100).then(|| {
+        Issue::new(
+            Severity::Warning,
+            channel,
+            format!(
+                "only {}% at the critical temperature of {}°C (expected 100%)",
+                duty, CRITICAL_TEMPERATURE
+            ),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cooling::parse_channel_expr;
+    use crate::storage::{ChannelSetting, CoolingMode, Threshold};
+
+    fn profile(pump: &[(u8, u8)], fan: &[(u8, u8)]) -> CoolingProfile {
+        let setting = |name: &str, points: &[(u8, u8)]| ChannelSetting {
+            channel_name: name.into(),
+            mode: Some(CoolingMode {
+                mode_type: Some("Custom".into()),
+                fixed_percentage: None,
+                custom_thresholds: Some(
+                    points
+                        .iter()
+                        .map(|&(temperature, fan_percentage)| Threshold {
+                            temperature,
+                            fan_percentage,
+                        })
+                        .collect(),
+                ),
+                temperature_option: None,
+                interpolation: None,
+            }),
+            expression: None,
+        };
+        CoolingProfile {
+            id: "Test".into(),
+            origin_id: None,
+            name: None,
+            channel_settings: vec![setting("pump", pump), setting("fan", fan)],
+            zero_rpm: None,
+        }
+    }
+
+    #[test]
+    fn test_valid_profile() {
+        let validation = validate_profile(&profile(&[(20, 60), (59, 100)], &[(20, 25), (59, 100)]));
+        assert_eq!(validation, Validation::default());
+        assert!(validation.into_result("Test").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_errors() {
+        let validation = validate_profile(&profile(
+            &[(20, 10), (40, 60), (40, 70), (59, 100)],
+            &[(20, 60), (45, 40), (65, 100)],
+        ));
+        let errors: Vec<String> = validation.errors().map(Issue::to_string).collect();
+        assert_eq!(
+            errors,
+            vec![
+                "Pump: duty 10% at 20°C is below the 20% pump minimum",
+                "Pump: more than one point at 40°C",
+                "Fan: point 65°C is outside 20-59°C",
+                "Fan: duty drops from 60% at 20°C to 40% at 45°C",
+            ]
+        );
+
+        let err = validation.into_result("Test").unwrap_err().to_string();
+        assert!(err.contains("Profile 'Test' is invalid"));
+        assert!(err.contains("more than one point at 40°C"));
+    }
+
+    #[test]
+    fn test_warnings() {
+        let validation = validate_profile(&profile(&[(20, 60), (59, 100)], &[(20, 25), (59, 80)]));
+        assert!(validation.is_valid());
+        let warnings = validation.into_result("Test").unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].message.contains("only 80%"));
+
+        // Expressions: the pump minimum is applied, clamps count
+        let expr = parse_channel_expr("curve(20:0,59:100)").unwrap();
+        let issues = validate_channel(Channel::Pump, &expr);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity, Severity::Warning);
+
+        let expr = parse_channel_expr("curve(20:0,59:100)+clamp(min=20)").unwrap();
+        assert!(validate_channel(Channel::Pump, &expr).is_empty());
+
+        let expr = parse_channel_expr("curve(20:30,59:100)+clamp(max=90)").unwrap();
+        assert!(
+            validate_channel(Channel::Fan, &expr)[0]
+                .message
+                .contains("only 90%")
+        );
+        assert!(
+            validate_channel(Channel::Fan, &parse_channel_expr("fixed(40)").unwrap()).is_empty()
+        );
+    }
+}
@@ -0,0 +1,235 @@
+//! Zero-RPM fan mode and fan stall detection.
+//!
+//! The fan is switched off below a stop temperature and restarted above a
+//! higher start temperature. A stopped fan needs a duty above its stall point
+//! to spin up, so restarts begin with a short high-duty kick; a minimum
+//! on-time keeps the fan from flapping around the thresholds.
+
+use std::time::{Duration, Instant};
+
+use crate::error::{KrakenError, Result};
+use crate::storage::ZeroRpmConfig;
+
+/// Readings with the fan at 0 RPM before a stall is reported.
+const STALL_READINGS: u32 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FanState {
+    Off,
+    /// Spinning up; kick duty until `until`
+    Kicking {
+        started: Instant,
+        until: Instant,
+    },
+    On {
+        started: Instant,
+    },
+}
+
+/// Gates the fan duty for zero-RPM operation.
+#[derive(Debug, Clone)]
+pub struct ZeroRpmController {
+    stop_temperature: u8,
+    start_temperature: u8,
+    kick_duty: u8,
+    kick: Duration,
+    min_on: Duration,
+    state: Option<FanState>,
+}
+
+impl ZeroRpmController {
+    /// Create a controller from a profile's zero-RPM settings.
+    ///
+    /// # Errors
+    /// Returns `InvalidProfile` if the stop temperature is not below the start
+    /// temperature or the kick duty is above 100%.
+    pub fn new(config: &ZeroRpmConfig) -> Result<Self> {
+        if config.stop_temperature >= config.start_temperature {
+            return Err(KrakenError::InvalidProfile(format!(
+                "Zero-RPM stop temperature ({}°C) must be below start temperature ({}°C)",
+                config.stop_temperature, config.start_temperature
+            )));
+        }
+        if config.kick_duty > 100 {
+            return Err(KrakenError::InvalidProfile(format!(
+                "Zero-RPM kick duty {}% is above 100%",
+                config.kick_duty
+            )));
+        }
+
+        Ok(Self {
+            stop_temperature: config.stop_temperature,
+            start_temperature: config.start_temperature,
+            kick_duty: config.kick_duty,
+            kick: Duration::from_secs(config.kick_secs),
+            min_on: Duration::from_secs(config.min_on_secs),
+            state: None,
+        })
+    }
+
+    /// Returns true while the fan is switched off.
+    pub fn is_stopped(&self) -> bool {
+        self.state == Some(FanState::Off)
+    }
+
+    /// Returns true while a spin-up kick is in progress.
+    pub fn is_kicking(&self) -> bool {
+        matches!(self.state, Some(FanState::Kicking { .. }))
+    }
+
+    /// Compute the fan duty for `temp`, given the duty the curve asks for.
+    pub fn update(&mut self, temp: u8, curve_duty: u8, now: Instant) -> u8 {
+        let state = match self.state {
+            // On startup the fan may already be spinning; kick unless clearly cold
+            None if temp <= self.stop_temperature => FanState::Off,
+            None => self.start_kick(now),
+            Some(FanState::Off) if temp >= self.start_temperature => self.start_kick(now),
+            Some(FanState::Kicking { started, until }) if now >= until => FanState::On { started },
+            Some(FanState::On { started })
+                if temp <= self.stop_temperature && now.duration_since(started) >= self.min_on =>
+            {
+                FanState::Off
+            }
+            Some(state) => state,
+        };
+        self.state = Some(state);
+
+        match state {
+            FanState::Off => 0,
+            FanState::Kicking { .. } => curve_duty.max(self.kick_duty),
+            FanState::On { .. } => curve_duty,
+        }
+    }
+
+    fn start_kick(&self, now: Instant) -> FanState {
+        FanState::Kicking {
+            started: now,
+            until: now + self.kick,
+        }
+    }
+}
+
+/// Reports a fan that stays at 0 RPM while it is driven.
+#[derive(Debug, Clone)]
+pub struct StallDetector {
+    /// Duties at or below this are allowed to stall
+    stall_duty: u8,
+    count: u32,
+    reported: bool,
+}
+
+impl StallDetector {
+    /// Create a detector. Duties at or below `stall_duty` (e.g. the calibrated
+    /// stall point) are not expected to spin the fan.
+    pub fn new(stall_duty: u8) -> Self {
+        Self {
+            stall_duty,
+            count: 0,
+            reported: false,
+        }
+    }
+
+    /// Feed the commanded duty and the measured RPM.
+    ///
+    /// Returns true when a stall is first detected. Reports again only after
+    /// the fan has recovered.
+    pub fn update(&mut self, duty: u8, rpm: u16) -> bool {
+        if duty > self.stall_duty && rpm == 0 {
+            self.count += 1;
+            if self.count >= STALL_READINGS && !self.reported {
+                self.reported = true;
+                return true;
+            }
+        } else if rpm > 0 || duty == 0 {
+            self.count = 0;
+            self.reported = false;
+        }
+        false
+    }
+
+    /// True from a reported stall until the fan recovers.
+    pub fn is_stalled(&self) -> bool {
+        self.reported
+    }
+}
+
+impl Default for StallDetector {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> ZeroRpmConfig {
+        ZeroRpmConfig {
+            stop_temperature: 35,
+            start_temperature: 40,
+            kick_duty: 60,
+            kick_secs: 3,
+            min_on_secs: 60,
+        }
+    }
+
+    #[test]
+    fn test_hysteresis_and_kick() {
+        let mut zero = ZeroRpmController::new(&config()).unwrap();
+        let t0 = Instant::now();
+        let at = |secs| t0 + Duration::from_secs(secs);
+
+        // Cold start: off, and stays off inside the hysteresis band
+        assert_eq!(zero.update(30, 25, at(0)), 0);
+        assert_eq!(zero.update(38, 25, at(1)), 0);
+
+        // Crossing the start temperature kicks, then follows the curve
+        assert_eq!(zero.update(40, 30, at(2)), 60);
+        assert_eq!(zero.update(40, 30, at(4)), 60);
+        assert_eq!(zero.update(40, 30, at(5)), 30);
+
+        // Below stop temperature, but within minimum on-time
+        assert_eq!(zero.update(34, 25, at(30)), 25);
+
+        // Minimum on-time elapsed
+        assert_eq!(zero.update(34, 25, at(62)), 0);
+        assert!(zero.is_stopped());
+    }
+
+    #[test]
+    fn test_warm_start_kicks() {
+        let mut zero = ZeroRpmController::new(&config()).unwrap();
+        assert_eq!(zero.update(37, 30, Instant::now()), 60);
+        assert!(zero.is_kicking());
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        let mut bad = config();
+        bad.stop_temperature = 40;
+        assert!(ZeroRpmController::new(&bad).is_err());
+    }
+
+    #[test]
+    fn test_stall_detector() {
+        let mut stall = StallDetector::new(10);
+
+        // Stopped on purpose or below stall duty: no alarm
+        for _ in 0..5 {
+            assert!(!stall.update(0, 0));
+            assert!(!stall.update(10, 0));
+        }
+
+        // Driven but not spinning
+        assert!(!stall.update(40, 0));
+        assert!(!stall.update(40, 0));
+        assert!(stall.update(40, 0));
+        assert!(!stall.update(40, 0));
+
+        // Recovery re-arms
+        assert!(!stall.update(40, 900));
+        stall.update(40, 0);
+        stall.update(40, 0);
+        assert!(stall.update(40, 0));
+    }
+}
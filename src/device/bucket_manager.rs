@@ -37,10 +37,10 @@ impl BucketManager {
         let mut manager = Self::new();
 
         if let Ok(buckets) = kraken.query_all_buckets() {
-            for (idx, exists, _, _) in buckets {
-                if idx < MAX_BUCKETS as u8 && exists {
-                    manager.occupied[idx as usize] = true;
-                    manager.queue.push_back(idx);
+            for bucket in buckets {
+                if bucket.index < MAX_BUCKETS as u8 && bucket.occupied {
+                    manager.occupied[bucket.index as usize] = true;
+                    manager.queue.push_back(bucket.index);
                 }
             }
         }
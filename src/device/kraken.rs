@@ -6,7 +6,7 @@ use hidapi::{HidApi, HidDevice};
 
 use crate::error::{KrakenError, Result};
 use crate::protocol::{
-    CMD_INIT_COMPLETE, CMD_INIT_INTERVAL, Channel, DeviceStatus, FirmwareVersion,
+    BucketInfo, CMD_INIT_COMPLETE, CMD_INIT_INTERVAL, Channel, DeviceStatus, FirmwareVersion,
     HID_REPORT_LENGTH, KRAKEN_Z3_PID, NZXT_VID, RESP_BUCKET_SETUP, RESP_FIRMWARE, RESP_LED_INFO,
     RESP_SPEED_ACK, RESP_STATUS, RESP_STATUS_ALT, RESP_SUB_OK, build_fixed_speed_cmd,
     build_speed_profile_cmd, interpolate_profile,
@@ -513,13 +513,16 @@ impl KrakenZ63 {
     }
 
     /// Query all 16 buckets and return their info.
-    ///
-    /// Returns a vector of tuples: (bucket_index, exists, start_page, size_pages)
-    pub fn query_all_buckets(&self) -> Result<Vec<(u8, bool, u16, u16)>> {
+    pub fn query_all_buckets(&self) -> Result<Vec<BucketInfo>> {
         let mut buckets = Vec::with_capacity(16);
-        for i in 0..16 {
-            let (exists, _, start_page, size_pages) = self.query_bucket(i)?;
-            buckets.push((i, exists, start_page, size_pages));
+        for index in 0..16 {
+            let (occupied, _, start_page, size_pages) = self.query_bucket(index)?;
+            buckets.push(BucketInfo {
+                index,
+                occupied,
+                start_page,
+                size_pages,
+            });
         }
         Ok(buckets)
     }
@@ -527,33 +530,33 @@ impl KrakenZ63 {
     /// Calculate memory offset for new bucket (following liquidctl logic).
     fn calculate_memory_offset(
         &self,
-        buckets: &[(u8, bool, u16, u16)],
+        buckets: &[BucketInfo],
         target_idx: u8,
         needed_size: u16,
     ) -> Result<u16> {
         // Find target bucket's current info
-        let target = buckets.iter().find(|(i, _, _, _)| *i == target_idx);
+        let target = buckets.iter().find(|b| b.index == target_idx);
 
-        if let Some((_, exists, current_start, current_size)) = target {
+        if let Some(target) = target {
             // If bucket exists and has enough space, reuse its offset
-            if *exists && *current_size >= needed_size {
-                return Ok(*current_start);
+            if target.occupied && target.size_pages >= needed_size {
+                return Ok(target.start_page);
             }
         }
 
         // Find the end of all occupied memory (EXCLUDING the target bucket)
         let max_end: u16 = buckets
             .iter()
-            .filter(|(i, exists, _, _)| *exists && *i != target_idx)
-            .map(|(_, _, start, size)| start + size)
+            .filter(|b| b.occupied && b.index != target_idx)
+            .map(BucketInfo::end_page)
             .max()
             .unwrap_or(0);
 
         // Find the minimum occupied start (EXCLUDING target)
         let min_start: u16 = buckets
             .iter()
-            .filter(|(i, exists, _, _)| *exists && *i != target_idx)
-            .map(|(_, _, start, _)| *start)
+            .filter(|b| b.occupied && b.index != target_idx)
+            .map(|b| b.start_page)
             .min()
             .unwrap_or(0xFFFF);
 
@@ -599,6 +602,15 @@ impl KrakenZ63 {
         self.write(&cmd)
     }
 
+    /// Set a fixed speed on a channel.
+    ///
+    /// # Errors
+    /// Returns `InvalidDuty` if duty is outside the channel's valid range.
+    pub fn set_channel_speed(&self, channel: Channel, duty: u8) -> Result<()> {
+        let cmd = build_fixed_speed_cmd(channel, duty)?;
+        self.write(&cmd)
+    }
+
     /// Set a speed profile for a channel.
     ///
     /// The profile is specified as (temperature, duty) pairs which are interpolated
@@ -643,6 +655,11 @@ impl KrakenZ63 {
         self.firmware
     }
 
+    /// Get the USB serial number of the device, if it reports one.
+    pub fn serial_number(&self) -> Option<String> {
+        self.device.get_serial_number_string().ok().flatten()
+    }
+
     // =========================================================================
     // Private Helpers
     // =========================================================================
@@ -0,0 +1,674 @@
+//! Control socket of the daemons.
+//!
+//! `start` and `cooling-daemon` listen on a Unix socket. The protocol is
+//! line-delimited JSON: each request line is answered by one response line.
+//!
+//! ```text
+//! → {"method":"set_duty","channel":"fan","duty":60}
+//! ← {"ok":true}
+//! → {"method":"status"}
+//! ← {"ok":true,"result":{"liquid_temp_c":31.5,"pump_rpm":2100,...}}
+//! → {"method":"set_profile","name":"nope"}
+//! ← {"ok":false,"error":"Profile 'nope' not found"}
+//! ```
+
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{KrakenError, Result};
+use crate::protocol::{BucketInfo, Channel, DeviceStatus};
+use crate::storage::{ControlConfig, get_runtime_dir};
+use crate::telemetry::{HistoryRecord, StatsReport};
+
+/// Socket file in the runtime directory.
+pub const SOCKET_FILE: &str = "control.sock";
+
+/// Environment variable overriding the socket path.
+pub const SOCKET_ENV: &str = "NZXT_KRAKEN_SOCKET";
+
+/// Longest wait for the daemon to answer (image uploads take a while).
+const REPLY_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Socket path: `$NZXT_KRAKEN_SOCKET`, else `path` from config.json, else
+/// `control.sock` in the runtime directory.
+pub fn socket_path(config: &ControlConfig) -> Result<PathBuf> {
+    if let Some(path) = std::env::var_os(SOCKET_ENV).filter(|p| !p.is_empty()) {
+        return Ok(path.into());
+    }
+    match &config.path {
+        Some(path) => Ok(path.clone()),
+        None => Ok(get_runtime_dir()?.join(SOCKET_FILE)),
+    }
+}
+
+fn socket_error(path: &Path, e: std::io::Error) -> KrakenError {
+    KrakenError::InvalidInput(format!("Control socket {}: {}", path.display(), e))
+}
+
+// =============================================================================
+// Protocol
+// =============================================================================
+
+/// Request sent to the daemon.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum ControlRequest {
+    /// Latest reading of the daemon ([`DaemonStatus`])
+    Status,
+    /// LCD memory buckets (a list of [`BucketInfo`])
+    Buckets,
+    /// Statistics over the recent telemetry ([`StatsReport`]), over the last
+    /// `window_secs` (all samples kept if missing)
+    Stats {
+        #[serde(default)]
+        window_secs: Option<u64>,
+        /// Also report the time the liquid temperature spent above this (°C)
+        #[serde(default)]
+        above: Option<f32>,
+    },
+    /// Hold a channel at a duty until a profile is selected
+    SetDuty {
+        channel: Channel,
+        duty: u8,
+    },
+    /// Switch the cooling profile
+    SetProfile {
+        name: String,
+    },
+    SetBrightness {
+        brightness: u8,
+    },
+    SetOrientation {
+        orientation: u8,
+    },
+    /// Set the LCD visual mode; pauses rendering
+    SetLcdMode {
+        mode: u8,
+        index: u8,
+    },
+    /// Upload an image or GIF; pauses rendering
+    UploadImage {
+        path: PathBuf,
+    },
+    /// Pause or resume the LCD gauge of `start`
+    PauseRendering {
+        paused: bool,
+    },
+}
+
+/// Answer of the daemon.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ControlResponse {
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl ControlResponse {
+    /// Success without a result.
+    pub fn ok() -> Self {
+        Self {
+            ok: true,
+            result: None,
+            error: None,
+        }
+    }
+
+    /// Success with a result.
+    pub fn with_result<T: Serialize>(result: &T) -> Self {
+        match serde_json::to_value(result) {
+            Ok(value) => Self {
+                result: Some(value),
+                ..Self::ok()
+            },
+            Err(e) => Self::error(format!("Cannot serialize result: {}", e)),
+        }
+    }
+
+    /// Success with the value of `result`, or its error.
+    pub fn from_result<T: Serialize>(result: Result<T>) -> Self {
+        match result.map(|value| serde_json::to_value(value)) {
+            Ok(Ok(serde_json::Value::Null)) => Self::ok(),
+            Ok(Ok(value)) => Self {
+                result: Some(value),
+                ..Self::ok()
+            },
+            Ok(Err(e)) => Self::error(format!("Cannot serialize result: {}", e)),
+            Err(e) => Self::error(e.to_string()),
+        }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Self {
+            ok: false,
+            result: None,
+            error: Some(message.into()),
+        }
+    }
+
+    /// The result, or the error reported by the daemon.
+    pub fn into_result(self) -> Result<Option<serde_json::Value>> {
+        if self.ok {
+            Ok(self.result)
+        } else {
+            Err(KrakenError::InvalidInput(
+                self.error.unwrap_or_else(|| "Request failed".into()),
+            ))
+        }
+    }
+}
+
+/// Daemon state returned by [`ControlRequest::Status`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DaemonStatus {
+    /// RFC 3339 time of the reading
+    pub timestamp: String,
+    #[serde(flatten)]
+    pub status: DeviceStatus,
+    pub cpu_temp: Option<f32>,
+    pub gpu_temp: Option<f32>,
+    pub profile: Option<String>,
+    pub commanded_pump_duty: Option<u8>,
+    pub commanded_fan_duty: Option<u8>,
+    /// The LCD gauge of `start` is paused
+    pub rendering_paused: bool,
+    /// LCD brightness last set (%)
+    #[serde(default)]
+    pub brightness: Option<u8>,
+    /// USB serial of the device, to find its calibration
+    #[serde(default)]
+    pub serial: Option<String>,
+}
+
+impl DaemonStatus {
+    pub fn new(
+        record: &HistoryRecord,
+        rendering_paused: bool,
+        brightness: Option<u8>,
+        serial: Option<String>,
+    ) -> Self {
+        Self {
+            timestamp: record.timestamp.to_rfc3339(),
+            status: record.status.clone(),
+            cpu_temp: record.cpu_temp,
+            gpu_temp: record.gpu_temp,
+            profile: record.profile.clone(),
+            commanded_pump_duty: record.commanded.map(|(pump, _)| pump),
+            commanded_fan_duty: record.commanded.map(|(_, fan)| fan),
+            rendering_paused,
+            brightness,
+            serial,
+        }
+    }
+}
+
+// =============================================================================
+// Request Queue
+// =============================================================================
+
+/// A request waiting for the daemon loop to answer it.
+#[derive(Debug)]
+pub struct PendingRequest {
+    pub request: ControlRequest,
+    reply: async_channel::Sender<ControlResponse>,
+}
+
+impl PendingRequest {
+    pub fn respond(self, response: ControlResponse) {
+        // The client may have hung up
+        let _ = self.reply.try_send(response);
+    }
+}
+
+/// Requests of every control interface, answered by the daemon loop, which
+/// owns the device.
+#[derive(Debug)]
+pub struct RequestQueue {
+    sender: Sender<PendingRequest>,
+    receiver: Receiver<PendingRequest>,
+}
+
+impl Default for RequestQueue {
+    fn default() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self { sender, receiver }
+    }
+}
+
+impl RequestQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Handle for an interface to queue requests.
+    pub fn sender(&self) -> RequestSender {
+        RequestSender(self.sender.clone())
+    }
+
+    /// Wait up to `timeout` for the next request.
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<PendingRequest> {
+        self.receiver.recv_timeout(timeout).ok()
+    }
+}
+
+/// Queues requests for the daemon loop.
+#[derive(Debug, Clone)]
+pub struct RequestSender(Sender<PendingRequest>);
+
+impl RequestSender {
+    /// Queue `request`. The returned channel yields the answer, or closes if
+    /// the daemon stops first.
+    pub fn send(&self, request: ControlRequest) -> async_channel::Receiver<ControlResponse> {
+        let (reply, answer) = async_channel::bounded(1);
+        let _ = self.0.send(PendingRequest { request, reply });
+        answer
+    }
+}
+
+// =============================================================================
+// Server
+// =============================================================================
+
+/// Listening control socket.
+///
+/// Connections are read on background threads that queue their requests.
+/// On drop the accept thread is stopped and the socket file removed.
+#[derive(Debug)]
+pub struct ControlServer {
+    path: PathBuf,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl ControlServer {
+    /// Listen on the socket configured in `config`.
+    ///
+    /// # Errors
+    /// Fails if another daemon is listening on the socket, or if it cannot
+    /// be created or given the configured group and mode.
+    pub fn bind(config: &ControlConfig, requests: RequestSender) -> Result<Self> {
+        Self::bind_at(&socket_path(config)?, config, requests)
+    }
+
+    /// Listen on `path` with the group and mode of `config`.
+    #[cfg(unix)]
+    pub fn bind_at(path: &Path, config: &ControlConfig, requests: RequestSender) -> Result<Self> {
+        use std::os::unix::fs::PermissionsExt;
+
+        if path.exists() {
+            if UnixStream::connect(path).is_ok() {
+                return Err(KrakenError::InvalidInput(format!(
+                    "Another daemon is listening on {}",
+                    path.display()
+                )));
+            }
+            // Left behind by a daemon that did not exit cleanly
+            std::fs::remove_file(path).map_err(|e| socket_error(path, e))?;
+        }
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| socket_error(path, e))?;
+        }
+
+        let listener = UnixListener::bind(path).map_err(|e| socket_error(path, e))?;
+        let server = Self::spawn(path, listener, requests);
+
+        let gid = config.group.as_deref().map(group_id).transpose()?;
+        let mode = match &config.mode {
+            Some(mode) => u32::from_str_radix(mode.trim_start_matches("0o"), 8).map_err(|_| {
+                KrakenError::InvalidInput(format!("Invalid socket mode '{}'", mode))
+            })?,
+            None if gid.is_some() => 0o660,
+            None => 0o600,
+        };
+        std::os::unix::fs::chown(path, None, gid).map_err(|e| socket_error(path, e))?;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))
+            .map_err(|e| socket_error(path, e))?;
+
+        Ok(server)
+    }
+
+    #[cfg(not(unix))]
+    pub fn bind_at(
+        _path: &Path,
+        _config: &ControlConfig,
+        _requests: RequestSender,
+    ) -> Result<Self> {
+        Err(KrakenError::InvalidInput(
+            "The control socket requires a Unix platform".into(),
+        ))
+    }
+
+    #[cfg(unix)]
+    fn spawn(path: &Path, listener: UnixListener, requests: RequestSender) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let stop = stop.clone();
+            std::thread::spawn(move || {
+                for stream in listener.incoming().flatten() {
+                    if stop.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    let requests = requests.clone();
+                    std::thread::spawn(move || serve_connection(stream, requests));
+                }
+            })
+        };
+        Self {
+            path: path.to_path_buf(),
+            stop,
+            thread: Some(thread),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for ControlServer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        // Wake the accept loop with a connection of our own. If the socket
+        // file is already gone nothing can wake it, so don't wait for it.
+        #[cfg(unix)]
+        if let Some(thread) = self.thread.take()
+            && UnixStream::connect(&self.path).is_ok()
+        {
+            let _ = thread.join();
+        }
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Answer the requests of one client until it disconnects.
+#[cfg(unix)]
+fn serve_connection(stream: UnixStream, requests: RequestSender) {
+    let Ok(mut writer) = stream.try_clone() else {
+        return;
+    };
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str::<ControlRequest>(&line) {
+            Ok(request) => requests
+                .send(request)
+                .recv_blocking()
+                .unwrap_or_else(|_| ControlResponse::error("The daemon is shutting down")),
+            Err(e) => ControlResponse::error(format!("Invalid request: {}", e)),
+        };
+
+        let line = serde_json::to_string(&response).unwrap_or_default();
+        if writeln!(writer, "{}", line).is_err() {
+            break;
+        }
+    }
+}
+
+/// Group id of a group name (from /etc/group) or number.
+#[cfg(unix)]
+fn group_id(group: &str) -> Result<u32> {
+    if let Ok(gid) = group.parse() {
+        return Ok(gid);
+    }
+    std::fs::read_to_string("/etc/group")
+        .ok()
+        .and_then(|groups| {
+            groups.lines().find_map(|line| {
+                let mut fields = line.split(':');
+                (fields.next() == Some(group))
+                    .then(|| fields.nth(1)?.parse().ok())
+                    .flatten()
+            })
+        })
+        .ok_or_else(|| KrakenError::InvalidInput(format!("Unknown group '{}'", group)))
+}
+
+// =============================================================================
+// Client
+// =============================================================================
+
+/// Connection to a running daemon.
+#[derive(Debug)]
+pub struct ControlClient {
+    #[cfg(unix)]
+    stream: BufReader<UnixStream>,
+}
+
+impl ControlClient {
+    /// Connect to the socket configured in `config`, or `None` if no daemon
+    /// is listening.
+    pub fn connect_running(config: &ControlConfig) -> Option<Self> {
+        Self::connect(&socket_path(config).ok()?).ok()
+    }
+
+    #[cfg(unix)]
+    pub fn connect(path: &Path) -> Result<Self> {
+        let stream = UnixStream::connect(path).map_err(|e| socket_error(path, e))?;
+        stream
+            .set_read_timeout(Some(REPLY_TIMEOUT))
+            .map_err(|e| socket_error(path, e))?;
+        Ok(Self {
+            stream: BufReader::new(stream),
+        })
+    }
+
+    #[cfg(not(unix))]
+    pub fn connect(_path: &Path) -> Result<Self> {
+        Err(KrakenError::InvalidInput(
+            "The control socket requires a Unix platform".into(),
+        ))
+    }
+
+    /// Send a request and return its result.
+    ///
+    /// # Errors
+    /// Returns the error reported by the daemon, or `InvalidInput` if the
+    /// connection fails.
+    pub fn call(&mut self, request: &ControlRequest) -> Result<Option<serde_json::Value>> {
+        let invalid = |e: serde_json::Error| {
+            KrakenError::InvalidInput(format!("Invalid daemon response: {}", e))
+        };
+
+        let request = serde_json::to_string(request).map_err(invalid)?;
+        let response = self
+            .exchange(&request)
+            .map_err(|e| KrakenError::InvalidInput(format!("Daemon connection failed: {}", e)))?;
+        serde_json::from_str::<ControlResponse>(&response)
+            .map_err(invalid)?
+            .into_result()
+    }
+
+    /// Write one line and read the answer.
+    #[cfg(unix)]
+    fn exchange(&mut self, request: &str) -> std::io::Result<String> {
+        writeln!(self.stream.get_mut(), "{}", request)?;
+        let mut response = String::new();
+        if self.stream.read_line(&mut response)? == 0 {
+            return Err(std::io::ErrorKind::UnexpectedEof.into());
+        }
+        Ok(response)
+    }
+
+    #[cfg(not(unix))]
+    fn exchange(&mut self, _request: &str) -> std::io::Result<String> {
+        Err(std::io::ErrorKind::Unsupported.into())
+    }
+
+    /// Latest reading of the daemon.
+    pub fn status(&mut self) -> Result<DaemonStatus> {
+        self.query(&ControlRequest::Status)
+    }
+
+    /// LCD memory buckets, as read by the daemon.
+    pub fn buckets(&mut self) -> Result<Vec<BucketInfo>> {
+        self.query(&ControlRequest::Buckets)
+    }
+
+    /// Statistics over the daemon's recent telemetry.
+    pub fn stats(&mut self, window: Option<Duration>, above: Option<f32>) -> Result<StatsReport> {
+        self.query(&ControlRequest::Stats {
+            window_secs: window.map(|w| w.as_secs()),
+            above,
+        })
+    }
+
+    fn query<T: serde::de::DeserializeOwned>(&mut self, request: &ControlRequest) -> Result<T> {
+        let result = self.call(request)?.unwrap_or_default();
+        serde_json::from_value(result)
+            .map_err(|e| KrakenError::InvalidInput(format!("Invalid daemon response: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status() -> DaemonStatus {
+        DaemonStatus {
+            timestamp: "2026-03-01T12:00:00+00:00".into(),
+            status: DeviceStatus {
+                liquid_temp_c: 31.5,
+                pump_rpm: 2100,
+                pump_duty: 60,
+                fan_rpm: 900,
+                fan_duty: 40,
+            },
+            cpu_temp: Some(55.0),
+            gpu_temp: None,
+            profile: Some("quiet".into()),
+            commanded_pump_duty: Some(60),
+            commanded_fan_duty: Some(40),
+            rendering_paused: false,
+            brightness: Some(80),
+            serial: Some("ABC123".into()),
+        }
+    }
+
+    #[test]
+    fn test_protocol() {
+        let request: ControlRequest =
+            serde_json::from_str(r#"{"method":"set_duty","channel":"fan","duty":60}"#).unwrap();
+        assert_eq!(
+            request,
+            ControlRequest::SetDuty {
+                channel: Channel::Fan,
+                duty: 60
+            }
+        );
+        assert_eq!(
+            serde_json::to_string(&ControlRequest::Status).unwrap(),
+            r#"{"method":"status"}"#
+        );
+        assert!(serde_json::from_str::<ControlRequest>(r#"{"method":"reboot"}"#).is_err());
+        assert_eq!(
+            serde_json::from_str::<ControlRequest>(r#"{"method":"stats","above":40}"#).unwrap(),
+            ControlRequest::Stats {
+                window_secs: None,
+                above: Some(40.0)
+            }
+        );
+
+        assert_eq!(
+            serde_json::to_string(&ControlResponse::from_result(Ok(()))).unwrap(),
+            r#"{"ok":true}"#
+        );
+        let failed = ControlResponse::from_result(Result::<()>::Err(KrakenError::Timeout));
+        assert!(failed.into_result().is_err());
+
+        let value = serde_json::to_value(status()).unwrap();
+        assert_eq!(value["pump_rpm"], 2100);
+        assert_eq!(
+            serde_json::from_value::<DaemonStatus>(value).unwrap(),
+            status()
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_socket() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("nzxt-control-{}", std::process::id()));
+        let path = dir.join(SOCKET_FILE);
+        let config = ControlConfig::default();
+        let queue = RequestQueue::new();
+        let server = ControlServer::bind_at(&path, &config, queue.sender()).unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert!(
+            ControlServer::bind_at(&path, &config, queue.sender()).is_err(),
+            "in use"
+        );
+
+        let client = std::thread::spawn({
+            let path = path.clone();
+            move || {
+                let mut client = ControlClient::connect(&path).unwrap();
+                let status = client.status().unwrap();
+                let profile = client.call(&ControlRequest::SetProfile {
+                    name: "nope".into(),
+                });
+
+                // Malformed lines are answered without reaching the daemon
+                let mut raw = UnixStream::connect(&path).unwrap();
+                raw.write_all(b"{\"method\":\"fly\"}\n").unwrap();
+                let mut line = String::new();
+                BufReader::new(raw).read_line(&mut line).unwrap();
+                (status, profile, line)
+            }
+        });
+
+        let pending = queue.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(pending.request, ControlRequest::Status);
+        pending.respond(ControlResponse::with_result(&status()));
+        let pending = queue.recv_timeout(Duration::from_secs(5)).unwrap();
+        pending.respond(ControlResponse::error("Profile 'nope' not found"));
+
+        let (received, profile, line) = client.join().unwrap();
+        assert_eq!(received, status());
+        assert!(
+            profile
+                .unwrap_err()
+                .to_string()
+                .contains("'nope' not found")
+        );
+        assert!(line.contains("\"ok\":false"), "{}", line);
+
+        // A socket left behind is replaced
+        drop(server);
+        assert!(!path.exists());
+        let stale = UnixListener::bind(&path).unwrap();
+        drop(stale);
+        assert!(ControlClient::connect(&path).is_err());
+        let server = ControlServer::bind_at(
+            &path,
+            &ControlConfig {
+                mode: Some("0640".into()),
+                ..Default::default()
+            },
+            queue.sender(),
+        )
+        .unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o640);
+        drop(server);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,408 @@
+//! D-Bus service of the daemons.
+//!
+//! `start` and `cooling-daemon` own `io.github.brittos.NzxtKraken` on the
+//! session or system bus. Properties mirror the latest tick and emit
+//! `PropertiesChanged`; methods queue a [`ControlRequest`] for the daemon
+//! loop, like the control socket.
+//!
+//! | Member | Signature |
+//! |--------|-----------|
+//! | `LiquidTemperature` | `d` (°C) |
+//! | `PumpRpm`, `FanRpm` | `q` |
+//! | `PumpDuty`, `FanDuty`, `Brightness` | `y` (%) |
+//! | `ActiveProfile` | `s` |
+//! | `SetProfile(name)` | `s` |
+//! | `SetFixedDuty(channel, duty)` | `sy` ("pump" / "fan") |
+//! | `SetBrightness(brightness)` | `y` |
+//! | `SetOrientation(degrees)` | `q` (0, 90, 180, 270) |
+//! | `UploadImage(path)` | `s` (absolute) |
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use zbus::fdo;
+use zbus::interface;
+use zbus::names::InterfaceName;
+use zbus::object_server::SignalEmitter;
+use zbus::zvariant::Value;
+
+use super::control::{ControlRequest, RequestSender};
+use crate::error::{KrakenError, Result};
+use crate::storage::{DbusBus, DbusConfig};
+use crate::telemetry::HistoryRecord;
+use crate::utils::parsing::parse_channel;
+
+/// Well-known name of the service.
+pub const BUS_NAME: &str = "io.github.brittos.NzxtKraken";
+
+/// Path of the device object.
+pub const OBJECT_PATH: &str = "/io/github/brittos/NzxtKraken";
+
+/// Interface of the device object.
+pub const INTERFACE_NAME: &str = "io.github.brittos.NzxtKraken1";
+
+fn dbus_error(e: zbus::Error) -> KrakenError {
+    KrakenError::InvalidInput(format!("D-Bus: {}", e))
+}
+
+/// Values of the D-Bus properties.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DbusState {
+    pub liquid_temperature: f64,
+    pub pump_rpm: u16,
+    pub fan_rpm: u16,
+    pub pump_duty: u8,
+    pub fan_duty: u8,
+    pub active_profile: String,
+    pub brightness: u8,
+}
+
+impl DbusState {
+    pub fn new(record: &HistoryRecord, brightness: u8) -> Self {
+        Self {
+            liquid_temperature: record.status.liquid_temp_c.into(),
+            pump_rpm: record.status.pump_rpm,
+            fan_rpm: record.status.fan_rpm,
+            pump_duty: record.status.pump_duty,
+            fan_duty: record.status.fan_duty,
+            active_profile: record.profile.clone().unwrap_or_default(),
+            brightness,
+        }
+    }
+}
+
+/// Properties shared by the interface and [`DbusService::publish`].
+///
+/// zbus holds the interface lock while a method awaits the daemon loop, so
+/// the loop must never need it: it writes here and emits the signal itself.
+type SharedState = Arc<Mutex<DbusState>>;
+
+fn lock(state: &SharedState) -> MutexGuard<'_, DbusState> {
+    // A panic while publishing leaves plain values behind; keep serving them
+    state.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+struct KrakenInterface {
+    requests: RequestSender,
+    state: SharedState,
+}
+
+impl KrakenInterface {
+    /// Queue a request and wait for the daemon loop to answer it.
+    async fn call(&self, request: ControlRequest) -> fdo::Result<()> {
+        match self.requests.send(request).recv().await {
+            Ok(response) => response
+                .into_result()
+                .map(drop)
+                .map_err(|e| fdo::Error::Failed(e.to_string())),
+            Err(_) => Err(fdo::Error::Failed("The daemon is shutting down".into())),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, DbusState> {
+        lock(&self.state)
+    }
+}
+
+#[interface(name = "io.github.brittos.NzxtKraken1")]
+impl KrakenInterface {
+    /// Switch the cooling profile.
+    async fn set_profile(&self, name: String) -> fdo::Result<()> {
+        self.call(ControlRequest::SetProfile { name }).await
+    }
+
+    /// Hold "pump" or "fan" at a duty until a profile is selected.
+    async fn set_fixed_duty(&self, channel: String, duty: u8) -> fdo::Result<()> {
+        let channel =
+            parse_channel(&channel).map_err(|e| fdo::Error::InvalidArgs(e.to_string()))?;
+        self.call(ControlRequest::SetDuty { channel, duty }).await
+    }
+
+    async fn set_brightness(&self, brightness: u8) -> fdo::Result<()> {
+        self.call(ControlRequest::SetBrightness { brightness })
+            .await
+    }
+
+    /// Rotate the LCD by 0, 90, 180 or 270 degrees.
+    async fn set_orientation(&self, degrees: u16) -> fdo::Result<()> {
+        if !degrees.is_multiple_of(90) || degrees > 270 {
+            return Err(fdo::Error::InvalidArgs(format!(
+                "Invalid orientation {}°. Use 0, 90, 180 or 270",
+                degrees
+            )));
+        }
+        let orientation = (degrees / 90) as u8;
+        self.call(ControlRequest::SetOrientation { orientation })
+            .await
+    }
+
+    /// Upload an image or GIF; pauses the LCD gauge.
+    async fn upload_image(&self, path: String) -> fdo::Result<()> {
+        let path = PathBuf::from(path);
+        if !path.is_absolute() {
+            return Err(fdo::Error::InvalidArgs(format!(
+                "{} is not an absolute path",
+                path.display()
+            )));
+        }
+        self.call(ControlRequest::UploadImage { path }).await
+    }
+
+    #[zbus(property)]
+    fn liquid_temperature(&self) -> f64 {
+        self.state().liquid_temperature
+    }
+
+    #[zbus(property)]
+    fn pump_rpm(&self) -> u16 {
+        self.state().pump_rpm
+    }
+
+    #[zbus(property)]
+    fn fan_rpm(&self) -> u16 {
+        self.state().fan_rpm
+    }
+
+    #[zbus(property)]
+    fn pump_duty(&self) -> u8 {
+        self.state().pump_duty
+    }
+
+    #[zbus(property)]
+    fn fan_duty(&self) -> u8 {
+        self.state().fan_duty
+    }
+
+    #[zbus(property)]
+    fn active_profile(&self) -> String {
+        self.state().active_profile.clone()
+    }
+
+    #[zbus(property)]
+    fn brightness(&self) -> u8 {
+        self.state().brightness
+    }
+}
+
+/// D-Bus service of a daemon.
+pub struct DbusService {
+    connection: zbus::blocking::Connection,
+    state: SharedState,
+}
+
+impl DbusService {
+    /// Own [`BUS_NAME`] on the configured bus and serve the device object.
+    ///
+    /// # Errors
+    /// Fails if the bus is unreachable or the name is taken (another daemon
+    /// runs, or the system bus policy does not allow it).
+    pub fn connect(config: &DbusConfig, requests: RequestSender) -> Result<Self> {
+        let builder = match config.bus {
+            DbusBus::Session => zbus::blocking::connection::Builder::session(),
+            DbusBus::System => zbus::blocking::connection::Builder::system(),
+        }
+        .and_then(|builder| builder.name(BUS_NAME))
+        .map_err(dbus_error)?;
+        Self::serve(builder, requests)
+    }
+
+    /// Serve the device object on a connection being built.
+    pub fn serve(
+        builder: zbus::blocking::connection::Builder<'_>,
+        requests: RequestSender,
+    ) -> Result<Self> {
+        let state = SharedState::default();
+        let interface = KrakenInterface {
+            requests,
+            state: state.clone(),
+        };
+        let connection = builder
+            .serve_at(OBJECT_PATH, interface)
+            .and_then(|builder| builder.build())
+            .map_err(dbus_error)?;
+        Ok(Self { connection, state })
+    }
+
+    /// Update the properties, emitting `PropertiesChanged` for those that
+    /// changed.
+    ///
+    /// Never waits for the interface, so it returns while a method call is
+    /// pending on the daemon loop.
+    pub fn publish(&self, state: DbusState) -> Result<()> {
+        let old = std::mem::replace(&mut *lock(&self.state), state.clone());
+
+        let mut changed: HashMap<&str, Value<'_>> = HashMap::new();
+        if old.liquid_temperature != state.liquid_temperature {
+            changed.insert("LiquidTemperature", state.liquid_temperature.into());
+        }
+        if old.pump_rpm != state.pump_rpm {
+            changed.insert("PumpRpm", state.pump_rpm.into());
+        }
+        if old.fan_rpm != state.fan_rpm {
+            changed.insert("FanRpm", state.fan_rpm.into());
+        }
+        if old.pump_duty != state.pump_duty {
+            changed.insert("PumpDuty", state.pump_duty.into());
+        }
+        if old.fan_duty != state.fan_duty {
+            changed.insert("FanDuty", state.fan_duty.into());
+        }
+        if old.active_profile != state.active_profile {
+            changed.insert("ActiveProfile", state.active_profile.as_str().into());
+        }
+        if old.brightness != state.brightness {
+            changed.insert("Brightness", state.brightness.into());
+        }
+        if changed.is_empty() {
+            return Ok(());
+        }
+
+        let emitter =
+            SignalEmitter::new(self.connection.inner(), OBJECT_PATH).map_err(dbus_error)?;
+        let interface = InterfaceName::from_static_str_unchecked(INTERFACE_NAME);
+        zbus::block_on(fdo::Properties::properties_changed(
+            &emitter,
+            interface,
+            changed,
+            Cow::Borrowed(&[]),
+        ))
+        .map_err(dbus_error)
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::ipc::{ControlResponse, RequestQueue};
+    use crate::protocol::{Channel, DeviceStatus};
+    use std::time::Duration;
+
+    /// A service and a proxy to it over a peer-to-peer connection.
+    fn connect(
+        requests: crate::ipc::RequestSender,
+    ) -> (DbusService, zbus::blocking::Proxy<'static>) {
+        let (server_stream, client_stream) = std::os::unix::net::UnixStream::pair().unwrap();
+        let service = std::thread::spawn(move || {
+            let builder = zbus::blocking::connection::Builder::async_io_unix_stream(server_stream)
+                .server(zbus::Guid::generate())
+                .unwrap()
+                .p2p();
+            DbusService::serve(builder, requests).unwrap()
+        });
+        let client = zbus::blocking::connection::Builder::async_io_unix_stream(client_stream)
+            .p2p()
+            .build()
+            .unwrap();
+        let service = service.join().unwrap();
+
+        let proxy = zbus::blocking::proxy::Builder::<zbus::blocking::Proxy>::new(&client)
+            .destination(BUS_NAME)
+            .unwrap()
+            .path(OBJECT_PATH)
+            .unwrap()
+            .interface(INTERFACE_NAME)
+            .unwrap()
+            .cache_properties(zbus::proxy::CacheProperties::No)
+            .build()
+            .unwrap();
+        (service, proxy)
+    }
+
+    fn record(liquid_temp_c: f32) -> HistoryRecord {
+        let mut record = HistoryRecord::new(&DeviceStatus {
+            liquid_temp_c,
+            pump_rpm: 2100,
+            pump_duty: 60,
+            fan_rpm: 900,
+            fan_duty: 40,
+        });
+        record.profile = Some("quiet".into());
+        record
+    }
+
+    #[test]
+    fn test_service() {
+        let queue = RequestQueue::new();
+        let (service, proxy) = connect(queue.sender());
+
+        service.publish(DbusState::new(&record(31.5), 80)).unwrap();
+        assert_eq!(
+            proxy.get_property::<f64>("LiquidTemperature").unwrap(),
+            31.5
+        );
+        assert_eq!(proxy.get_property::<u16>("PumpRpm").unwrap(), 2100);
+        assert_eq!(
+            proxy.get_property::<String>("ActiveProfile").unwrap(),
+            "quiet"
+        );
+        assert_eq!(proxy.get_property::<u8>("Brightness").unwrap(), 80);
+
+        // Methods are answered by the daemon loop
+        let daemon = std::thread::spawn(move || {
+            let mut received = Vec::new();
+            for response in [
+                ControlResponse::ok(),
+                ControlResponse::error("Profile 'x' not found"),
+            ] {
+                let pending = queue.recv_timeout(Duration::from_secs(5)).unwrap();
+                received.push(pending.request.clone());
+                pending.respond(response);
+            }
+            received
+        });
+        proxy
+            .call::<_, _, ()>("SetFixedDuty", &("fan", 60u8))
+            .unwrap();
+        let error = proxy.call::<_, _, ()>("SetProfile", &("x",)).unwrap_err();
+        assert!(error.to_string().contains("'x' not found"), "{}", error);
+        assert_eq!(
+            daemon.join().unwrap(),
+            vec![
+                ControlRequest::SetDuty {
+                    channel: Channel::Fan,
+                    duty: 60
+                },
+                ControlRequest::SetProfile { name: "x".into() },
+            ]
+        );
+
+        // Invalid arguments never reach the daemon
+        assert!(proxy.call::<_, _, ()>("SetOrientation", &(45u16,)).is_err());
+        assert!(
+            proxy
+                .call::<_, _, ()>("UploadImage", &("relative.png",))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_publish_while_call_pending() {
+        let queue = RequestQueue::new();
+        let (service, proxy) = connect(queue.sender());
+
+        let caller = {
+            let proxy = proxy.clone();
+            std::thread::spawn(move || proxy.call::<_, _, ()>("SetFixedDuty", &("fan", 60u8)))
+        };
+        let pending = queue.recv_timeout(Duration::from_secs(5)).unwrap();
+
+        // The daemon loop publishes before it gets to answer the call
+        let (done, published) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            service.publish(DbusState::new(&record(33.0), 80)).unwrap();
+            done.send(service).unwrap();
+        });
+        let _service = published
+            .recv_timeout(Duration::from_secs(5))
+            .expect("publish blocked on the pending call");
+        assert_eq!(
+            proxy.get_property::<f64>("LiquidTemperature").unwrap(),
+            33.0
+        );
+
+        pending.respond(ControlResponse::ok());
+        caller.join().unwrap().unwrap();
+    }
+}
@@ -0,0 +1,14 @@
+//! Inter-process control of the running daemon.
+//!
+//! The daemons own the HID handle. Other processes query and control the
+//! cooler through them instead of opening the device a second time: over a
+//! Unix socket ([`control`]) or D-Bus ([`dbus`]).
+
+pub mod control;
+pub mod dbus;
+
+pub use control::{
+    ControlClient, ControlRequest, ControlResponse, ControlServer, DaemonStatus, PendingRequest,
+    RequestQueue, RequestSender,
+};
+pub use dbus::{DbusService, DbusState};
@@ -37,12 +37,18 @@
 //! }
 //! ```
 
+// Some tests configure a default struct field by field
+#![cfg_attr(test, allow(clippy::field_reassign_with_default))]
+
 pub mod config;
 pub mod cooling;
 pub mod device;
 pub mod error;
+pub mod ipc;
 pub mod protocol;
 pub mod storage;
+pub mod telemetry;
+pub mod tui;
 pub mod utils;
 
 // Re-exports for convenience
@@ -53,4 +59,4 @@ pub use protocol::Channel;
 // Re-exports for Radial Gauge Editor (GUI)
 pub use storage::{StoredGradientStop, StoredRadialGaugeConfig};
 pub use utils::radial_gauge::{GradientStop, RadialGaugeConfig};
-pub use utils::stats_image::generate_radial_stats_image;
+pub use utils::stats_image::{generate_radial_stats_image, generate_radial_trend_image};
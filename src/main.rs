@@ -10,9 +10,13 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
 
 use nzxt_rust_devices::device::KrakenZ63;
+use nzxt_rust_devices::ipc::ControlRequest;
+use nzxt_rust_devices::protocol::Channel;
 
+use nzxt_rust_devices::config::SpeedProfile;
 use nzxt_rust_devices::storage;
-use nzxt_rust_devices::utils::parsing::{parse_channel, parse_speed_profile};
+use nzxt_rust_devices::utils::OutputFormat;
+use nzxt_rust_devices::utils::parsing::{parse_channel, parse_channel_curve, parse_speed_profile};
 use nzxt_rust_devices::utils::sensors::SystemSensors;
 
 // =============================================================================
@@ -24,6 +28,11 @@ use nzxt_rust_devices::utils::sensors::SystemSensors;
 #[command(name = "nzxt-kraken-cli")]
 #[command(author, version, about, long_about = None)]
 struct Args {
+    /// Output format of status, list, info, list-buckets, sensors and monitor
+    /// (table, json, yaml, jsonl)
+    #[arg(long, global = true, default_value = "table")]
+    format: OutputFormat,
+
     #[command(subcommand)]
     command: Command,
 }
@@ -89,10 +98,22 @@ enum Command {
         path: PathBuf,
     },
 
-    /// Apply a speed profile
+    /// Pause the LCD gauge of the running daemon
+    Pause,
+
+    /// Resume the LCD gauge of the running daemon
+    Resume,
+
+    /// Apply a speed profile, or manage stored profiles
+    #[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
     Profile {
-        /// Profile name: silent, performance, or fixed:XX
-        name: String,
+        #[command(subcommand)]
+        action: Option<ProfileCommand>,
+
+        /// Profile name: silent, performance, fixed:XX, or an expression
+        /// such as "spline(20:30,59:100)+clamp(min=30)"
+        #[arg(required = true)]
+        name: Option<String>,
 
         /// Channel to apply profile: fan or pump
         #[arg(short, long, default_value = "fan")]
@@ -145,11 +166,82 @@ enum Command {
     },
 
     /// Diagnostic: List all available system sensors
-    Sensors,
+    Sensors {
+        /// Choose the CPU and GPU sensors and save them to config.json
+        #[arg(long)]
+        pick: bool,
+    },
+
+    /// Measure the duty→RPM table of the pump/fan and save it for this device
+    Calibrate {
+        /// Channel to calibrate: pump, fan or both
+        #[arg(short, long, default_value = "both")]
+        channel: String,
+
+        /// Duty step in percent (default: 10)
+        #[arg(long, default_value = "10", value_parser = clap::value_parser!(u8).range(1..=50))]
+        step: u8,
+
+        /// Maximum seconds to wait for RPM to settle at each step (default: 20)
+        #[arg(long, default_value = "20")]
+        settle: u64,
+
+        /// Show the stored calibration instead of running a sweep
+        #[arg(long)]
+        show: bool,
+    },
+
+    /// Replay a temperature trace through profile curves (no device needed)
+    Simulate {
+        /// Trace file: CSV with a header row, or JSONL
+        trace: PathBuf,
+
+        /// Profile from defaults.json (default: silent)
+        #[arg(short, long, default_value = "silent")]
+        profile: String,
+
+        /// Second profile to compare side by side
+        #[arg(long)]
+        compare: Option<String>,
+
+        /// Temperature source: liquid or cpu (default: liquid)
+        #[arg(short, long, default_value = "liquid")]
+        source: String,
+
+        /// Seconds between samples if the trace has no time column (default: 2)
+        #[arg(short, long, default_value = "2")]
+        interval: u64,
+
+        /// Add the load feed-forward boost from config.json (needs a cpu_load column)
+        #[arg(long)]
+        load: bool,
+
+        /// Print every sample instead of only the duty changes
+        #[arg(long)]
+        full: bool,
+    },
+
+    /// Statistics over the recent telemetry of the running daemon
+    Stats {
+        /// Only the last DURATION, e.g. 30s, 5m, 1h (default: all samples kept)
+        #[arg(short, long)]
+        window: Option<String>,
+
+        /// Report the time the liquid temperature spent above this (°C)
+        #[arg(long)]
+        above: Option<f32>,
+    },
+
+    /// Live terminal dashboard: graphs, duty gauges, profile curve and LCD buckets
+    Tui {
+        /// Seconds between readings (default: 1)
+        #[arg(short, long, default_value = "1")]
+        interval: u64,
+    },
 
     /// Run cooling daemon with temperature-based fan/pump control
     CoolingDaemon {
-        /// Profile name: silent, performance, fixed (default: silent)
+        /// Profile name or expression, e.g. "max(cpu,liquid)+curve(20:30,59:100)" (default: silent)
         #[arg(short, long, default_value = "silent")]
         profile: String,
 
@@ -160,13 +252,27 @@ enum Command {
         /// Update interval in seconds (default: 2)
         #[arg(short, long, default_value = "2")]
         interval: u64,
+
+        /// Hold the pump at this RPM instead of following the curve
+        #[arg(long)]
+        pump_rpm: Option<u16>,
+
+        /// Hold the fan at this RPM instead of following the curve
+        #[arg(long)]
+        fan_rpm: Option<u16>,
+
+        /// Serve Prometheus metrics on this address, e.g. 127.0.0.1:9185
+        /// (default: config.json metrics section)
+        #[arg(long)]
+        metrics: Option<String>,
     },
 
     /// Start unified LCD monitor + Cooling daemon
     Start {
-        /// Cooling profile name: silent, performance, fixed (default: silent)
-        #[arg(short, long, default_value = "silent")]
-        profile: String,
+        /// Cooling profile name or expression
+        /// (default: config.json startup, then the active profile)
+        #[arg(short, long)]
+        profile: Option<String>,
 
         /// Temperature source: liquid or cpu (default: liquid)
         #[arg(short, long, default_value = "liquid")]
@@ -175,7 +281,183 @@ enum Command {
         /// Update interval in seconds (default: 2)
         #[arg(short = 'n', long, default_value = "2")]
         interval: u64,
+
+        /// Hold the pump at this RPM instead of following the curve
+        #[arg(long)]
+        pump_rpm: Option<u16>,
+
+        /// Hold the fan at this RPM instead of following the curve
+        #[arg(long)]
+        fan_rpm: Option<u16>,
+
+        /// Serve Prometheus metrics on this address, e.g. 127.0.0.1:9185
+        /// (default: config.json metrics section)
+        #[arg(long)]
+        metrics: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ProfileCommand {
+    /// List stored profiles (* marks the active one)
+    List,
+
+    /// Show a stored profile's curves
+    Show {
+        /// Profile name
+        name: String,
+    },
+
+    /// Plot a stored profile's curves and 40-point device tables
+    Plot {
+        /// Profile name or expression
+        name: String,
+
+        /// Write a PNG chart here instead of plotting in the terminal
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Mark the current operating point (reads the device)
+        #[arg(long)]
+        live: bool,
+
+        /// Temperature source for --live: liquid or cpu (default: liquid)
+        #[arg(short, long, default_value = "liquid")]
+        source: String,
+    },
+
+    /// Check profiles for invalid or risky curves
+    Check {
+        /// Profile name or expression (default: all stored profiles)
+        name: Option<String>,
+    },
+
+    /// Create a profile from inline curves
+    Create {
+        /// Profile name
+        name: String,
+
+        /// Channel curves, e.g. pump=20:70,45:80,59:100 fan=fixed:40
+        /// (unspecified channels start from the Silent curves)
+        curves: Vec<String>,
+    },
+
+    /// Replace channel curves of a stored profile
+    Edit {
+        /// Profile name
+        name: String,
+
+        /// Channel curves, e.g. fan=20:30,50:60,59:100
+        #[arg(required = true)]
+        curves: Vec<String>,
+    },
+
+    /// Copy a profile under a new name
+    Clone {
+        /// Profile to copy
+        source: String,
+
+        /// Name of the copy
+        name: String,
+    },
+
+    /// Delete a profile
+    Delete {
+        /// Profile name
+        name: String,
+    },
+
+    /// Rename a profile
+    Rename {
+        /// Current name
+        old: String,
+
+        /// New name
+        new: String,
+    },
+
+    /// Make a profile the default for `start`
+    Activate {
+        /// Profile name
+        name: String,
+    },
+
+    /// Import profiles from an NZXT CAM coolingController.json
+    Import {
+        /// Path to the CAM file
+        path: PathBuf,
     },
+
+    /// Export profiles as an NZXT CAM coolingController.json
+    Export {
+        /// Output file (default: print to stdout)
+        path: Option<PathBuf>,
+    },
+}
+
+// =============================================================================
+// Machine-Readable Output
+// =============================================================================
+
+/// `status` document: the device readings plus calibrated expectations and,
+/// when a daemon answered, its state.
+#[derive(serde::Serialize)]
+struct StatusOutput<'a> {
+    #[serde(flatten)]
+    status: &'a nzxt_rust_devices::protocol::DeviceStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expected_pump_rpm: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expected_fan_rpm: Option<u16>,
+    #[serde(flatten)]
+    daemon: Option<DaemonOutput<'a>>,
+}
+
+/// Daemon fields of the `status` document.
+#[derive(serde::Serialize)]
+struct DaemonOutput<'a> {
+    timestamp: &'a str,
+    cpu_temp: Option<f32>,
+    gpu_temp: Option<f32>,
+    profile: Option<&'a str>,
+    commanded_pump_duty: Option<u8>,
+    commanded_fan_duty: Option<u8>,
+    rendering_paused: bool,
+    brightness: Option<u8>,
+}
+
+impl<'a> From<&'a nzxt_rust_devices::ipc::DaemonStatus> for DaemonOutput<'a> {
+    fn from(status: &'a nzxt_rust_devices::ipc::DaemonStatus) -> Self {
+        Self {
+            timestamp: &status.timestamp,
+            cpu_temp: status.cpu_temp,
+            gpu_temp: status.gpu_temp,
+            profile: status.profile.as_deref(),
+            commanded_pump_duty: status.commanded_pump_duty,
+            commanded_fan_duty: status.commanded_fan_duty,
+            rendering_paused: status.rendering_paused,
+            brightness: status.brightness,
+        }
+    }
+}
+
+/// One `monitor` sample.
+#[derive(serde::Serialize)]
+struct MonitorSample<'a> {
+    timestamp: String,
+    #[serde(flatten)]
+    status: &'a nzxt_rust_devices::protocol::DeviceStatus,
+    cpu_temp: Option<f32>,
+    gpu_temp: Option<f32>,
+    cpu_load: f32,
+}
+
+/// Print `value` as one document in a machine-readable format.
+fn print_formatted<T: serde::Serialize>(format: OutputFormat, value: &T) -> Result<()> {
+    if let Some(text) = format.render(value)? {
+        print!("{}", text);
+    }
+    Ok(())
 }
 
 // =============================================================================
@@ -185,38 +467,93 @@ enum Command {
 fn main() -> Result<()> {
     let args = Args::parse();
 
+    let format = args.format;
+
     match args.command {
-        Command::Status => cmd_status(),
-        Command::Monitor { interval } => cmd_monitor(interval),
+        Command::Status => cmd_status(format),
+        Command::Monitor { interval } => cmd_monitor(interval, format),
         Command::SetPump { duty } => cmd_set_pump(duty),
         Command::SetFan { duty } => cmd_set_fan(duty),
         Command::SetBrightness { brightness } => cmd_set_brightness(brightness),
         Command::SetLcdMode { mode, index } => cmd_set_lcd_mode(mode, index),
         Command::SetOrientation { orientation } => cmd_set_orientation(orientation),
         Command::DeleteBuckets => cmd_delete_buckets(),
-        Command::ListBuckets => cmd_list_buckets(),
+        Command::ListBuckets => cmd_list_buckets(format),
         Command::UploadImage { path } => cmd_upload_image(&path),
+        Command::Pause => cmd_pause_rendering(true),
+        Command::Resume => cmd_pause_rendering(false),
         Command::LcdProfile { name } => cmd_lcd_profile(&name),
-        Command::Profile { name, channel } => cmd_profile(&name, &channel),
-        Command::List => cmd_list(),
-        Command::Info => cmd_info(),
+        Command::Profile {
+            action: Some(action),
+            ..
+        } => cmd_profile_manage(action),
+        Command::Profile { name, channel, .. } => {
+            cmd_profile(name.as_deref().unwrap_or_default(), &channel)
+        }
+        Command::List => cmd_list(format),
+        Command::Info => cmd_info(format),
         Command::Debug { count } => cmd_debug(count),
         Command::DebugLcd => cmd_debug_lcd(),
         Command::DiscoverPresets { mode, max } => cmd_discover_presets(mode, max),
         Command::CheckBulk => cmd_check_bulk(),
         Command::LcdStats => cmd_lcd_stats(),
         Command::LcdMonitor { interval } => cmd_lcd_monitor(interval),
-        Command::Sensors => cmd_sensors(),
+        Command::Sensors { pick } => cmd_sensors(format, pick),
+        Command::Calibrate {
+            channel,
+            step,
+            settle,
+            show,
+        } => cmd_calibrate(&channel, step, settle, show),
+        Command::Simulate {
+            trace,
+            profile,
+            compare,
+            source,
+            interval,
+            load,
+            full,
+        } => cmd_simulate(
+            &trace,
+            &profile,
+            compare.as_deref(),
+            &source,
+            interval,
+            load,
+            full,
+        ),
+        Command::Stats { window, above } => cmd_stats(window.as_deref(), above, format),
+        Command::Tui { interval } => cmd_tui(interval),
         Command::CoolingDaemon {
             profile,
             source,
             interval,
-        } => cmd_cooling_daemon(&profile, &source, interval),
+            pump_rpm,
+            fan_rpm,
+            metrics,
+        } => cmd_cooling_daemon(
+            &profile,
+            &source,
+            interval,
+            pump_rpm,
+            fan_rpm,
+            metrics.as_deref(),
+        ),
         Command::Start {
             profile,
             source,
             interval,
-        } => cmd_start(&profile, &source, interval),
+            pump_rpm,
+            fan_rpm,
+            metrics,
+        } => cmd_start(
+            profile.as_deref(),
+            &source,
+            interval,
+            pump_rpm,
+            fan_rpm,
+            metrics.as_deref(),
+        ),
     }
 }
 
@@ -224,7 +561,20 @@ fn main() -> Result<()> {
 // Command Implementations
 // =============================================================================
 
+/// Connection to the running daemon, if one listens on the control socket.
+/// Commands route through it instead of opening the device a second time.
+fn daemon_client() -> Option<nzxt_rust_devices::ipc::ControlClient> {
+    let config = storage::load_config().unwrap_or_default();
+    nzxt_rust_devices::ipc::ControlClient::connect_running(&config.control)
+}
+
 fn cmd_set_brightness(brightness: u8) -> Result<()> {
+    if let Some(mut daemon) = daemon_client() {
+        daemon.call(&ControlRequest::SetBrightness { brightness })?;
+        println!("✅ LCD brightness set to {}% (via daemon)", brightness);
+        return Ok(());
+    }
+
     let kraken = KrakenZ63::open().context("Failed to open Kraken Z63")?;
     // kraken.initialize().context("Failed to initialize device")?; // Often not needed for brightness only
 
@@ -236,6 +586,15 @@ fn cmd_set_brightness(brightness: u8) -> Result<()> {
 }
 
 fn cmd_set_orientation(orientation: u8) -> Result<()> {
+    if let Some(mut daemon) = daemon_client() {
+        daemon.call(&ControlRequest::SetOrientation { orientation })?;
+        println!(
+            "✅ LCD orientation set to {}° (via daemon)",
+            orientation as u16 * 90
+        );
+        return Ok(());
+    }
+
     let kraken = KrakenZ63::open().context("Failed to open Kraken Z63")?;
 
     let angle = match orientation {
@@ -258,6 +617,15 @@ fn cmd_set_orientation(orientation: u8) -> Result<()> {
 }
 
 fn cmd_set_lcd_mode(mode: u8, index: u8) -> Result<()> {
+    if let Some(mut daemon) = daemon_client() {
+        daemon.call(&ControlRequest::SetLcdMode { mode, index })?;
+        println!(
+            "✅ LCD visual mode set to {} (idx {}) via daemon; `resume` restores the gauge",
+            mode, index
+        );
+        return Ok(());
+    }
+
     let mut kraken = KrakenZ63::open().context("Failed to open Kraken Z63")?;
     kraken.initialize().context("Failed to initialize device")?;
 
@@ -320,12 +688,26 @@ fn cmd_discover_presets(mode: u8, max_index: u8) -> Result<()> {
 }
 
 fn cmd_upload_image(path: &PathBuf) -> Result<()> {
-    use nzxt_rust_devices::device::bucket_manager::BucketManager;
-    use nzxt_rust_devices::device::bulk;
-    use nzxt_rust_devices::utils::image_processing;
+    if let Some(mut daemon) = daemon_client() {
+        // The daemon may run in another working directory
+        let path = std::fs::canonicalize(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        println!("📤 Uploading {} via daemon...", path.display());
+        daemon.call(&ControlRequest::UploadImage { path })?;
+        println!("✅ Image/GIF uploaded and displayed! `resume` restores the gauge");
+        return Ok(());
+    }
 
     let mut kraken = KrakenZ63::open().context("Failed to open Kraken Z63")?;
     kraken.initialize().context("Failed to initialize device")?;
+    upload_image(&kraken, path)
+}
+
+/// Upload an image or GIF to a free bucket and display it.
+fn upload_image(kraken: &KrakenZ63, path: &std::path::Path) -> Result<()> {
+    use nzxt_rust_devices::device::bucket_manager::BucketManager;
+    use nzxt_rust_devices::device::bulk;
+    use nzxt_rust_devices::utils::image_processing;
 
     // Get current LCD orientation to apply rotation
     let (_, orientation) = kraken.get_lcd_info().context("Failed to get LCD info")?;
@@ -337,12 +719,12 @@ fn cmd_upload_image(path: &PathBuf) -> Result<()> {
 
     // Initialize BucketManager to manage memory correctly
     let mut bucket_manager =
-        BucketManager::from_device(&kraken).context("Failed to initialize BucketManager")?;
+        BucketManager::from_device(kraken).context("Failed to initialize BucketManager")?;
 
     println!("🖼️  Processing image: {:?}", path);
 
     // Acquire a bucket from the manager (handles deletion/FIFO if needed)
-    let bucket_idx = bucket_manager.acquire(&kraken);
+    let bucket_idx = bucket_manager.acquire(kraken);
     println!("🪣 Selected bucket: {}", bucket_idx);
 
     // Check extension for GIF
@@ -384,20 +766,21 @@ fn cmd_upload_image(path: &PathBuf) -> Result<()> {
     Ok(())
 }
 
+fn cmd_pause_rendering(paused: bool) -> Result<()> {
+    let mut daemon = daemon_client().context("No daemon is running")?;
+    daemon.call(&ControlRequest::PauseRendering { paused })?;
+    if paused {
+        println!("⏸️  LCD gauge paused");
+    } else {
+        println!("▶️  LCD gauge resumed");
+    }
+    Ok(())
+}
+
 fn cmd_lcd_profile(name: &str) -> Result<()> {
-    let profile = match name.to_lowercase().as_str() {
-        "off" => nzxt_rust_devices::config::LcdProfile::OFF,
-        "night" => nzxt_rust_devices::config::LcdProfile::NIGHT,
-        "day" => nzxt_rust_devices::config::LcdProfile::DAY,
-        "max" => nzxt_rust_devices::config::LcdProfile::MAX,
-        _ => {
-            return Err(nzxt_rust_devices::KrakenError::InvalidInput(format!(
-                "Unknown LCD profile: {}",
-                name
-            ))
-            .into());
-        }
-    };
+    let profile = nzxt_rust_devices::config::LcdProfile::from_name(name).ok_or_else(|| {
+        nzxt_rust_devices::KrakenError::InvalidInput(format!("Unknown LCD profile: {}", name))
+    })?;
 
     let kraken = KrakenZ63::open().context("Failed to open Kraken Z63")?;
     // Initialization might not be needed for just setting mode/brightness if already running?
@@ -415,38 +798,107 @@ fn cmd_lcd_profile(name: &str) -> Result<()> {
     Ok(())
 }
 
-fn cmd_status() -> Result<()> {
-    let mut kraken = KrakenZ63::open().context("Failed to open Kraken Z63")?;
-    kraken.initialize().context("Failed to initialize device")?;
-    let status = kraken.get_status().context("Failed to read status")?;
+fn cmd_status(format: OutputFormat) -> Result<()> {
+    // With a daemon running, ask it rather than open the device a second time
+    let (status, daemon, calibration) = match daemon_client() {
+        Some(mut client) => {
+            let daemon = client.status()?;
+            let calibration = daemon.serial.as_deref().and_then(load_serial_calibration);
+            (daemon.status.clone(), Some(daemon), calibration)
+        }
+        None => {
+            let mut kraken = KrakenZ63::open().context("Failed to open Kraken Z63")?;
+            kraken.initialize().context("Failed to initialize device")?;
+            let status = kraken.get_status().context("Failed to read status")?;
+            (status, None, load_device_calibration(&kraken))
+        }
+    };
+
+    if !format.is_table() {
+        let expected = |channel: Channel| {
+            calibration
+                .as_ref()
+                .and_then(|c| c.channel(channel))
+                .and_then(|cal| cal.expected_rpm(status.duty(channel)))
+        };
+        return print_formatted(
+            format,
+            &StatusOutput {
+                status: &status,
+                expected_pump_rpm: expected(Channel::Pump),
+                expected_fan_rpm: expected(Channel::Fan),
+                daemon: daemon.as_ref().map(DaemonOutput::from),
+            },
+        );
+    }
     print!("{}", status);
+    if let Some(daemon) = &daemon {
+        if let Some(profile) = &daemon.profile {
+            println!("📋 Profile: {} (via daemon)", profile);
+        }
+        if daemon.rendering_paused {
+            println!("⏸️  LCD gauge paused");
+        }
+    }
+
+    // Compare against the calibration, if this device has one
+    if let Some(calibration) = calibration {
+        for channel in [Channel::Pump, Channel::Fan] {
+            let Some(cal) = calibration.channel(channel) else {
+                continue;
+            };
+            let duty = status.duty(channel);
+            let rpm = status.rpm(channel);
+            let Some(expected) = cal.expected_rpm(duty) else {
+                continue;
+            };
+            match cal.deviation(duty, rpm) {
+                Some(deviation) if deviation <= -20.0 => println!(
+                    "⚠️  {} expected ~{} RPM at {}%, measured {} ({:.0}%)",
+                    channel, expected, duty, rpm, deviation
+                ),
+                _ => println!("📏 {} expected ~{} RPM at {}%", channel, expected, duty),
+            }
+        }
+    }
     Ok(())
 }
 
-fn cmd_list_buckets() -> Result<()> {
+fn cmd_list_buckets(format: OutputFormat) -> Result<()> {
     let kraken = KrakenZ63::open().context("Failed to open Kraken Z63")?;
 
-    println!("📦 LCD Memory Buckets Status:");
-    println!("{}", "─".repeat(50));
-
     let buckets = kraken
         .query_all_buckets()
         .context("Failed to query buckets")?;
 
-    let mut total_used: u32 = 0;
-    let mut occupied_count = 0;
+    let occupied: Vec<_> = buckets.iter().filter(|b| b.occupied).collect();
+    let occupied_count = occupied.len();
+    // 1KB per page
+    let total_used: u32 = occupied.iter().map(|b| b.size_pages as u32).sum();
+
+    if !format.is_table() {
+        return print_formatted(
+            format,
+            &serde_json::json!({
+                "buckets": buckets,
+                "occupied": occupied_count,
+                "used_kb": total_used,
+                "total_kb": 24320,
+            }),
+        );
+    }
+
+    println!("📦 LCD Memory Buckets Status:");
+    println!("{}", "─".repeat(50));
 
-    for (idx, exists, start_page, size_pages) in &buckets {
-        let size_kb = *size_pages as u32; // 1KB per page
-        if *exists {
+    for bucket in &buckets {
+        if bucket.occupied {
             println!(
                 "  Bucket {:2}: ✅ Ocupado | Offset: {:5} KB | Size: {:4} KB",
-                idx, start_page, size_kb
+                bucket.index, bucket.start_page, bucket.size_pages
             );
-            total_used += size_kb;
-            occupied_count += 1;
         } else {
-            println!("  Bucket {:2}: ❌ Livre", idx);
+            println!("  Bucket {:2}: ❌ Livre", bucket.index);
         }
     }
 
@@ -459,14 +911,17 @@ fn cmd_list_buckets() -> Result<()> {
     Ok(())
 }
 
-fn cmd_monitor(interval_secs: u64) -> Result<()> {
+fn cmd_monitor(interval_secs: u64, format: OutputFormat) -> Result<()> {
+    use nzxt_rust_devices::telemetry::HistoryRecord;
     use sysinfo::System;
 
     let mut kraken = KrakenZ63::open().context("Failed to open Kraken Z63")?;
     kraken.initialize().context("Failed to initialize device")?;
 
     let mut sys = System::new_all();
-    let mut sensors = SystemSensors::new();
+    let config = storage::load_config().unwrap_or_default();
+    let mut sensors = system_sensors(&config)?;
+    let mut history = open_history(&config);
 
     // Setup Ctrl+C handler
     let running = Arc::new(AtomicBool::new(true));
@@ -477,11 +932,13 @@ fn cmd_monitor(interval_secs: u64) -> Result<()> {
     })
     .context("Failed to set Ctrl+C handler")?;
 
-    println!("🌡️  Monitoring Kraken Z63 (Ctrl+C to stop)...");
-    println!(
-        "📡 Syncing CPU/GPU telemetry to LCD every {}s\n",
-        interval_secs
-    );
+    if format.is_table() {
+        println!("🌡️  Monitoring Kraken Z63 (Ctrl+C to stop)...");
+        println!(
+            "📡 Syncing CPU/GPU telemetry to LCD every {}s\n",
+            interval_secs
+        );
+    }
 
     while running.load(Ordering::SeqCst) {
         // Refresh system data
@@ -499,6 +956,29 @@ fn cmd_monitor(interval_secs: u64) -> Result<()> {
         let _ = kraken.set_host_info(cpu_temp, gpu_temp);
 
         match kraken.get_status() {
+            Ok(status) if !format.is_table() => {
+                let sample = MonitorSample {
+                    timestamp: chrono::Local::now().to_rfc3339(),
+                    status: &status,
+                    cpu_temp: sensors.find_cpu_temp(),
+                    gpu_temp: sensors.find_gpu_temp(),
+                    cpu_load: sys.global_cpu_usage(),
+                };
+                if let Some(text) = format.render_sample(&sample)? {
+                    print!("{}", text);
+                    let _ = std::io::Write::flush(&mut std::io::stdout());
+                }
+
+                log_history(
+                    &mut history,
+                    HistoryRecord {
+                        cpu_temp: sample.cpu_temp,
+                        gpu_temp: sample.gpu_temp,
+                        cpu_load: Some(sample.cpu_load),
+                        ..HistoryRecord::new(&status)
+                    },
+                );
+            }
             Ok(status) => {
                 // Clear screen and move cursor to top
                 print!("\x1B[2J\x1B[1;1H");
@@ -538,6 +1018,16 @@ fn cmd_monitor(interval_secs: u64) -> Result<()> {
 
                 println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
                 print!("{}", status);
+
+                log_history(
+                    &mut history,
+                    HistoryRecord {
+                        cpu_temp: sensors.find_cpu_temp(),
+                        gpu_temp: sensors.find_gpu_temp(),
+                        cpu_load: Some(sys.global_cpu_usage()),
+                        ..HistoryRecord::new(&status)
+                    },
+                );
             }
             Err(e) => {
                 eprintln!("⚠️  Read error: {}", e);
@@ -547,17 +1037,37 @@ fn cmd_monitor(interval_secs: u64) -> Result<()> {
         std::thread::sleep(Duration::from_secs(interval_secs));
     }
 
-    println!("\n👋 Monitoring stopped.");
+    if format.is_table() {
+        println!("\n👋 Monitoring stopped.");
+    }
     Ok(())
 }
 
-fn cmd_sensors() -> Result<()> {
+/// System sensors with the selection of config.json.
+fn system_sensors(config: &storage::AppConfig) -> Result<SystemSensors> {
+    SystemSensors::with_config(&config.sensors).context("Invalid sensors section in config.json")
+}
+
+fn cmd_sensors(format: OutputFormat, pick: bool) -> Result<()> {
+    use nzxt_rust_devices::utils::sensors::SensorKind;
     use sysinfo::System;
 
-    println!("🔍 Scanning for system sensors...");
-    let sensors = SystemSensors::new();
+    let sensors = system_sensors(&storage::load_config().unwrap_or_default())?;
     let count = sensors.count();
 
+    if !format.is_table() && !pick {
+        return print_formatted(
+            format,
+            &serde_json::json!({
+                "sensors": sensors.list_all(),
+                "cpu_sensor": sensors.find_sensor(SensorKind::Cpu).map(|s| s.label),
+                "gpu_sensor": sensors.find_sensor(SensorKind::Gpu).map(|s| s.label),
+            }),
+        );
+    }
+
+    println!("🔍 Scanning for system sensors...");
+
     if count == 0 {
         println!("❌ No sensors detected. (Are you running as Admin?)");
         // Try refreshing system-wide just in case
@@ -568,40 +1078,102 @@ fn cmd_sensors() -> Result<()> {
     }
 
     println!("✅ Found {} sensors:\n", count);
-    println!("{:<40} | {:<10} | {:<10}", "Label", "Temp", "Critical");
-    println!("{}", "─".repeat(66));
+    println!(
+        "   {:>3} {:<40} | {:<10} | {:<10}",
+        "#", "Label", "Temp", "Critical"
+    );
+    println!("{}", "─".repeat(72));
 
-    // Get the CPU sensor that would be selected
-    let cpu_sensor = sensors.find_cpu_sensor();
+    // The sensors that are read as the CPU and GPU temperatures
+    let cpu_index = sensors.find_index(SensorKind::Cpu);
+    let gpu_index = sensors.find_index(SensorKind::Gpu);
     let all_sensors = sensors.list_all();
 
-    for sensor in &all_sensors {
+    for (i, sensor) in all_sensors.iter().enumerate() {
         let critical = sensor
             .critical
             .map(|c| format!("{:.1}°C", c))
             .unwrap_or_else(|| "-".to_string());
 
-        // Check if this is the sensor that would be selected
-        let is_selected = cpu_sensor
-            .as_ref()
-            .map(|s| s.label == sensor.label)
-            .unwrap_or(false);
-
-        let prefix = if is_selected { "👉" } else { "  " };
+        let prefix = if cpu_index == Some(i) {
+            "👉"
+        } else if gpu_index == Some(i) {
+            "🎮"
+        } else {
+            "  "
+        };
 
         println!(
-            "{} {:<40} | {:.1}°C    | {}",
-            prefix, sensor.label, sensor.temperature, critical
+            "{} {:>3} {:<40} | {:.1}°C    | {}",
+            prefix, i, sensor.label, sensor.temperature, critical
         );
     }
 
-    println!("{}", "─".repeat(66));
-    if cpu_sensor.is_none() {
-        println!("⚠️  Warning: Current logic would NOT select any of these sensors for CPU Temp.");
-    } else {
-        println!("👉 = Sensor currently selected by the app");
+    println!("{}", "─".repeat(72));
+    for (kind, icon) in [(SensorKind::Cpu, "👉"), (SensorKind::Gpu, "🎮")] {
+        match (sensors.find_sensor(kind), sensors.selector(kind)) {
+            (Some(sensor), Some(selector)) => {
+                println!("{} {}: {} (config: {})", icon, kind, sensor.label, selector)
+            }
+            (Some(sensor), None) => println!("{} {}: {} (detected)", icon, kind, sensor.label),
+            (None, _) => println!("⚠️  {}", sensors.describe_missing(kind)),
+        }
+    }
+
+    if pick {
+        cmd_sensors_pick(&all_sensors)?;
+    }
+    Ok(())
+}
+
+/// Ask which sensors to read as the CPU and GPU temperatures and save the
+/// answer to config.json.
+fn cmd_sensors_pick(sensors: &[nzxt_rust_devices::utils::sensors::SensorInfo]) -> Result<()> {
+    use nzxt_rust_devices::utils::sensors::{SensorKind, selector_for};
+    use std::io::Write;
+
+    let mut config = storage::load_config().context("Failed to load config.json")?;
+    println!();
+    for kind in [SensorKind::Cpu, SensorKind::Gpu] {
+        let selected = match kind {
+            SensorKind::Cpu => &mut config.sensors.cpu,
+            SensorKind::Gpu => &mut config.sensors.gpu,
+        };
+        let current = selected
+            .as_ref()
+            .map_or("auto".to_string(), |s| s.to_string());
+        loop {
+            print!(
+                "{} sensor # (Enter keeps {}, \"auto\" detects by label): ",
+                kind, current
+            );
+            std::io::stdout().flush()?;
+            let mut answer = String::new();
+            if std::io::stdin().read_line(&mut answer)? == 0 {
+                anyhow::bail!("No answer, config.json not changed");
+            }
+            match answer.trim() {
+                "" => break,
+                "auto" => {
+                    *selected = None;
+                    break;
+                }
+                number => match number.parse().ok().and_then(|i| selector_for(sensors, i)) {
+                    Some(selector) => {
+                        *selected = Some(selector);
+                        break;
+                    }
+                    None => println!("❌ No sensor #{}", number),
+                },
+            }
+        }
     }
 
+    storage::save_config(&config)?;
+    println!(
+        "💾 Sensor selection saved to {}",
+        storage::get_config_path()?.display()
+    );
     Ok(())
 }
 
@@ -615,6 +1187,18 @@ fn cmd_set_pump(duty: u8) -> Result<()> {
         eprintln!("Warning: Failed to update defaults: {}", e);
     }
 
+    if let Some(mut daemon) = daemon_client() {
+        daemon.call(&ControlRequest::SetDuty {
+            channel: Channel::Pump,
+            duty,
+        })?;
+        println!(
+            "✅ Pump held at {}% by the daemon until a profile is selected",
+            duty
+        );
+        return Ok(());
+    }
+
     let mut kraken = KrakenZ63::open().context("Failed to open Kraken Z63")?;
     kraken.initialize().context("Failed to initialize device")?;
 
@@ -636,6 +1220,18 @@ fn cmd_set_fan(duty: u8) -> Result<()> {
         eprintln!("Warning: Failed to update defaults: {}", e);
     }
 
+    if let Some(mut daemon) = daemon_client() {
+        daemon.call(&ControlRequest::SetDuty {
+            channel: Channel::Fan,
+            duty,
+        })?;
+        println!(
+            "✅ Fan held at {}% by the daemon until a profile is selected",
+            duty
+        );
+        return Ok(());
+    }
+
     let mut kraken = KrakenZ63::open().context("Failed to open Kraken Z63")?;
     kraken.initialize().context("Failed to initialize device")?;
 
@@ -668,31 +1264,391 @@ fn cmd_profile(name: &str, channel_str: &str) -> Result<()> {
         .context("Failed to apply profile")?;
 
     println!("✅ Applied {} profile to {}", profile.name(), channel);
+
+    // Show the expected speed along the curve, if calibrated
+    if let Some(cal) = load_device_calibration(&kraken)
+        .as_ref()
+        .and_then(|c| c.channel(channel))
+    {
+        println!("📏 Expected RPM:");
+        for &(temp, duty) in points.iter().step_by(5).chain(points.last()) {
+            println!(
+                "   {}°C → {:3}% ≈ {} RPM",
+                temp,
+                duty,
+                cal.expected_rpm(duty).unwrap_or(0)
+            );
+        }
+    }
     Ok(())
 }
 
-fn cmd_list() -> Result<()> {
-    let devices = KrakenZ63::list_devices().context("Failed to enumerate devices")?;
-
-    if devices.is_empty() {
-        println!("❌ No Kraken Z63 devices found.");
+/// Bring config.json and defaults.json up to the current schema, printing
+/// what moved.
+fn migrate_config_files() -> Result<()> {
+    let Some(report) = storage::migrate::migrate_all().context("Failed to migrate config files")?
+    else {
         return Ok(());
+    };
+    if !report.moved_profiles.is_empty() {
+        println!(
+            "📦 Moved cooling profiles from config.json to defaults.json: {}",
+            report.moved_profiles.join(", ")
+        );
     }
-
-    println!("🔍 Found {} device(s):\n", devices.len());
-    for (i, (path, serial)) in devices.iter().enumerate() {
-        let serial_str = serial.as_deref().unwrap_or("unknown");
-        println!("  {}. Serial: {}", i + 1, serial_str);
-        println!("     Path: {}", path);
-    }
-
+    println!(
+        "📦 Migrated {} from schema version {} to {}",
+        report.path.display(),
+        report.from_version,
+        storage::CONFIG_SCHEMA_VERSION
+    );
     Ok(())
 }
 
-fn cmd_info() -> Result<()> {
+fn cmd_profile_manage(action: ProfileCommand) -> Result<()> {
+    use nzxt_rust_devices::cooling::expression::is_expression;
+    use nzxt_rust_devices::storage::defaults::{load_defaults, save_defaults};
+
+    migrate_config_files()?;
+    storage::ensure_defaults_exist().context("Failed to initialize defaults")?;
+
+    match action {
+        ProfileCommand::List => {
+            let defaults = load_defaults()?;
+            println!("📋 Profiles ({}):", storage::get_defaults_path()?.display());
+            for profile in &defaults.profiles {
+                let active = defaults.active_profile_id.as_deref() == Some(profile.id.as_str());
+                println!(
+                    " {} {:<16} pump: {:<18} fan: {}",
+                    if active { "*" } else { " " },
+                    profile.id,
+                    describe_channel(profile, Channel::Pump),
+                    describe_channel(profile, Channel::Fan)
+                );
+            }
+        }
+        ProfileCommand::Show { name } => {
+            let defaults = load_defaults()?;
+            let profile = storage::find_profile(&defaults, &name)
+                .with_context(|| format!("Profile '{}' not found in defaults", name))?;
+            let active = defaults.active_profile_id.as_deref() == Some(profile.id.as_str());
+
+            println!(
+                "📋 {}{}",
+                profile.name.as_deref().unwrap_or(&profile.id),
+                if active { " (active)" } else { "" }
+            );
+            for channel in [Channel::Pump, Channel::Fan] {
+                println!("   {}: {}", channel, describe_channel(profile, channel));
+                if describe_channel(profile, channel).starts_with("expr ") {
+                    continue;
+                }
+                for (temp, duty) in profile.curve(&channel.to_string()) {
+                    println!("      {:>2}°C → {:3}%", temp, duty);
+                }
+            }
+            if let Some(zero) = &profile.zero_rpm {
+                println!(
+                    "   Zero-RPM: off ≤{}°C, on ≥{}°C",
+                    zero.stop_temperature, zero.start_temperature
+                );
+            }
+        }
+        ProfileCommand::Plot {
+            name,
+            output,
+            live,
+            source,
+        } => {
+            let profile = storage::resolve_profile(&name)?;
+            cmd_profile_plot(&profile, output, live, &source)?;
+        }
+        ProfileCommand::Check { name } => {
+            let profiles = match name {
+                Some(name) if is_expression(&name) => vec![storage::expression_profile(&name)?],
+                Some(name) => {
+                    let defaults = load_defaults()?;
+                    let profile = storage::find_profile(&defaults, &name)
+                        .with_context(|| format!("Profile '{}' not found in defaults", name))?;
+                    vec![profile.clone()]
+                }
+                None => load_defaults()?.profiles,
+            };
+            cmd_profile_check(&profiles)?;
+        }
+        ProfileCommand::Create { name, curves } => {
+            let channels = parse_curve_args(&curves)?;
+            storage::modify_defaults(|c| storage::create_custom_profile(c, &name, &channels))
+                .with_context(|| format!("Failed to create profile '{}'", name))?;
+            println!("✅ Created profile {}", name);
+        }
+        ProfileCommand::Edit { name, curves } => {
+            let channels = parse_curve_args(&curves)?;
+            storage::modify_defaults(|c| storage::edit_profile(c, &name, &channels))
+                .with_context(|| format!("Failed to edit profile '{}'", name))?;
+            println!("✅ Updated profile {}", name);
+        }
+        ProfileCommand::Clone { source, name } => {
+            storage::modify_defaults(|c| storage::clone_profile(c, &source, &name))
+                .with_context(|| format!("Failed to clone profile '{}'", source))?;
+            println!("✅ Copied {} to {}", source, name);
+        }
+        ProfileCommand::Delete { name } => {
+            let removed = storage::modify_defaults(|c| storage::delete_profile(c, &name))
+                .with_context(|| format!("Failed to delete profile '{}'", name))?;
+            println!("🗑️  Deleted profile {}", removed.id);
+        }
+        ProfileCommand::Rename { old, new } => {
+            let previous = storage::modify_defaults(|c| storage::rename_profile(c, &old, &new))
+                .with_context(|| format!("Failed to rename profile '{}'", old))?;
+            println!("✅ Renamed {} to {}", previous, new);
+
+            let mut config = storage::load_config()?;
+            let renamed = storage::rename_profile_references(&mut config, &previous, &new);
+            if renamed > 0 {
+                storage::save_config(&config)?;
+                println!("📝 Updated {} reference(s) in config.json", renamed);
+            }
+        }
+        ProfileCommand::Activate { name } => {
+            let id = storage::modify_defaults(|c| storage::activate_profile(c, &name))
+                .with_context(|| format!("Failed to activate profile '{}'", name))?;
+            println!("✅ {} is now the default profile for start", id);
+            if let Some(mut daemon) = daemon_client() {
+                daemon.call(&ControlRequest::SetProfile { name: id })?;
+                println!("🔄 Running daemon switched to it");
+            }
+        }
+        ProfileCommand::Import { path } => {
+            let content = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+
+            let mut defaults = load_defaults()?;
+            let report = storage::import_cam(&content, &mut defaults)
+                .with_context(|| format!("Failed to import {}", path.display()))?;
+            save_defaults(&defaults)?;
+
+            println!("📥 Imported from {}", path.display());
+            for id in &report.imported {
+                println!("   ✅ Added {}", id);
+            }
+            for id in &report.replaced {
+                println!("   🔁 Replaced {}", id);
+            }
+            for (from, to) in &report.renamed {
+                println!("   ✏️  Duplicate id {} renamed to {}", from, to);
+            }
+            if !report.warnings.is_empty() {
+                println!("\n⚠️  Not mapped exactly:");
+                for warning in &report.warnings {
+                    println!("   - {}", warning);
+                }
+            }
+        }
+        ProfileCommand::Export { path } => {
+            let json = storage::export_cam(&load_defaults()?)?;
+            match path {
+                Some(path) => {
+                    std::fs::write(&path, json)
+                        .with_context(|| format!("Failed to write {}", path.display()))?;
+                    println!("📤 Exported profiles to {}", path.display());
+                }
+                None => println!("{}", json),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Print the validation report of each profile; fails if any has errors.
+fn cmd_profile_check(profiles: &[storage::CoolingProfile]) -> Result<()> {
+    use nzxt_rust_devices::cooling::{Severity, validate_profile};
+
+    println!("🔍 Checking {} profile(s)", profiles.len());
+    let mut invalid = 0;
+    for profile in profiles {
+        let validation = validate_profile(profile);
+        let icon = if !validation.is_valid() {
+            invalid += 1;
+            "❌"
+        } else if validation.warnings().next().is_some() {
+            "⚠️ "
+        } else {
+            "✅"
+        };
+        println!("   {} {}", icon, profile.id);
+        for issue in &validation.issues {
+            let label = match issue.severity {
+                Severity::Error => "error",
+                Severity::Warning => "warning",
+            };
+            println!("      - {}: {}", label, issue);
+        }
+    }
+
+    if invalid > 0 {
+        return Err(nzxt_rust_devices::KrakenError::InvalidProfile(format!(
+            "{} of {} profile(s) have errors",
+            invalid,
+            profiles.len()
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+/// PNG chart size for `profile plot --output`.
+const PLOT_WIDTH: u32 = 800;
+const PLOT_HEIGHT: u32 = 500;
+
+fn cmd_profile_plot(
+    profile: &storage::CoolingProfile,
+    output: Option<PathBuf>,
+    live: bool,
+    source: &str,
+) -> Result<()> {
+    use nzxt_rust_devices::cooling::TempSource;
+    use nzxt_rust_devices::utils::curve_plot::{CurvePlot, OperatingPoint};
+
+    let mut plot = CurvePlot::from_exprs(
+        profile.channel_expr(Channel::Pump)?.as_ref(),
+        profile.channel_expr(Channel::Fan)?.as_ref(),
+    );
+
+    if live {
+        let kraken = KrakenZ63::open().context("Failed to open Kraken Z63")?;
+        let status = kraken.get_status().context("Failed to read status")?;
+        let temperature = match TempSource::from(source) {
+            TempSource::Liquid => status.liquid_temp_c,
+            TempSource::Cpu => system_sensors(&storage::load_config().unwrap_or_default())?
+                .find_cpu_temp()
+                .context("No CPU temperature sensor found")?,
+        };
+        plot = plot.with_operating_point(OperatingPoint {
+            temperature,
+            pump_duty: status.pump_duty,
+            fan_duty: status.fan_duty,
+        });
+    }
+
+    let title = profile.name.as_deref().unwrap_or(&profile.id);
+    if let Some(path) = output {
+        plot.render_png(PLOT_WIDTH, PLOT_HEIGHT)
+            .save(&path)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        println!("🖼️  Saved {} chart to {}", title, path.display());
+    } else {
+        println!("📈 {}\n", title);
+        print!("{}", plot.render_terminal());
+    }
+
+    for channel in [Channel::Pump, Channel::Fan] {
+        let Some(table) = plot.table(channel) else {
+            println!("\n   {}: not set", channel);
+            continue;
+        };
+        println!("\n   {} device table:", channel);
+        for (row, duties) in table.chunks(10).enumerate() {
+            let start = 20 + row * 10;
+            let cells: Vec<String> = duties.iter().map(|d| format!("{:3}", d)).collect();
+            println!(
+                "   {}-{}°C: {}",
+                start,
+                start + duties.len() - 1,
+                cells.join(" ")
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Parse `CHANNEL=CURVE` arguments for `profile create/edit`.
+fn parse_curve_args(args: &[String]) -> Result<Vec<(Channel, SpeedProfile)>> {
+    let channels = args
+        .iter()
+        .map(|arg| parse_channel_curve(arg))
+        .collect::<nzxt_rust_devices::Result<Vec<_>>>()?;
+
+    for (i, (channel, _)) in channels.iter().enumerate() {
+        if channels[..i].iter().any(|(c, _)| c == channel) {
+            anyhow::bail!("{} curve given more than once", channel);
+        }
+    }
+    Ok(channels)
+}
+
+/// One-line summary of a stored profile channel.
+fn describe_channel(profile: &storage::CoolingProfile, channel: Channel) -> String {
+    use nzxt_rust_devices::cooling::Interpolation;
+
+    let setting = profile
+        .channel_settings
+        .iter()
+        .find(|c| c.channel_name.eq_ignore_ascii_case(&channel.to_string()));
+    if let Some(expression) = setting.and_then(|c| c.expression.as_ref()) {
+        return format!("expr {}", expression);
+    }
+
+    match setting.and_then(|c| c.mode.as_ref()) {
+        Some(m) if m.custom_thresholds.as_ref().is_some_and(|t| !t.is_empty()) => {
+            let curve = profile.curve(&channel.to_string());
+            match m.interpolation.filter(|&i| i != Interpolation::Linear) {
+                Some(interpolation) => {
+                    format!("{} curve ({} points)", interpolation, curve.len())
+                }
+                None => format!("curve ({} points)", curve.len()),
+            }
+        }
+        Some(storage::CoolingMode {
+            fixed_percentage: Some(duty),
+            ..
+        }) => format!("fixed {}%", duty),
+        _ => "not set".into(),
+    }
+}
+
+fn cmd_list(format: OutputFormat) -> Result<()> {
+    let devices = KrakenZ63::list_devices().context("Failed to enumerate devices")?;
+
+    if !format.is_table() {
+        let devices: Vec<_> = devices
+            .iter()
+            .map(|(path, serial)| serde_json::json!({ "serial": serial, "path": path }))
+            .collect();
+        return print_formatted(format, &devices);
+    }
+
+    if devices.is_empty() {
+        println!("❌ No Kraken Z63 devices found.");
+        return Ok(());
+    }
+
+    println!("🔍 Found {} device(s):\n", devices.len());
+    for (i, (path, serial)) in devices.iter().enumerate() {
+        let serial_str = serial.as_deref().unwrap_or("unknown");
+        println!("  {}. Serial: {}", i + 1, serial_str);
+        println!("     Path: {}", path);
+    }
+
+    Ok(())
+}
+
+fn cmd_info(format: OutputFormat) -> Result<()> {
     let mut kraken = KrakenZ63::open().context("Failed to open Kraken Z63")?;
     let firmware = kraken.initialize().context("Failed to initialize device")?;
 
+    if !format.is_table() {
+        return print_formatted(
+            format,
+            &serde_json::json!({
+                "firmware": firmware.to_string(),
+                "firmware_version": firmware,
+                "serial": kraken.serial_number(),
+            }),
+        );
+    }
+
     println!("╭─────────────────────────────────╮");
     println!("│      NZXT Kraken Z63 Info       │");
     println!("├─────────────────────────────────┤");
@@ -990,6 +1946,7 @@ fn cmd_lcd_monitor(interval: u64) -> Result<()> {
 
     let temp_path = std::env::temp_dir().join("kraken_stats_monitor.png");
     let mut cycle_count: u64 = 0;
+    let mut history = nzxt_rust_devices::telemetry::TelemetryHistory::default();
 
     while running.load(Ordering::SeqCst) {
         cycle_count += 1;
@@ -1001,11 +1958,21 @@ fn cmd_lcd_monitor(interval: u64) -> Result<()> {
         // Get current status
         match kraken.get_status() {
             Ok(status) => {
+                history.push(nzxt_rust_devices::telemetry::Sample::new(
+                    chrono::Local::now().timestamp_millis(),
+                    &status,
+                ));
+                let trend = history.rate_of_change(
+                    nzxt_rust_devices::telemetry::Metric::LiquidTemp,
+                    Some(LCD_TREND_WINDOW),
+                );
+
                 // Generate radial gauge image
-                if let Some(img) = stats_image::generate_radial_stats_image(
+                if let Some(img) = stats_image::generate_radial_trend_image(
                     status.liquid_temp_c,
                     "LIQUID",
                     status.pump_rpm,
+                    trend,
                     gauge_config.as_ref(),
                 ) {
                     // Save to temp file and process
@@ -1049,235 +2016,2189 @@ fn cmd_lcd_monitor(interval: u64) -> Result<()> {
 }
 
 // =============================================================================
-// Cooling Daemon
+// Simulation
 // =============================================================================
 
-fn cmd_cooling_daemon(profile_name: &str, source: &str, interval: u64) -> Result<()> {
-    use nzxt_rust_devices::cooling::{TempSource, interpolate_duty};
-    use nzxt_rust_devices::storage;
-
-    // Ensure defaults exist
-    storage::ensure_defaults_exist().context("Failed to initialize defaults")?;
-
-    // Load profile from defaults
-    let profile = storage::get_profile(profile_name)
-        .with_context(|| format!("Failed to load profile '{}'", profile_name))?;
-
-    // Parse temperature source from CLI
-    let temp_source = TempSource::from(source);
-
-    // Initialize device
-    let mut kraken = KrakenZ63::open().context("Failed to open Kraken Z63")?;
-    kraken.initialize().context("Failed to initialize device")?;
-
-    // Initialize sensors
-    let mut sensors = SystemSensors::new();
-
-    // Setup Ctrl+C handler
-    let running = Arc::new(AtomicBool::new(true));
-    let r = running.clone();
-    ctrlc::set_handler(move || {
-        r.store(false, Ordering::SeqCst);
-    })
-    .context("Failed to set Ctrl+C handler")?;
-
-    println!("🌡️  Cooling Daemon Started (Ctrl+C to stop)");
-    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
-    println!("   Profile: {}", profile_name);
-    println!("   Source:  {}", temp_source);
-    println!("   Interval: {}s", interval);
-    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
-    println!();
-
-    // Extract curves from profile
-    let pump_curve: Vec<(u8, u8)> = profile
-        .channel_settings
-        .iter()
-        .find(|c| c.channel_name.to_lowercase() == "pump")
-        .and_then(|c| c.mode.as_ref())
-        .and_then(|m| m.custom_thresholds.as_ref())
-        .map(|t| {
-            t.iter()
-                .map(|th| (th.temperature, th.fan_percentage))
-                .collect()
-        })
-        .unwrap_or_default();
+fn cmd_simulate(
+    path: &PathBuf,
+    profile_name: &str,
+    compare: Option<&str>,
+    source: &str,
+    interval: u64,
+    load: bool,
+    full: bool,
+) -> Result<()> {
+    use nzxt_rust_devices::cooling::{SimulationResult, TempSource, parse_trace, simulate};
+
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let trace = parse_trace(&content, interval as f64)
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+    let source = TempSource::from(source);
+
+    let load_control = if load {
+        let config = storage::load_config().context("Failed to load config.json")?;
+        if !trace.iter().any(|s| s.cpu_load.is_some()) {
+            println!("⚠️  Trace has no cpu_load column; the load boost stays at 0");
+        }
+        Some(config.load_control)
+    } else {
+        None
+    };
 
-    let fan_curve: Vec<(u8, u8)> = profile
-        .channel_settings
+    let names: Vec<&str> = std::iter::once(profile_name).chain(compare).collect();
+    let runs = names
         .iter()
-        .find(|c| c.channel_name.to_lowercase() == "fan")
-        .and_then(|c| c.mode.as_ref())
-        .and_then(|m| m.custom_thresholds.as_ref())
-        .map(|t| {
-            t.iter()
-                .map(|th| (th.temperature, th.fan_percentage))
-                .collect()
+        .map(|&name| {
+            let profile = storage::resolve_profile(name)
+                .with_context(|| format!("Failed to load profile '{}'", name))?;
+            let result = simulate(&profile, &trace, source, load_control.as_ref())
+                .with_context(|| format!("Failed to simulate '{}'", name))?;
+            Ok((name, result))
         })
-        .unwrap_or_default();
-
-    println!("📊 Pump curve: {} points", pump_curve.len());
-    println!("📊 Fan curve:  {} points", fan_curve.len());
-    println!();
-
-    let mut cycle_count: u64 = 0;
-
-    while running.load(Ordering::SeqCst) {
-        cycle_count += 1;
-
-        // Get current temperatures
-        let status = kraken.get_status().context("Failed to get device status")?;
-        sensors.refresh();
+        .collect::<Result<Vec<(&str, SimulationResult)>>>()?;
 
-        let liquid_temp = status.liquid_temp_c as u8;
-        let cpu_temp = sensors.find_cpu_temp().unwrap_or(0.0) as u8;
+    let duration = trace.last().map_or(0.0, |s| s.time);
+    println!(
+        "🧪 Simulating {} ({} samples, {}, {} temperature)",
+        path.display(),
+        trace.len(),
+        format_elapsed(duration),
+        source
+    );
+    if let Some((_, result)) = runs.first()
+        && result.skipped > 0
+    {
+        println!(
+            "⚠️  {} samples without a {} temperature were skipped",
+            result.skipped, source
+        );
+    }
 
-        // Select temperature based on source
-        let current_temp = match temp_source {
-            TempSource::Liquid => liquid_temp,
-            TempSource::Cpu => cpu_temp,
-        };
+    // Timeline: one column pair per profile
+    println!(
+        "\n📈 Timeline{}:",
+        if full { "" } else { " (duty changes)" }
+    );
+    let header: String = names
+        .iter()
+        .map(|name| format!(" | {:>15}", truncate(name, 15)))
+        .collect();
+    println!("   {:>8} {:>5}{}", "Time", "Temp", header);
+    println!(
+        "   {:>8} {:>5}{}",
+        "",
+        "",
+        format!(" | {:>7} {:>7}", "pump", "fan").repeat(runs.len())
+    );
 
-        // Calculate and apply pump duty
-        let pump_duty = if pump_curve.is_empty() {
-            70 // Default if no curve
-        } else {
-            interpolate_duty(&pump_curve, current_temp)
+    // Profiles may skip different samples (an expression reading a missing
+    // temperature), so rows follow the trace and a skipped sample is an empty cell
+    let mut next = vec![0; runs.len()];
+    let mut last: Vec<Option<(u8, u8)>> = vec![None; runs.len()];
+    for sample in &trace {
+        let row: Vec<_> = runs
+            .iter()
+            .zip(next.iter_mut())
+            .map(|((_, r), next)| {
+                let step = r.steps.get(*next).filter(|s| s.time == sample.time)?;
+                *next += 1;
+                Some(step)
+            })
+            .collect();
+        let Some(temperature) = row.iter().flatten().map(|s| s.temperature).next() else {
+            continue;
         };
-        kraken.set_pump_speed(pump_duty.max(20))?;
 
-        // Calculate and apply fan duty
-        let fan_duty = if fan_curve.is_empty() {
-            50 // Default if no curve
-        } else {
-            interpolate_duty(&fan_curve, current_temp)
-        };
-        kraken.set_fan_speed(fan_duty)?;
+        let mut changed = false;
+        for (step, last) in row.iter().zip(last.iter_mut()) {
+            if let Some(step) = step {
+                let duties = Some((step.pump_duty, step.fan_duty));
+                changed |= *last != duties;
+                *last = duties;
+            }
+        }
+        if !full && !changed {
+            continue;
+        }
 
-        // Display status
+        let duties: String = row
+            .iter()
+            .map(|step| match step {
+                Some(s) => format!(" | {:>6}% {:>6}%", s.pump_duty, s.fan_duty),
+                None => format!(" | {:>7} {:>7}", "", ""),
+            })
+            .collect();
         println!(
-            "[{:4}] {} {}°C | Pump: {:3}% ({} RPM) | Fan: {:3}%",
-            cycle_count,
-            match temp_source {
-                TempSource::Liquid => "💧",
-                TempSource::Cpu => "🔥",
-            },
-            current_temp,
-            pump_duty,
-            status.pump_rpm,
-            fan_duty
+            "   {:>8} {:>3}°C{}",
+            format_elapsed(sample.time),
+            temperature,
+            duties
         );
+    }
 
-        std::thread::sleep(Duration::from_secs(interval));
+    // Summary per channel
+    println!("\n📊 Summary:");
+    println!("   {:<16}{}", "", header);
+    for channel in [Channel::Pump, Channel::Fan] {
+        let row = |label: &str, value: &dyn Fn(&SimulationResult) -> String| {
+            let cells: String = runs
+                .iter()
+                .map(|(_, r)| format!(" | {:>15}", value(r)))
+                .collect();
+            println!("   {:<16}{}", format!("{} {}", channel, label), cells);
+        };
+        row("mean", &|r| format!("{:.1}%", r.summary(channel).mean_duty));
+        row("range", &|r| {
+            let s = r.summary(channel);
+            format!("{}-{}%", s.min_duty, s.max_duty)
+        });
+        row("changes", &|r| r.summary(channel).changes.to_string());
+    }
+
+    // Time spent per 10% band
+    println!("\n⏱️  Time at duty:");
+    println!("   {:<16}{}", "", header);
+    for channel in [Channel::Pump, Channel::Fan] {
+        let bands: Vec<_> = runs
+            .iter()
+            .map(|(_, r)| r.summary(channel).time_in_bands(10))
+            .collect();
+        let mut keys: Vec<u8> = bands.iter().flat_map(|b| b.keys().copied()).collect();
+        keys.sort_unstable();
+        keys.dedup();
+
+        for band in keys {
+            let cells: String = bands
+                .iter()
+                .map(|b| {
+                    let secs = b.get(&band).copied().unwrap_or(0.0);
+                    let total: f64 = b.values().sum();
+                    let share = if total > 0.0 {
+                        100.0 * secs / total
+                    } else {
+                        0.0
+                    };
+                    format!(" | {:>8} {:>4.0}%", format_elapsed(secs), share)
+                })
+                .collect();
+            let label = format!("{} {}-{}%", channel, band, (band + 9).min(100));
+            println!("   {:<16}{}", label, cells);
+        }
     }
 
-    println!("\n✅ Cooling daemon stopped after {} cycles.", cycle_count);
     Ok(())
 }
 
-// =============================================================================
-// Unified Start Command (LCD Monitor + Cooling Daemon)
-// =============================================================================
+/// Format seconds as `H:MM:SS` or `M:SS`.
+fn format_elapsed(secs: f64) -> String {
+    let secs = secs.round() as u64;
+    let (h, m, s) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if h > 0 {
+        format!("{}:{:02}:{:02}", h, m, s)
+    } else {
+        format!("{}:{:02}", m, s)
+    }
+}
 
-fn cmd_start(cli_profile: &str, cli_source: &str, cli_interval: u64) -> Result<()> {
-    use nzxt_rust_devices::cooling::{TempSource, interpolate_duty};
-    use nzxt_rust_devices::device::BucketManager;
-    use nzxt_rust_devices::utils::stats_image;
+fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        text.to_string()
+    } else {
+        text.chars().take(max - 1).chain(['…']).collect()
+    }
+}
 
-    // Ensure storage exists and load configs
-    storage::ensure_defaults_exist().context("Failed to initialize defaults")?;
-    nzxt_rust_devices::storage::ensure_config_exists()?;
+// =============================================================================
+// Calibration
+// =============================================================================
 
-    // Load config for fallback values
-    let app_config = nzxt_rust_devices::storage::load_config().unwrap_or_default();
-    let startup = &app_config.startup;
+fn cmd_calibrate(channel_str: &str, step: u8, settle: u64, show: bool) -> Result<()> {
+    use nzxt_rust_devices::cooling::{CalibrationOptions, calibrate_channel};
+    use nzxt_rust_devices::storage::DeviceCalibration;
 
-    // Resolve values: CLI overrides config (check if CLI is default value)
-    let profile_name = if cli_profile == "silent" {
-        &startup.cooling_profile
-    } else {
-        cli_profile
-    };
-    let source = if cli_source == "liquid" {
-        &startup.temperature_source
-    } else {
-        cli_source
+    let channels = match channel_str.to_lowercase().as_str() {
+        "both" | "all" => vec![Channel::Pump, Channel::Fan],
+        other => vec![parse_channel(other)?],
     };
-    let interval = if cli_interval == 2 {
-        startup.interval
-    } else {
-        cli_interval
-    };
-    let brightness = startup.brightness;
-    let config_orientation = startup.orientation;
-    let display_mode = startup.display_mode.to_lowercase();
 
-    // Initialize device
     let mut kraken = KrakenZ63::open().context("Failed to open Kraken Z63")?;
-    kraken.initialize().context("Failed to initialize device")?;
+    let firmware = kraken.initialize().context("Failed to initialize device")?;
+    let serial = kraken
+        .serial_number()
+        .context("Device did not report a serial number")?;
+
+    if show {
+        match storage::load_calibration(&serial)? {
+            Some(calibration) => print_calibration(&calibration),
+            None => println!("❌ Device {} has not been calibrated yet.", serial),
+        }
+        return Ok(());
+    }
 
-    // Apply configured brightness
-    kraken.set_brightness(brightness)?;
+    // Ctrl+C aborts the sweep; the channel is restored either way
+    let cancel = Arc::new(AtomicBool::new(false));
+    let c = cancel.clone();
+    ctrlc::set_handler(move || {
+        c.store(true, Ordering::SeqCst);
+    })?;
 
-    // Get/apply orientation
-    let (_, current_orientation) = kraken.get_lcd_info().context("Failed to get LCD info")?;
-    let target_orientation = (config_orientation / 90) as u8;
-    if target_orientation != current_orientation && config_orientation > 0 {
-        kraken.set_orientation(target_orientation)?;
-    }
-    let orientation = if config_orientation > 0 {
-        target_orientation
-    } else {
-        current_orientation
+    let options = CalibrationOptions {
+        step,
+        settle_timeout: Duration::from_secs(settle),
+        cancel: Some(cancel.clone()),
+        ..Default::default()
     };
 
-    // Initialize sensors
-    let mut sensors = SystemSensors::new();
-
-    // Parse temperature source
-    let temp_source = TempSource::from(source);
+    // Channels go back to the active profile's curve, or to their current duty
+    let active_profile = storage::active_profile_id().ok().flatten();
+    let before = kraken.get_status().context("Failed to read status")?;
 
-    // Load cooling profile
-    let profile = storage::get_profile(profile_name)
-        .with_context(|| format!("Failed to load profile '{}'", profile_name))?;
+    println!("🔧 Calibrating Kraken Z63 (serial {})", serial);
+    println!("   Each channel is swept from 100% down to its minimum; this takes a few minutes.");
+    println!("   Keep the system idle; the fan may stop briefly.");
+    println!();
 
-    // Load gauge config
-    let gauge_config = app_config
-        .active_profile
-        .as_ref()
-        .and_then(|name| app_config.lcd.get(name))
-        .or_else(|| app_config.lcd.get("default_gauge"))
-        .and_then(|p| p.radial_gauge.as_ref())
-        .map(|stored| {
-            nzxt_rust_devices::utils::radial_gauge::RadialGaugeConfig::from_stored(stored)
+    // Start from the existing calibration so a single-channel run keeps the other
+    let mut calibration = storage::load_calibration(&serial)?.unwrap_or(DeviceCalibration {
+        serial: serial.clone(),
+        firmware: None,
+        calibrated_at: String::new(),
+        pump: None,
+        fan: None,
+    });
+
+    for channel in channels {
+        println!("⚙️  {}:", channel);
+        let result = calibrate_channel(&kraken, channel, &options, |point| {
+            println!("   {:3}% → {:5} RPM", point.duty, point.rpm);
         });
 
-    // Extract cooling curves from profile
-    let pump_curve: Vec<(u8, u8)> = profile
-        .channel_settings
+        // Restore even if the sweep failed or was cancelled
+        let restored = match &active_profile {
+            Some(name) => apply_device_curve(&kraken, name, channel)
+                .inspect_err(|e| eprintln!("⚠️  Cannot restore profile '{}': {}", name, e))
+                .unwrap_or(false),
+            None => false,
+        };
+        if !restored {
+            let restore = before
+                .duty(channel)
+                .clamp(channel.min_duty(), channel.max_duty());
+            let _ = kraken.set_channel_speed(channel, restore);
+        }
+
+        if cancel.load(Ordering::SeqCst) {
+            anyhow::bail!("Calibration cancelled; {} restored", channel);
+        }
+        let result = result.with_context(|| format!("Calibration of {} failed", channel))?;
+        match channel {
+            Channel::Pump => calibration.pump = Some(result),
+            Channel::Fan => calibration.fan = Some(result),
+        }
+        println!();
+    }
+
+    calibration.firmware = Some(firmware.to_string());
+    calibration.calibrated_at = chrono::Local::now().to_rfc3339();
+    storage::save_calibration(&calibration).context("Failed to save calibration")?;
+
+    print_calibration(&calibration);
+    println!(
+        "\n✅ Calibration saved to {}",
+        storage::get_calibration_path(&serial)?.display()
+    );
+    Ok(())
+}
+
+fn print_calibration(calibration: &nzxt_rust_devices::storage::DeviceCalibration) {
+    println!("📏 Calibration for {}", calibration.serial);
+    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+    println!("   Date:     {}", calibration.calibrated_at);
+    if let Some(firmware) = &calibration.firmware {
+        println!("   Firmware: {}", firmware);
+    }
+
+    for channel in [Channel::Pump, Channel::Fan] {
+        let Some(cal) = calibration.channel(channel) else {
+            continue;
+        };
+        let format_duty = |duty: Option<u8>| {
+            duty.map(|d| format!("{}%", d))
+                .unwrap_or_else(|| "-".to_string())
+        };
+
+        println!();
+        println!(
+            "   {}: max {} RPM | stall ≤ {} | start {}",
+            channel,
+            cal.max_rpm,
+            format_duty(cal.stall_duty),
+            format_duty(cal.start_duty)
+        );
+        for point in &cal.points {
+            println!("     {:3}% → {:5} RPM", point.duty, point.rpm);
+        }
+    }
+}
+
+/// Load the calibration of an open device, if it has one.
+fn load_device_calibration(
+    kraken: &KrakenZ63,
+) -> Option<nzxt_rust_devices::storage::DeviceCalibration> {
+    load_serial_calibration(&kraken.serial_number()?)
+}
+
+/// Load the calibration of the device with this serial, if it has one.
+fn load_serial_calibration(serial: &str) -> Option<nzxt_rust_devices::storage::DeviceCalibration> {
+    match storage::load_calibration(serial) {
+        Ok(calibration) => calibration,
+        Err(e) => {
+            eprintln!("⚠️  Ignoring calibration: {}", e);
+            None
+        }
+    }
+}
+
+/// Warns when the pump or fan runs persistently slower than calibrated.
+struct FanHealth {
+    calibration: Option<nzxt_rust_devices::storage::DeviceCalibration>,
+    pump: nzxt_rust_devices::cooling::DegradationMonitor,
+    fan: nzxt_rust_devices::cooling::DegradationMonitor,
+    stall: nzxt_rust_devices::cooling::StallDetector,
+    /// Duties commanded on the previous tick (what the current RPM reflects)
+    last_duties: Option<(u8, u8)>,
+}
+
+impl FanHealth {
+    fn new(calibration: Option<nzxt_rust_devices::storage::DeviceCalibration>) -> Self {
+        // Below the calibrated stall duty a stopped fan is expected
+        let stall_duty = calibration
+            .as_ref()
+            .and_then(|c| c.fan.as_ref())
+            .and_then(|f| f.stall_duty)
+            .unwrap_or(0);
+
+        Self {
+            calibration,
+            pump: Default::default(),
+            fan: Default::default(),
+            stall: nzxt_rust_devices::cooling::StallDetector::new(stall_duty),
+            last_duties: None,
+        }
+    }
+
+    /// Check the status against the previous duties, then record the new ones.
+    fn update(&mut self, status: &nzxt_rust_devices::protocol::DeviceStatus, pump: u8, fan: u8) {
+        if let Some((last_pump, last_fan)) = self.last_duties {
+            if self.stall.update(last_fan, status.fan_rpm) {
+                eprintln!(
+                    "🚨 Fan stall: 0 RPM at {}% duty. Check the fan connection and for obstructions.",
+                    last_fan
+                );
+            }
+
+            for (channel, duty, monitor) in [
+                (Channel::Pump, last_pump, &mut self.pump),
+                (Channel::Fan, last_fan, &mut self.fan),
+            ] {
+                let Some(cal) = self.calibration.as_ref().and_then(|c| c.channel(channel)) else {
+                    continue;
+                };
+                let rpm = status.rpm(channel);
+                if let Some(deviation) = monitor.update(cal, duty, rpm) {
+                    eprintln!(
+                        "⚠️  {} at {}% runs {} RPM, {:.0}% below calibration ({} RPM). Check for dust or a failing bearing.",
+                        channel,
+                        duty,
+                        rpm,
+                        -deviation,
+                        cal.expected_rpm(duty).unwrap_or(0)
+                    );
+                }
+            }
+        }
+        self.last_duties = Some((pump, fan));
+    }
+
+    /// True while the fan is reported stalled.
+    fn fan_stalled(&self) -> bool {
+        self.stall.is_stalled()
+    }
+}
+
+/// RPM-target controllers for the daemon loops.
+struct RpmTargets {
+    pump: Option<nzxt_rust_devices::cooling::RpmController>,
+    fan: Option<nzxt_rust_devices::cooling::RpmController>,
+}
+
+impl RpmTargets {
+    fn new(
+        calibration: Option<&nzxt_rust_devices::storage::DeviceCalibration>,
+        pump_rpm: Option<u16>,
+        fan_rpm: Option<u16>,
+    ) -> Result<Self> {
+        use nzxt_rust_devices::cooling::RpmController;
+
+        let controller = |channel: Channel, rpm: Option<u16>| -> Result<_> {
+            let Some(rpm) = rpm else {
+                return Ok(None);
+            };
+            let cal = calibration.and_then(|c| c.channel(channel));
+            let controller = RpmController::new(channel, rpm, cal)?;
+            println!(
+                "🎯 {} target: {} RPM (starting at {}%{})",
+                channel,
+                rpm,
+                controller.duty(),
+                if cal.is_some() { ", calibrated" } else { "" }
+            );
+            Ok(Some(controller))
+        };
+
+        Ok(Self {
+            pump: controller(Channel::Pump, pump_rpm)?,
+            fan: controller(Channel::Fan, fan_rpm)?,
+        })
+    }
+
+    /// Replace the curve duties of channels that have an RPM target.
+    ///
+    /// A target that cannot be reached is dropped and its channel goes back
+    /// to the curve.
+    fn apply(
+        &mut self,
+        status: &nzxt_rust_devices::protocol::DeviceStatus,
+        pump_duty: u8,
+        fan_duty: u8,
+    ) -> (u8, u8) {
+        (
+            Self::follow(&mut self.pump, Channel::Pump, status.pump_rpm, pump_duty),
+            Self::follow(&mut self.fan, Channel::Fan, status.fan_rpm, fan_duty),
+        )
+    }
+
+    fn follow(
+        target: &mut Option<nzxt_rust_devices::cooling::RpmController>,
+        channel: Channel,
+        rpm: u16,
+        curve_duty: u8,
+    ) -> u8 {
+        let Some(controller) = target.as_mut() else {
+            return curve_duty;
+        };
+        match controller.update(rpm) {
+            Ok(duty) => duty,
+            Err(e) => {
+                eprintln!(
+                    "⚠️  {} RPM target dropped, following the curve: {}",
+                    channel, e
+                );
+                *target = None;
+                curve_duty
+            }
+        }
+    }
+
+    /// Status line suffix showing the targets.
+    fn suffix(&self) -> String {
+        let mut suffix = String::new();
+        if let Some(c) = &self.pump {
+            suffix.push_str(&format!(" | Pump target: {} RPM", c.target()));
+        }
+        if let Some(c) = &self.fan {
+            suffix.push_str(&format!(" | Fan target: {} RPM", c.target()));
+        }
+        suffix
+    }
+}
+
+// =============================================================================
+// Stats
+// =============================================================================
+
+fn cmd_stats(window: Option<&str>, above: Option<f32>, format: OutputFormat) -> Result<()> {
+    use nzxt_rust_devices::telemetry::Metric;
+    use nzxt_rust_devices::utils::parsing::parse_duration;
+
+    let window = window.map(parse_duration).transpose()?;
+    let Some(mut daemon) = daemon_client() else {
+        anyhow::bail!("No daemon is listening. Is cooling-daemon or start running?");
+    };
+    let report = daemon.stats(window, above)?;
+
+    if !format.is_table() {
+        return print_formatted(format, &report);
+    }
+
+    let span = Duration::from_secs_f64(report.span_secs);
+    println!(
+        "📊 Telemetry: {} samples over {}{}",
+        report.samples,
+        format_duration(span),
+        window
+            .map(|w| format!(" (last {})", format_duration(w)))
+            .unwrap_or_default()
+    );
+    if report.samples == 0 {
+        return Ok(());
+    }
+
+    println!();
+    println!(
+        "{:<10} {:>8} {:>8} {:>8} {:>8} {:>8}",
+        "Metric", "Min", "Mean", "P50", "P95", "Max"
+    );
+    println!("{}", "─".repeat(56));
+    for metric in Metric::ALL {
+        let Some(s) = report.stats(metric) else {
+            continue;
+        };
+        println!(
+            "{:<10} {:>8.1} {:>8.1} {:>8.1} {:>8.1} {:>8.1}  {}",
+            metric.to_string(),
+            s.min,
+            s.mean,
+            s.p50,
+            s.p95,
+            s.max,
+            metric.unit()
+        );
+    }
+    println!("{}", "─".repeat(56));
+
+    if let Some(trend) = report.liquid_trend_per_min {
+        println!("💧 Liquid trend: {:+.2}°C/min", trend);
+    }
+    if let Some(above) = report.liquid_above {
+        let span = span.min(window.unwrap_or(Duration::MAX));
+        let share = if span.is_zero() {
+            0.0
+        } else {
+            above.secs / span.as_secs_f64() * 100.0
+        };
+        println!(
+            "🔥 Above {}°C: {} ({:.0}%)",
+            above.threshold,
+            format_duration(Duration::from_secs_f64(above.secs)),
+            share
+        );
+    }
+    Ok(())
+}
+
+/// Format a duration as e.g. "1h 5m", "3m 20s" or "45s".
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    match (secs / 3600, secs % 3600 / 60, secs % 60) {
+        (0, 0, s) => format!("{}s", s),
+        (0, m, s) => format!("{}m {}s", m, s),
+        (h, m, _) => format!("{}h {}m", h, m),
+    }
+}
+
+// =============================================================================
+// Terminal Dashboard
+// =============================================================================
+
+/// How often the dashboard re-reads the LCD buckets.
+const TUI_BUCKET_REFRESH: Duration = Duration::from_secs(10);
+
+/// Where the dashboard reads from and sends its changes to.
+enum TuiSource {
+    /// The running daemon, over the control socket
+    Daemon(nzxt_rust_devices::ipc::ControlClient),
+    /// The device itself, when no daemon runs
+    Device {
+        kraken: KrakenZ63,
+        sensors: SystemSensors,
+    },
+}
+
+impl TuiSource {
+    fn name(&self) -> &'static str {
+        match self {
+            TuiSource::Daemon(_) => "daemon",
+            TuiSource::Device { .. } => "device",
+        }
+    }
+
+    /// A sample, the active profile and the brightness, when known.
+    fn read(
+        &mut self,
+    ) -> Result<(
+        nzxt_rust_devices::telemetry::Sample,
+        Option<String>,
+        Option<u8>,
+    )> {
+        use nzxt_rust_devices::telemetry::Sample;
+
+        let now = chrono::Local::now().timestamp_millis();
+        match self {
+            TuiSource::Daemon(daemon) => {
+                let status = daemon.status()?;
+                let sample = Sample {
+                    cpu_temp: status.cpu_temp,
+                    gpu_temp: status.gpu_temp,
+                    ..Sample::new(now, &status.status)
+                };
+                Ok((sample, status.profile, status.brightness))
+            }
+            TuiSource::Device { kraken, sensors } => {
+                let status = kraken.get_status().context("Failed to read status")?;
+                sensors.refresh();
+                let sample = Sample {
+                    cpu_temp: sensors.find_cpu_temp(),
+                    gpu_temp: sensors.find_gpu_temp(),
+                    ..Sample::new(now, &status)
+                };
+                Ok((sample, None, None))
+            }
+        }
+    }
+
+    fn buckets(&mut self) -> Result<Vec<nzxt_rust_devices::protocol::BucketInfo>> {
+        Ok(match self {
+            TuiSource::Daemon(daemon) => daemon.buckets()?,
+            TuiSource::Device { kraken, .. } => kraken.query_all_buckets()?,
+        })
+    }
+
+    fn apply(&mut self, action: &nzxt_rust_devices::tui::Action) -> Result<()> {
+        use nzxt_rust_devices::tui::Action;
+
+        match self {
+            TuiSource::Daemon(daemon) => {
+                let request = match action {
+                    Action::Quit => return Ok(()),
+                    Action::SetProfile(name) => ControlRequest::SetProfile { name: name.clone() },
+                    Action::SetDuty(channel, duty) => ControlRequest::SetDuty {
+                        channel: *channel,
+                        duty: *duty,
+                    },
+                    Action::SetBrightness(brightness) => ControlRequest::SetBrightness {
+                        brightness: *brightness,
+                    },
+                };
+                daemon.call(&request)?;
+            }
+            TuiSource::Device { kraken, .. } => match action {
+                Action::Quit => {}
+                Action::SetProfile(name) => {
+                    for channel in [Channel::Pump, Channel::Fan] {
+                        apply_device_curve(kraken, name, channel)?;
+                    }
+                }
+                Action::SetDuty(Channel::Pump, duty) => kraken.set_pump_speed(*duty)?,
+                Action::SetDuty(Channel::Fan, duty) => kraken.set_fan_speed(*duty)?,
+                Action::SetBrightness(brightness) => kraken.set_brightness(*brightness)?,
+            },
+        }
+        Ok(())
+    }
+}
+
+/// Load the curve a profile sets for a channel into the device. Returns
+/// `false` if the profile leaves the channel alone.
+fn apply_device_curve(kraken: &KrakenZ63, name: &str, channel: Channel) -> Result<bool> {
+    let profile = storage::resolve_profile(name)?;
+    let Some(expr) = profile.channel_expr(channel)? else {
+        return Ok(false);
+    };
+    let points: Vec<(u8, u8)> = expr
+        .device_table(channel)?
         .iter()
-        .find(|c| c.channel_name.to_lowercase() == "pump")
-        .and_then(|c| c.mode.as_ref())
-        .and_then(|m| m.custom_thresholds.as_ref())
-        .map(|t| {
-            t.iter()
-                .map(|th| (th.temperature, th.fan_percentage))
-                .collect()
+        .enumerate()
+        .map(|(i, &duty)| (20 + i as u8, duty))
+        .collect();
+    kraken.set_speed_profile(channel, &points)?;
+    Ok(true)
+}
+
+fn cmd_tui(interval_secs: u64) -> Result<()> {
+    use nzxt_rust_devices::storage::defaults::load_defaults;
+    use nzxt_rust_devices::tui::Dashboard;
+
+    let mut source = match daemon_client() {
+        Some(daemon) => TuiSource::Daemon(daemon),
+        None => {
+            let mut kraken = KrakenZ63::open().context("Failed to open Kraken Z63")?;
+            kraken.initialize().context("Failed to initialize device")?;
+            TuiSource::Device {
+                kraken,
+                sensors: system_sensors(&storage::load_config().unwrap_or_default())?,
+            }
+        }
+    };
+
+    storage::ensure_defaults_exist().context("Failed to initialize defaults")?;
+    let profiles = load_defaults()?
+        .profiles
+        .into_iter()
+        .map(|p| p.id)
+        .collect();
+    let mut dashboard = Dashboard::new(source.name(), profiles);
+    if let TuiSource::Device { kraken, .. } = &source {
+        // Without a daemon the device follows whatever was last applied; show
+        // the default profile until one is picked here
+        dashboard.set_profile(storage::active_profile_id().ok().flatten());
+        dashboard.brightness = kraken.get_lcd_info().ok().map(|(brightness, _)| brightness);
+    }
+
+    let mut terminal = ratatui::init();
+    let result = run_tui(
+        &mut terminal,
+        &mut source,
+        &mut dashboard,
+        Duration::from_secs(interval_secs.max(1)),
+    );
+    ratatui::restore();
+    result
+}
+
+fn run_tui(
+    terminal: &mut ratatui::DefaultTerminal,
+    source: &mut TuiSource,
+    dashboard: &mut nzxt_rust_devices::tui::Dashboard,
+    interval: Duration,
+) -> Result<()> {
+    use nzxt_rust_devices::tui::{self, Action};
+    use ratatui::crossterm::event::{self, Event};
+    use std::time::Instant;
+
+    let mut next_read = Instant::now();
+    let mut next_buckets = Instant::now();
+    loop {
+        let now = Instant::now();
+        if now >= next_read {
+            match source.read() {
+                Ok((sample, profile, brightness)) => {
+                    dashboard.push(sample, profile);
+                    if brightness.is_some() {
+                        dashboard.brightness = brightness;
+                    }
+                }
+                Err(e) => dashboard.message = Some(format!("❌ {:#}", e)),
+            }
+            next_read = now + interval;
+        }
+        if now >= next_buckets {
+            match source.buckets() {
+                Ok(buckets) => dashboard.buckets = buckets,
+                Err(e) => dashboard.message = Some(format!("❌ {:#}", e)),
+            }
+            next_buckets = now + TUI_BUCKET_REFRESH;
+        }
+
+        terminal.draw(|frame| tui::draw(frame, dashboard))?;
+
+        if !event::poll(next_read.saturating_duration_since(Instant::now()))? {
+            continue;
+        }
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        let Some(action) = dashboard.handle_key(key) else {
+            continue;
+        };
+        let done = match &action {
+            Action::Quit => return Ok(()),
+            Action::SetProfile(name) => format!("✅ Profile {}", name),
+            Action::SetDuty(channel, duty) => format!("✅ {} held at {}%", channel, duty),
+            Action::SetBrightness(brightness) => format!("✅ Brightness {}%", brightness),
+        };
+        dashboard.message = Some(match source.apply(&action) {
+            Ok(()) => {
+                match action {
+                    Action::SetProfile(name) => dashboard.set_profile(Some(name)),
+                    Action::SetBrightness(brightness) => dashboard.brightness = Some(brightness),
+                    _ => {}
+                }
+                done
+            }
+            Err(e) => format!("❌ {:#}", e),
+        });
+    }
+}
+
+// =============================================================================
+// Cooling Daemon
+// =============================================================================
+
+fn cmd_cooling_daemon(
+    profile_name: &str,
+    source: &str,
+    interval: u64,
+    pump_rpm: Option<u16>,
+    fan_rpm: Option<u16>,
+    metrics_addr: Option<&str>,
+) -> Result<()> {
+    use nzxt_rust_devices::cooling::TempSource;
+    use nzxt_rust_devices::storage;
+
+    // Ensure defaults exist
+    storage::ensure_defaults_exist().context("Failed to initialize defaults")?;
+    migrate_config_files()?;
+
+    // Schedule and process rules come from config.json
+    // A missing file means defaults; a broken one must not be ignored
+    let app_config = storage::load_config().context("Failed to load config.json")?;
+
+    // Load profile from defaults (MQTT can select another one)
+    let active = ActiveProfile::load(profile_name)?;
+    let mut base_profile = profile_name.to_string();
+    let mut watch = ConfigWatch::new()?;
+
+    // Parse temperature source from CLI
+    let temp_source = TempSource::from(source);
+
+    // Initialize device
+    let mut kraken = KrakenZ63::open().context("Failed to open Kraken Z63")?;
+    kraken.initialize().context("Failed to initialize device")?;
+
+    // Calibration: fan degradation warnings and RPM-target seeding
+    let calibration = load_device_calibration(&kraken);
+    let rpm_targets = RpmTargets::new(calibration.as_ref(), pump_rpm, fan_rpm)?;
+    let health = FanHealth::new(calibration);
+
+    // Initialize sensors, telemetry and remote control
+    let (metrics, _metrics_server) = start_metrics(&kraken, &app_config, metrics_addr)?;
+    let mut daemon = DaemonLoop::new(&kraken, app_config, metrics, active, rpm_targets, health)?;
+
+    // Setup Ctrl+C handler
+    let running = Arc::new(AtomicBool::new(true));
+    let r = running.clone();
+    ctrlc::set_handler(move || {
+        r.store(false, Ordering::SeqCst);
+    })
+    .context("Failed to set Ctrl+C handler")?;
+
+    println!("🌡️  Cooling Daemon Started (Ctrl+C to stop)");
+    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+    println!("   Profile: {}", profile_name);
+    println!("   Source:  {}", temp_source);
+    println!("   Interval: {}s", interval);
+    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+    println!();
+
+    daemon.active.print_curves();
+    if let Some(zero_rpm) = &daemon.active.zero_rpm_config {
+        println!(
+            "🌀 Zero-RPM: off ≤ {}°C, on ≥ {}°C",
+            zero_rpm.stop_temperature, zero_rpm.start_temperature
+        );
+    }
+    println!();
+
+    let mut cycle_count: u64 = 0;
+
+    while running.load(Ordering::SeqCst) {
+        cycle_count += 1;
+
+        // Pick up edits to config.json and defaults.json
+        if let Some((config, rules)) = watch.poll_config() {
+            daemon.reload(&kraken, config, rules);
+        }
+        if watch.defaults_changed() {
+            daemon.refresh_profile(&base_profile);
+        }
+        for changes in daemon.remote_changes(&kraken) {
+            if let Some(profile) = changes.profile {
+                base_profile = profile;
+            }
+        }
+
+        // Apply schedule and process rules (profile, LCD preset, fan cap)
+        daemon.apply_rules(&kraken, &base_profile, None);
+
+        let Some(tick) = daemon.cooling_tick(&kraken, cycle_count, temp_source) else {
+            daemon.wait(&kraken, interval);
+            continue;
+        };
+        daemon.record(&tick, None);
+
+        // Display status
+        println!(
+            "[{:4}] {} {}°C | Pump: {:3}% ({} RPM) | Fan: {:3}%{}",
+            cycle_count,
+            match temp_source {
+                TempSource::Liquid => "💧",
+                TempSource::Cpu => "🔥",
+            },
+            tick.current_temp,
+            tick.pump_duty,
+            tick.status.pump_rpm,
+            tick.fan_duty,
+            daemon.suffix(&tick)
+        );
+
+        daemon.wait(&kraken, interval);
+    }
+
+    println!("\n✅ Cooling daemon stopped after {} cycles.", cycle_count);
+    Ok(())
+}
+
+// =============================================================================
+// Daemon Profile Rules (schedules + process triggers)
+// =============================================================================
+
+/// Visual mode and bucket `cooling-daemon` returns to after an LCD preset
+/// if no mode was set over the control socket (liquid temperature).
+const DEFAULT_LCD_MODE: (u8, u8) = (2, 0);
+
+/// What the LCD shows when no scheduled LCD preset is active.
+#[derive(Debug, Clone, Copy)]
+enum LcdBase {
+    /// `start` draws the gauge or image: presets only change the brightness
+    Gauge { brightness: u8 },
+    /// `cooling-daemon` leaves the LCD to the device: presets also change the
+    /// visual mode. Values the daemon does not know are `None`
+    Device {
+        brightness: Option<u8>,
+        mode: Option<(u8, u8)>,
+    },
+}
+
+/// Profile rules evaluated each tick by the daemon loops.
+///
+/// Process rules take precedence over schedules for the cooling profile;
+/// schedules also control the LCD preset and the fan duty cap.
+struct DaemonRules {
+    scheduler: nzxt_rust_devices::cooling::Scheduler,
+    processes: nzxt_rust_devices::cooling::ProcessWatcher,
+    schedule: nzxt_rust_devices::cooling::ScheduleState,
+    process: Option<nzxt_rust_devices::cooling::ProcessMatch>,
+}
+
+impl DaemonRules {
+    fn new(config: &storage::AppConfig) -> Result<Self> {
+        use nzxt_rust_devices::cooling::{ProcessWatcher, Scheduler};
+
+        // Catch a misspelled profile now rather than when the rule fires
+        for rule in &config.schedules {
+            if let Some(profile) = &rule.cooling_profile {
+                storage::resolve_profile(profile)
+                    .with_context(|| format!("Invalid schedule '{}' in config.json", rule.name))?;
+            }
+        }
+
+        Ok(Self {
+            scheduler: Scheduler::new(&config.schedules)
+                .context("Invalid schedule in config.json")?,
+            processes: ProcessWatcher::new(&config.process_rules)
+                .context("Invalid process rule in config.json")?,
+            schedule: Default::default(),
+            process: None,
+        })
+    }
+
+    /// Fan duty cap from the active schedule.
+    fn fan_cap(&self) -> Option<u8> {
+        self.schedule.max_fan_duty
+    }
+
+    /// Cooling profile selected by the rules, or `base_profile` if none applies.
+    fn profile<'a>(&'a self, base_profile: &'a str) -> &'a str {
+        self.process
+            .as_ref()
+            .map(|m| m.profile.as_str())
+            .or(self.schedule.cooling_profile.as_deref())
+            .unwrap_or(base_profile)
+    }
+
+    /// Evaluate all rules and apply LCD preset changes, restoring `base_lcd`
+    /// when no preset is scheduled. The resulting cooling profile is read
+    /// with [`DaemonRules::profile`].
+    fn tick(&mut self, kraken: &KrakenZ63, base_profile: &str, base_lcd: LcdBase) {
+        use nzxt_rust_devices::config::LcdProfile;
+
+        if !self.scheduler.is_empty()
+            && let Some(state) = self.scheduler.poll(chrono::Local::now().naive_local())
+        {
+            let rules = if state.active_rules.is_empty() {
+                "no rule active".to_string()
+            } else {
+                state.active_rules.join(", ")
+            };
+            println!(
+                "🗓️  Schedule: {} → profile {} | LCD {} | fan cap {}",
+                rules,
+                state.cooling_profile.as_deref().unwrap_or(base_profile),
+                state.lcd_profile.as_deref().unwrap_or("-"),
+                state
+                    .max_fan_duty
+                    .map(|d| format!("{}%", d))
+                    .unwrap_or_else(|| "-".to_string())
+            );
+
+            let lcd = state.lcd_profile.as_deref().and_then(LcdProfile::from_name);
+            let preset_ended = self.schedule.lcd_profile.is_some();
+            match (lcd, base_lcd) {
+                (Some(lcd), LcdBase::Gauge { .. }) => {
+                    let _ = kraken.set_brightness(lcd.brightness);
+                }
+                (Some(lcd), LcdBase::Device { .. }) => {
+                    let _ = kraken.set_brightness(lcd.brightness);
+                    let _ = kraken.set_visual_mode(lcd.mode, lcd.bucket);
+                }
+                (None, LcdBase::Gauge { brightness }) => {
+                    let _ = kraken.set_brightness(brightness);
+                }
+                (None, LcdBase::Device { brightness, mode }) if preset_ended => {
+                    if let Some(brightness) = brightness {
+                        let _ = kraken.set_brightness(brightness);
+                    }
+                    let (mode, index) = mode.unwrap_or(DEFAULT_LCD_MODE);
+                    let _ = kraken.set_visual_mode(mode, index);
+                }
+                (None, LcdBase::Device { .. }) => {}
+            }
+
+            self.schedule = state;
+        }
+
+        if let Some(process) = self.processes.poll() {
+            match &process {
+                Some(m) => println!(
+                    "🎮 Process rule '{}' active → profile {}",
+                    m.rule, m.profile
+                ),
+                None => println!("🎮 Process rule ended → reverting"),
+            }
+            self.process = process;
+        }
+    }
+}
+
+/// Create the load feed-forward controller if enabled in config.json.
+fn load_feed_forward(
+    config: &storage::AppConfig,
+) -> Option<nzxt_rust_devices::cooling::LoadFeedForward> {
+    if !config.load_control.enabled {
+        return None;
+    }
+
+    let load = nzxt_rust_devices::cooling::LoadFeedForward::new(&config.load_control);
+    println!(
+        "⚡ Load feed-forward: pump gain {:.2}, fan gain {:.2}, τ {:.0}s ({})",
+        config.load_control.pump_gain,
+        config.load_control.fan_gain,
+        config.load_control.time_constant_secs,
+        if load.has_package_power() {
+            "CPU usage + RAPL package power"
+        } else {
+            "CPU usage only"
+        }
+    );
+    Some(load)
+}
+
+/// Status line suffix showing the load and boost.
+fn load_suffix(enabled: bool, boost: &nzxt_rust_devices::cooling::LoadBoost) -> String {
+    if !enabled {
+        return String::new();
+    }
+    format!(
+        " | Load: {:.0}% (+{}/+{})",
+        boost.load, boost.pump, boost.fan
+    )
+}
+
+/// Curves and fan mode of the cooling profile in use.
+struct ActiveProfile {
+    /// Profile the curves were loaded from (or last tried to load)
+    name: String,
+    pump: Option<nzxt_rust_devices::cooling::ChannelExpr>,
+    fan: Option<nzxt_rust_devices::cooling::ChannelExpr>,
+    zero_rpm_config: Option<storage::ZeroRpmConfig>,
+    zero_rpm: Option<nzxt_rust_devices::cooling::ZeroRpmController>,
+}
+
+impl ActiveProfile {
+    fn load(profile_name: &str) -> Result<Self> {
+        let profile = storage::resolve_profile(profile_name)
+            .with_context(|| format!("Failed to load profile '{}'", profile_name))?;
+        let channel_expr = |channel: Channel| {
+            profile
+                .channel_expr(channel)
+                .with_context(|| format!("Invalid {} curve in '{}'", channel, profile_name))
+        };
+        let (pump, fan) = (channel_expr(Channel::Pump)?, channel_expr(Channel::Fan)?);
+        for warning in nzxt_rust_devices::cooling::validate_profile(&profile).warnings() {
+            println!("⚠️  {}: {}", profile_name, warning);
+        }
+        let zero_rpm = profile
+            .zero_rpm
+            .as_ref()
+            .map(nzxt_rust_devices::cooling::ZeroRpmController::new)
+            .transpose()
+            .with_context(|| format!("Invalid zero-RPM settings in '{}'", profile_name))?;
+
+        Ok(Self {
+            name: profile_name.to_string(),
+            pump,
+            fan,
+            zero_rpm_config: profile.zero_rpm,
+            zero_rpm,
+        })
+    }
+
+    /// Switch to another profile, keeping the current one if it fails to load.
+    fn reload(&mut self, profile_name: &str) {
+        match Self::load(profile_name) {
+            Ok(profile) => {
+                *self = profile;
+                println!("📊 Switched to profile {}", profile_name);
+            }
+            Err(e) => {
+                // Don't retry every tick; a defaults.json edit triggers a refresh
+                self.name = profile_name.to_string();
+                eprintln!("⚠️  {:#}", e);
+            }
+        }
+    }
+
+    /// Switch to `profile_name` if it is not the profile in use.
+    fn follow(&mut self, profile_name: &str) {
+        if !self.name.eq_ignore_ascii_case(profile_name) {
+            self.reload(profile_name);
+        }
+    }
+
+    /// Re-read the profile after defaults.json changed, keeping the current
+    /// curves if the new ones are invalid.
+    fn refresh(&mut self, profile_name: &str) {
+        match Self::load(profile_name) {
+            Ok(profile) => {
+                // Keep the zero-RPM state unless its settings changed
+                let zero_rpm = if profile.zero_rpm_config == self.zero_rpm_config {
+                    self.zero_rpm.take()
+                } else {
+                    profile.zero_rpm
+                };
+                *self = Self {
+                    zero_rpm,
+                    ..profile
+                };
+                println!("🔄 Reloaded profile {}", profile_name);
+            }
+            Err(e) => {
+                self.name = profile_name.to_string();
+                eprintln!(
+                    "⚠️  defaults.json not applied, keeping current curves: {:#}",
+                    e
+                );
+            }
+        }
+    }
+
+    /// Pump and fan duty for the current temperatures (70%/50% for a channel
+    /// the profile does not set).
+    ///
+    /// A curve that reads the CPU while no CPU sensor answers follows the
+    /// liquid temperature instead.
+    fn duties(
+        &self,
+        liquid_temp: u8,
+        cpu: Option<f32>,
+        source: nzxt_rust_devices::cooling::TempSource,
+    ) -> (u8, u8) {
+        use nzxt_rust_devices::cooling::{ChannelExpr, Temperatures};
+
+        let liquid = Some(liquid_temp as f32);
+        let temps = Temperatures { liquid, cpu };
+        let fallback = Temperatures {
+            liquid,
+            cpu: cpu.or(liquid),
+        };
+        let duty = |expr: &Option<ChannelExpr>, channel, default| {
+            expr.as_ref()
+                .and_then(|e| {
+                    e.duty(channel, &temps, source)
+                        .or_else(|| e.duty(channel, &fallback, source))
+                })
+                .unwrap_or(default)
+        };
+        (
+            duty(&self.pump, Channel::Pump, 70),
+            duty(&self.fan, Channel::Fan, 50),
+        )
+    }
+
+    fn print_curves(&self) {
+        for (channel, expr) in [(Channel::Pump, &self.pump), (Channel::Fan, &self.fan)] {
+            match expr {
+                Some(expr) => println!("📊 {} curve: {}", channel, expr),
+                None => println!("📊 {} curve: not set", channel),
+            }
+        }
+    }
+
+    /// Apply the profile's zero-RPM mode to a fan duty.
+    fn gate_fan(&mut self, temp: u8, duty: u8) -> u8 {
+        match self.zero_rpm.as_mut() {
+            Some(zero_rpm) => zero_rpm.update(temp, duty, std::time::Instant::now()),
+            None => duty,
+        }
+    }
+}
+
+// =============================================================================
+// Hot Reload
+// =============================================================================
+
+/// Watches config.json and defaults.json from the daemon loops.
+struct ConfigWatch {
+    config: storage::FileWatcher,
+    defaults: storage::FileWatcher,
+}
+
+impl ConfigWatch {
+    fn new() -> Result<Self> {
+        Ok(Self {
+            config: storage::FileWatcher::new(storage::get_config_path()?),
+            defaults: storage::FileWatcher::new(storage::get_defaults_path()?),
         })
-        .unwrap_or_default();
+    }
+
+    /// Load config.json if it changed.
+    ///
+    /// The whole file is validated (startup values, schedules, process rules)
+    /// before anything is applied. An invalid file is logged and ignored, so
+    /// the daemon keeps its current settings.
+    fn poll_config(&mut self) -> Option<(storage::AppConfig, DaemonRules)> {
+        if !self.config.changed() {
+            return None;
+        }
+
+        let reloaded = storage::load_config()
+            .context("Failed to load config.json")
+            .and_then(|config| {
+                config
+                    .startup
+                    .validate()
+                    .context("Invalid startup settings")?;
+                let rules = DaemonRules::new(&config)?;
+                Ok((config, rules))
+            });
+
+        match reloaded {
+            Ok(reloaded) => {
+                println!("🔄 config.json changed, applying");
+                Some(reloaded)
+            }
+            Err(e) => {
+                eprintln!(
+                    "⚠️  config.json not applied, keeping current settings: {:#}",
+                    e
+                );
+                None
+            }
+        }
+    }
+
+    /// Returns true if defaults.json changed.
+    fn defaults_changed(&mut self) -> bool {
+        self.defaults.changed()
+    }
+}
+
+// =============================================================================
+// Daemon Loop (shared by cooling-daemon and start)
+// =============================================================================
+
+/// One tick's readings and the duties sent to the device.
+struct CoolingTick {
+    status: nzxt_rust_devices::protocol::DeviceStatus,
+    cpu_reading: Option<f32>,
+    gpu_reading: Option<f32>,
+    /// CPU temperature, or the liquid's without a CPU reading
+    cpu_temp: u8,
+    /// Temperature of the selected source
+    current_temp: u8,
+    pump_duty: u8,
+    fan_duty: u8,
+    boost: nzxt_rust_devices::cooling::LoadBoost,
+}
+
+/// Cooling pipeline and config.json services of the daemon loops.
+struct DaemonLoop {
+    config: storage::AppConfig,
+    rules: DaemonRules,
+    active: ActiveProfile,
+    rpm_targets: RpmTargets,
+    health: FanHealth,
+    load: Option<nzxt_rust_devices::cooling::LoadFeedForward>,
+    sensors: DaemonSensors,
+    metrics: nzxt_rust_devices::telemetry::Metrics,
+    history: Option<nzxt_rust_devices::telemetry::HistoryLogger>,
+    telemetry: DaemonTelemetry,
+    mqtt: MqttControl,
+    alerts: DaemonAlerts,
+    control: DaemonControl,
+}
+
+impl DaemonLoop {
+    /// Start the services config.json enables.
+    fn new(
+        kraken: &KrakenZ63,
+        config: storage::AppConfig,
+        metrics: nzxt_rust_devices::telemetry::Metrics,
+        active: ActiveProfile,
+        rpm_targets: RpmTargets,
+        health: FanHealth,
+    ) -> Result<Self> {
+        Ok(Self {
+            rules: DaemonRules::new(&config)?,
+            active,
+            rpm_targets,
+            health,
+            load: load_feed_forward(&config),
+            sensors: DaemonSensors::new(&config)?,
+            history: open_history(&config),
+            telemetry: DaemonTelemetry::default(),
+            mqtt: MqttControl::new(kraken, &config),
+            alerts: DaemonAlerts::new(&config)?,
+            control: DaemonControl::new(kraken, &config, &metrics),
+            metrics,
+            config,
+        })
+    }
+
+    /// Apply an edited config.json, restarting the services whose section
+    /// changed.
+    fn reload(&mut self, kraken: &KrakenZ63, config: storage::AppConfig, rules: DaemonRules) {
+        if config.load_control != self.config.load_control {
+            self.load = load_feed_forward(&config);
+        }
+        if config.history != self.config.history {
+            self.history = open_history(&config);
+        }
+        if config.mqtt != self.config.mqtt {
+            // Drop the old connection first: both would use the same client id
+            drop(std::mem::take(&mut self.mqtt));
+            self.mqtt = MqttControl::new(kraken, &config);
+        }
+        if config.alerts != self.config.alerts {
+            self.alerts.reload(&config);
+        }
+        if config.sensors != self.config.sensors {
+            self.sensors.reload(&config);
+        }
+        if (&config.control, &config.dbus) != (&self.config.control, &self.config.dbus) {
+            self.control.listen(&config);
+        }
+        self.rules = rules;
+        self.config = config;
+    }
+
+    /// Reload the active profile after defaults.json changed.
+    fn refresh_profile(&mut self, base_profile: &str) {
+        self.active.refresh(self.rules.profile(base_profile));
+    }
+
+    /// Settings changed from MQTT and the control socket since the last tick.
+    fn remote_changes(&mut self, kraken: &KrakenZ63) -> [RemoteChanges; 2] {
+        [self.mqtt.poll(kraken), self.control.take_changes()]
+    }
+
+    /// Evaluate the schedule and process rules and follow their profile.
+    ///
+    /// With `gauge_brightness` set the LCD shows the gauge or an image, so
+    /// LCD presets only change its brightness.
+    fn apply_rules(
+        &mut self,
+        kraken: &KrakenZ63,
+        base_profile: &str,
+        gauge_brightness: Option<u8>,
+    ) {
+        let base_lcd = match gauge_brightness {
+            Some(brightness) => LcdBase::Gauge { brightness },
+            None => LcdBase::Device {
+                brightness: self.control.brightness,
+                mode: self.control.lcd_mode,
+            },
+        };
+        self.rules.tick(kraken, base_profile, base_lcd);
+        self.active.follow(self.rules.profile(base_profile));
+    }
 
-    let fan_curve: Vec<(u8, u8)> = profile
-        .channel_settings
-        .iter()
-        .find(|c| c.channel_name.to_lowercase() == "fan")
-        .and_then(|c| c.mode.as_ref())
-        .and_then(|m| m.custom_thresholds.as_ref())
-        .map(|t| {
-            t.iter()
-                .map(|th| (th.temperature, th.fan_percentage))
-                .collect()
+    /// Read the device and sensors, then compute and set this tick's duties.
+    ///
+    /// Device errors are logged and the daemon keeps running. Returns `None`
+    /// if the status could not be read.
+    fn cooling_tick(
+        &mut self,
+        kraken: &KrakenZ63,
+        cycle: u64,
+        temp_source: nzxt_rust_devices::cooling::TempSource,
+    ) -> Option<CoolingTick> {
+        use nzxt_rust_devices::cooling::TempSource;
+
+        let status = match kraken.get_status() {
+            Ok(s) => s,
+            Err(e) => {
+                eprintln!("[{}] ⚠️  Failed to get status: {}", cycle, e);
+                self.alerts.tick(None, false);
+                return None;
+            }
+        };
+        let (cpu_reading, gpu_reading) = self.sensors.read();
+        self.metrics.record_status(&status);
+        self.metrics.record_temperatures(cpu_reading, gpu_reading);
+
+        let liquid_temp = status.liquid_temp_c as u8;
+        // Without a CPU reading, follow the liquid rather than read 0 °C
+        let cpu_temp = cpu_reading.map_or(liquid_temp, |t| t as u8);
+
+        // Select temperature based on source
+        let current_temp = match temp_source {
+            TempSource::Liquid => liquid_temp,
+            TempSource::Cpu => cpu_temp,
+        };
+
+        // Calculate pump and fan duty
+        let (pump_duty, fan_duty) = self.active.duties(liquid_temp, cpu_reading, temp_source);
+
+        // Add load feed-forward boost and apply zero-RPM mode
+        let boost = self.load.as_mut().map(|l| l.tick()).unwrap_or_default();
+        let pump_duty = boost.apply(Channel::Pump, pump_duty);
+        let fan_duty = boost.apply(Channel::Fan, fan_duty);
+        let fan_duty = self.active.gate_fan(current_temp, fan_duty);
+
+        // RPM targets, then duties held from MQTT or the control socket,
+        // replace the curve on their channel
+        let (pump_duty, fan_duty) = self.rpm_targets.apply(&status, pump_duty, fan_duty);
+        let (pump_duty, fan_duty) = self.mqtt.apply(pump_duty, fan_duty);
+        let (pump_duty, fan_duty) = self.control.apply(pump_duty, fan_duty);
+
+        // The schedule fan cap limits every source of the fan duty
+        let fan_duty = self
+            .rules
+            .fan_cap()
+            .map_or(fan_duty, |cap| fan_duty.min(cap));
+
+        if let Err(e) = kraken.set_pump_speed(pump_duty) {
+            eprintln!("[{}] ⚠️  Failed to set pump duty: {}", cycle, e);
+        }
+        if let Err(e) = kraken.set_fan_speed(fan_duty) {
+            eprintln!("[{}] ⚠️  Failed to set fan duty: {}", cycle, e);
+        }
+        self.metrics.record_duties(pump_duty, fan_duty);
+        self.health.update(&status, pump_duty, fan_duty);
+
+        Some(CoolingTick {
+            status,
+            cpu_reading,
+            gpu_reading,
+            cpu_temp,
+            current_temp,
+            pump_duty,
+            fan_duty,
+            boost,
         })
-        .unwrap_or_default();
+    }
+
+    /// Record a tick in the telemetry, history log, MQTT, alerts and the
+    /// control socket status.
+    fn record(&mut self, tick: &CoolingTick, lcd_bucket: Option<u8>) {
+        let record = nzxt_rust_devices::telemetry::HistoryRecord {
+            cpu_temp: tick.cpu_reading,
+            gpu_temp: tick.gpu_reading,
+            cpu_load: self.load.is_some().then_some(tick.boost.load),
+            profile: Some(self.active.name.clone()),
+            commanded: Some((tick.pump_duty, tick.fan_duty)),
+            lcd_bucket,
+            ..nzxt_rust_devices::telemetry::HistoryRecord::new(&tick.status)
+        };
+        self.telemetry.record(&record);
+        self.mqtt.publish(&record);
+        self.alerts.tick(Some(&record), self.health.fan_stalled());
+        self.control.update(&record);
+        log_history(&mut self.history, record);
+    }
+
+    /// Load boost and RPM target notes for a tick's status line.
+    fn suffix(&self, tick: &CoolingTick) -> String {
+        format!(
+            "{}{}",
+            load_suffix(self.load.is_some(), &tick.boost),
+            self.rpm_targets.suffix()
+        )
+    }
+
+    /// Answer control requests until the next tick.
+    fn wait(&mut self, kraken: &KrakenZ63, interval: u64) {
+        self.control.wait(
+            kraken,
+            &self.telemetry.history,
+            Duration::from_secs(interval),
+        );
+    }
+}
+
+// =============================================================================
+// Unified Start Command (LCD Monitor + Cooling Daemon)
+// =============================================================================
+
+/// Create the metrics registry and, if `--metrics` or config.json enables
+/// it, serve it over HTTP.
+fn start_metrics(
+    kraken: &KrakenZ63,
+    config: &storage::AppConfig,
+    cli_addr: Option<&str>,
+) -> Result<(
+    nzxt_rust_devices::telemetry::Metrics,
+    Option<nzxt_rust_devices::telemetry::MetricsServer>,
+)> {
+    use nzxt_rust_devices::telemetry::{Metrics, MetricsServer};
+
+    let metrics = Metrics::new(device_labels(kraken));
+
+    let addr = match cli_addr {
+        Some(addr) => addr,
+        None if config.metrics.enabled => config.metrics.listen.as_str(),
+        None => return Ok((metrics, None)),
+    };
+    let server = MetricsServer::start(addr, metrics.clone())?;
+    println!("📈 Metrics at http://{}/metrics", server.addr());
+    Ok((metrics, Some(server)))
+}
+
+/// Serial and firmware of an initialized device, for metrics and MQTT.
+fn device_labels(kraken: &KrakenZ63) -> nzxt_rust_devices::telemetry::DeviceLabels {
+    nzxt_rust_devices::telemetry::DeviceLabels {
+        serial: kraken
+            .serial_number()
+            .unwrap_or_else(|| "unknown".to_string()),
+        firmware: kraken
+            .firmware_version()
+            .map_or_else(|| "unknown".to_string(), |f| f.to_string()),
+    }
+}
+
+/// Settings changed from MQTT or the control socket since the last tick.
+#[derive(Debug, Default)]
+struct RemoteChanges {
+    profile: Option<String>,
+    brightness: Option<u8>,
+    orientation: Option<u8>,
+}
+
+/// MQTT publishing and the commands received from Home Assistant.
+///
+/// Duties set from MQTT replace the curve on their channel until a profile
+/// is selected.
+#[derive(Default)]
+struct MqttControl {
+    bridge: Option<nzxt_rust_devices::telemetry::MqttBridge>,
+    /// Held duties: (pump, fan)
+    held: (Option<u8>, Option<u8>),
+}
+
+impl MqttControl {
+    /// Connect if config.json enables MQTT. Failures are logged and leave
+    /// MQTT off.
+    fn new(kraken: &KrakenZ63, config: &storage::AppConfig) -> Self {
+        let mut control = Self::default();
+        if !config.mqtt.enabled {
+            return control;
+        }
+
+        let profiles = storage::defaults::load_defaults()
+            .map(|d| d.profiles.into_iter().map(|p| p.id).collect())
+            .unwrap_or_default();
+        match nzxt_rust_devices::telemetry::MqttBridge::connect(
+            &config.mqtt,
+            device_labels(kraken),
+            profiles,
+        ) {
+            Ok(bridge) => {
+                println!(
+                    "📡 MQTT broker {}:{} (state on {})",
+                    config.mqtt.host,
+                    config.mqtt.port,
+                    bridge.topics().state()
+                );
+                control.bridge = Some(bridge);
+            }
+            Err(e) => eprintln!("⚠️  MQTT disabled: {}", e),
+        }
+        control
+    }
+
+    /// Apply the commands received since the last tick. Duties are held and
+    /// the brightness is set here; a new profile is returned for the caller.
+    fn poll(&mut self, kraken: &KrakenZ63) -> RemoteChanges {
+        use nzxt_rust_devices::telemetry::{MqttCommand, MqttEvent};
+
+        let mut changes = RemoteChanges::default();
+        let Some(bridge) = &self.bridge else {
+            return changes;
+        };
+        for event in bridge.poll() {
+            match event {
+                MqttEvent::Connected => println!("📡 MQTT connected"),
+                MqttEvent::Disconnected(e) => eprintln!("⚠️  MQTT: {} (retrying)", e),
+                MqttEvent::Command(MqttCommand::PumpDuty(duty)) => {
+                    let duty = duty.max(Channel::Pump.min_duty());
+                    self.held.0 = Some(duty);
+                    println!("📡 MQTT: pump held at {}%", duty);
+                }
+                MqttEvent::Command(MqttCommand::FanDuty(duty)) => {
+                    self.held.1 = Some(duty);
+                    println!("📡 MQTT: fan held at {}%", duty);
+                }
+                MqttEvent::Command(MqttCommand::Brightness(brightness)) => {
+                    match kraken.set_brightness(brightness) {
+                        Ok(()) => {
+                            println!("📡 MQTT: brightness {}%", brightness);
+                            changes.brightness = Some(brightness);
+                        }
+                        Err(e) => eprintln!("⚠️  MQTT: failed to set brightness: {}", e),
+                    }
+                }
+                MqttEvent::Command(MqttCommand::Profile(name)) => {
+                    match storage::resolve_profile(&name) {
+                        Ok(_) => {
+                            println!("📡 MQTT: profile {}", name);
+                            self.held = (None, None);
+                            changes.profile = Some(name);
+                        }
+                        Err(e) => eprintln!("⚠️  MQTT: {}", e),
+                    }
+                }
+            }
+        }
+        changes
+    }
+
+    /// Replace the computed duties with the held ones.
+    fn apply(&self, pump_duty: u8, fan_duty: u8) -> (u8, u8) {
+        (
+            self.held.0.unwrap_or(pump_duty),
+            self.held.1.unwrap_or(fan_duty),
+        )
+    }
+
+    fn publish(&self, record: &nzxt_rust_devices::telemetry::HistoryRecord) {
+        if let Some(bridge) = &self.bridge {
+            bridge.publish_state(&record.into());
+        }
+    }
+}
+
+/// Control socket and D-Bus service of the daemon loops.
+///
+/// Requests are answered while the loop waits for its next tick. Duties set
+/// through them replace the curve on their channel until a profile is
+/// selected.
+struct DaemonControl {
+    queue: nzxt_rust_devices::ipc::RequestQueue,
+    server: Option<nzxt_rust_devices::ipc::ControlServer>,
+    dbus: Option<nzxt_rust_devices::ipc::DbusService>,
+    /// Held duties: (pump, fan)
+    held: (Option<u8>, Option<u8>),
+    rendering_paused: bool,
+    /// LCD brightness last set, published on D-Bus
+    brightness: Option<u8>,
+    /// LCD visual mode and bucket last set over the control socket
+    lcd_mode: Option<(u8, u8)>,
+    changes: RemoteChanges,
+    /// Latest tick, answered to `status`
+    last: Option<nzxt_rust_devices::telemetry::HistoryRecord>,
+    /// Records the uploads of `upload-image` requests
+    metrics: nzxt_rust_devices::telemetry::Metrics,
+}
+
+impl DaemonControl {
+    /// Listen on the interfaces config.json enables. Failures are logged
+    /// and leave that interface off.
+    fn new(
+        kraken: &KrakenZ63,
+        config: &storage::AppConfig,
+        metrics: &nzxt_rust_devices::telemetry::Metrics,
+    ) -> Self {
+        let mut control = Self {
+            queue: Default::default(),
+            server: None,
+            dbus: None,
+            held: (None, None),
+            rendering_paused: false,
+            brightness: kraken.get_lcd_info().ok().map(|(brightness, _)| brightness),
+            lcd_mode: None,
+            changes: Default::default(),
+            last: None,
+            metrics: metrics.clone(),
+        };
+        control.listen(config);
+        control
+    }
+
+    fn listen(&mut self, config: &storage::AppConfig) {
+        // Drop the old ones first: both would use the same path and name
+        self.server = None;
+        self.dbus = None;
+
+        if config.control.enabled {
+            match nzxt_rust_devices::ipc::ControlServer::bind(&config.control, self.queue.sender())
+            {
+                Ok(server) => {
+                    println!("🔌 Control socket {}", server.path().display());
+                    self.server = Some(server);
+                }
+                Err(e) => eprintln!("⚠️  Control socket disabled: {}", e),
+            }
+        }
+        if config.dbus.enabled {
+            match nzxt_rust_devices::ipc::DbusService::connect(&config.dbus, self.queue.sender()) {
+                Ok(dbus) => {
+                    println!(
+                        "🔌 D-Bus service {} on the {:?} bus",
+                        nzxt_rust_devices::ipc::dbus::BUS_NAME,
+                        config.dbus.bus
+                    );
+                    self.dbus = Some(dbus);
+                }
+                Err(e) => eprintln!("⚠️  D-Bus service disabled: {}", e),
+            }
+        }
+    }
+
+    /// Answer requests until `timeout` has passed.
+    fn wait(
+        &mut self,
+        kraken: &KrakenZ63,
+        history: &nzxt_rust_devices::telemetry::TelemetryHistory,
+        timeout: Duration,
+    ) {
+        let deadline = std::time::Instant::now() + timeout;
+        while let Some(pending) = self
+            .queue
+            .recv_timeout(deadline.saturating_duration_since(std::time::Instant::now()))
+        {
+            let response = self.handle(kraken, history, pending.request.clone());
+            pending.respond(response);
+        }
+    }
+
+    fn handle(
+        &mut self,
+        kraken: &KrakenZ63,
+        history: &nzxt_rust_devices::telemetry::TelemetryHistory,
+        request: ControlRequest,
+    ) -> nzxt_rust_devices::ipc::ControlResponse {
+        use nzxt_rust_devices::ipc::{ControlResponse, DaemonStatus};
+
+        let result = match request {
+            ControlRequest::Status => {
+                return match &self.last {
+                    Some(record) => ControlResponse::with_result(&DaemonStatus::new(
+                        record,
+                        self.rendering_paused,
+                        self.brightness,
+                        kraken.serial_number(),
+                    )),
+                    None => ControlResponse::error("No reading yet"),
+                };
+            }
+            ControlRequest::Buckets => {
+                return ControlResponse::from_result(kraken.query_all_buckets());
+            }
+            ControlRequest::Stats { window_secs, above } => {
+                let window = window_secs.map(Duration::from_secs);
+                return ControlResponse::with_result(&history.report(window, above));
+            }
+            ControlRequest::SetDuty { channel, duty } => channel.validate_duty(duty).map(|duty| {
+                match channel {
+                    Channel::Pump => self.held.0 = Some(duty),
+                    Channel::Fan => self.held.1 = Some(duty),
+                }
+                println!("🔌 Control: {} held at {}%", channel, duty);
+            }),
+            ControlRequest::SetProfile { name } => storage::resolve_profile(&name).map(|_| {
+                println!("🔌 Control: profile {}", name);
+                self.held = (None, None);
+                self.changes.profile = Some(name);
+            }),
+            ControlRequest::SetBrightness { brightness } => {
+                kraken.set_brightness(brightness).map(|()| {
+                    println!("🔌 Control: brightness {}%", brightness);
+                    self.brightness = Some(brightness);
+                    self.changes.brightness = Some(brightness);
+                })
+            }
+            ControlRequest::SetOrientation { orientation } => {
+                kraken.set_orientation(orientation).map(|()| {
+                    println!("🔌 Control: orientation {}°", orientation as u16 * 90);
+                    self.changes.orientation = Some(orientation);
+                })
+            }
+            ControlRequest::SetLcdMode { mode, index } => {
+                kraken.set_visual_mode(mode, index).map(|()| {
+                    println!("🔌 Control: LCD mode {} (idx {})", mode, index);
+                    self.lcd_mode = Some((mode, index));
+                    self.rendering_paused = true;
+                })
+            }
+            ControlRequest::UploadImage { path } => {
+                return match self.metrics.time_upload(|| upload_image(kraken, &path)) {
+                    Ok(()) => {
+                        self.rendering_paused = true;
+                        ControlResponse::ok()
+                    }
+                    Err(e) => ControlResponse::error(format!("{:#}", e)),
+                };
+            }
+            ControlRequest::PauseRendering { paused } => {
+                println!(
+                    "🔌 Control: LCD gauge {}",
+                    if paused { "paused" } else { "resumed" }
+                );
+                self.rendering_paused = paused;
+                Ok(())
+            }
+        };
+        ControlResponse::from_result(result)
+    }
+
+    /// Settings changed since the last call.
+    fn take_changes(&mut self) -> RemoteChanges {
+        std::mem::take(&mut self.changes)
+    }
+
+    /// Replace the computed duties with the held ones.
+    fn apply(&self, pump_duty: u8, fan_duty: u8) -> (u8, u8) {
+        (
+            self.held.0.unwrap_or(pump_duty),
+            self.held.1.unwrap_or(fan_duty),
+        )
+    }
+
+    fn update(&mut self, record: &nzxt_rust_devices::telemetry::HistoryRecord) {
+        self.last = Some(record.clone());
+
+        if let Some(dbus) = &self.dbus {
+            let state =
+                nzxt_rust_devices::ipc::DbusState::new(record, self.brightness.unwrap_or(0));
+            if let Err(e) = dbus.publish(state) {
+                eprintln!("⚠️  D-Bus service disabled: {}", e);
+                self.dbus = None;
+            }
+        }
+    }
+}
+
+/// Open the history log if config.json enables it. Failures are logged and
+/// leave logging off.
+fn open_history(
+    config: &storage::AppConfig,
+) -> Option<nzxt_rust_devices::telemetry::HistoryLogger> {
+    if !config.history.enabled {
+        return None;
+    }
+    match nzxt_rust_devices::telemetry::HistoryLogger::new(&config.history) {
+        Ok(logger) => {
+            println!("📝 History log in {}", logger.directory().display());
+            Some(logger)
+        }
+        Err(e) => {
+            eprintln!("⚠️  History log disabled: {}", e);
+            None
+        }
+    }
+}
+
+fn log_history(
+    history: &mut Option<nzxt_rust_devices::telemetry::HistoryLogger>,
+    record: nzxt_rust_devices::telemetry::HistoryRecord,
+) {
+    if let Some(logger) = history
+        && let Err(e) = logger.log(&record)
+    {
+        eprintln!("⚠️  History log: {}", e);
+    }
+}
+
+/// Samples the temperature trend on the LCD is computed over.
+const LCD_TREND_WINDOW: Duration = Duration::from_secs(60);
+
+/// Recent samples of a daemon loop, answered to `stats` over the control
+/// socket.
+#[derive(Default)]
+struct DaemonTelemetry {
+    history: nzxt_rust_devices::telemetry::TelemetryHistory,
+}
+
+impl DaemonTelemetry {
+    fn record(&mut self, record: &nzxt_rust_devices::telemetry::HistoryRecord) {
+        self.history.push(record.into());
+    }
+
+    /// Trend of a temperature for the LCD (°C/min).
+    fn trend(&self, metric: nzxt_rust_devices::telemetry::Metric) -> Option<f32> {
+        self.history.rate_of_change(metric, Some(LCD_TREND_WINDOW))
+    }
+}
+
+/// Alert rules of config.json, evaluated on every daemon tick.
+struct DaemonAlerts {
+    engine: nzxt_rust_devices::telemetry::AlertEngine,
+}
+
+impl DaemonAlerts {
+    fn new(config: &storage::AppConfig) -> Result<Self> {
+        let engine = nzxt_rust_devices::telemetry::AlertEngine::new(&config.alerts)
+            .context("Invalid alert rule in config.json")?;
+        if !engine.is_empty() {
+            println!("🚨 {} alert rule(s) active", config.alerts.len());
+        }
+        Ok(Self { engine })
+    }
+
+    /// Replace the rules after a config.json edit, keeping the current ones
+    /// if the new ones are invalid.
+    fn reload(&mut self, config: &storage::AppConfig) {
+        match Self::new(config) {
+            Ok(alerts) => *self = alerts,
+            Err(e) => eprintln!("⚠️  Alerts not updated: {:#}", e),
+        }
+    }
+
+    /// Evaluate the rules on this tick's record, or with no readings when the
+    /// device could not be read.
+    fn tick(
+        &mut self,
+        record: Option<&nzxt_rust_devices::telemetry::HistoryRecord>,
+        fan_stalled: bool,
+    ) {
+        let input = nzxt_rust_devices::telemetry::AlertInput {
+            sample: record.map(Into::into),
+            fan_stalled,
+        };
+        for event in self.engine.evaluate(&input, std::time::Instant::now()) {
+            match event.state {
+                nzxt_rust_devices::telemetry::AlertState::Firing => {
+                    println!("🚨 Alert {}: {}", event.name, event.condition)
+                }
+                nzxt_rust_devices::telemetry::AlertState::Resolved => {
+                    println!("✅ Alert {} resolved", event.name)
+                }
+            }
+            // Commands and webhooks may be slow; keep the loop running
+            if !event.actions.is_empty() {
+                std::thread::spawn(move || {
+                    for e in event.run_actions() {
+                        eprintln!("⚠️  Alert {}: {}", event.name, e);
+                    }
+                });
+            }
+        }
+    }
+}
+
+/// CPU/GPU sensors of a daemon, reporting when a selected one disappears.
+struct DaemonSensors {
+    sensors: SystemSensors,
+    missing: Vec<nzxt_rust_devices::utils::sensors::SensorKind>,
+}
+
+impl DaemonSensors {
+    /// Fails if a selected sensor is missing and `on_missing` is `refuse`.
+    fn new(config: &storage::AppConfig) -> Result<Self> {
+        let sensors = system_sensors(config)?;
+        let missing = sensors.missing();
+        if !missing.is_empty() && config.sensors.on_missing == storage::MissingSensor::Refuse {
+            let missing: Vec<String> = missing
+                .iter()
+                .map(|&k| sensors.describe_missing(k))
+                .collect();
+            anyhow::bail!(
+                "{} (pick another with `sensors --pick`)",
+                missing.join(", ")
+            );
+        }
+        for &kind in &missing {
+            eprintln!(
+                "⚠️  {}, reading it as missing",
+                sensors.describe_missing(kind)
+            );
+        }
+        Ok(Self { sensors, missing })
+    }
+
+    fn reload(&mut self, config: &storage::AppConfig) {
+        match self.sensors.select(&config.sensors) {
+            Ok(()) => self.missing.clear(),
+            Err(e) => eprintln!("⚠️  Sensor selection not applied: {}", e),
+        }
+    }
+
+    /// Refresh and read the CPU and GPU temperatures.
+    fn read(&mut self) -> (Option<f32>, Option<f32>) {
+        self.sensors.refresh();
+        let missing = self.sensors.missing();
+        for &kind in missing.iter().filter(|k| !self.missing.contains(k)) {
+            eprintln!("⚠️  {}", self.sensors.describe_missing(kind));
+        }
+        for kind in self.missing.iter().filter(|k| !missing.contains(k)) {
+            println!("✅ {} sensor is back", kind);
+        }
+        self.missing = missing;
+        (self.sensors.find_cpu_temp(), self.sensors.find_gpu_temp())
+    }
+}
+
+/// Settings of the `start` command, resolved from the CLI and config.json.
+#[derive(Debug, Clone, PartialEq)]
+struct StartSettings {
+    profile: String,
+    source: String,
+    interval: u64,
+    pump_rpm: Option<u16>,
+    fan_rpm: Option<u16>,
+    brightness: u8,
+    orientation: u16,
+    display_mode: String,
+    image_path: Option<String>,
+    gif_path: Option<String>,
+}
+
+impl StartSettings {
+    /// CLI values override config.json (a CLI value equal to its default
+    /// counts as not given, except `--profile`, which has no default).
+    /// Without either, the profile activated with `profile activate` is used.
+    fn resolve(
+        startup: &storage::StartupConfig,
+        cli_profile: Option<&str>,
+        cli_source: &str,
+        cli_interval: u64,
+        cli_pump_rpm: Option<u16>,
+        cli_fan_rpm: Option<u16>,
+    ) -> Self {
+        let profile = if let Some(profile) = cli_profile {
+            profile.to_string()
+        } else if startup.cooling_profile != "silent" {
+            startup.cooling_profile.clone()
+        } else {
+            storage::active_profile_id()
+                .ok()
+                .flatten()
+                .unwrap_or_else(|| startup.cooling_profile.clone())
+        };
+
+        Self {
+            profile,
+            source: if cli_source == "liquid" {
+                startup.temperature_source.clone()
+            } else {
+                cli_source.to_string()
+            },
+            interval: if cli_interval == 2 {
+                startup.interval
+            } else {
+                cli_interval
+            },
+            pump_rpm: cli_pump_rpm.or(startup.pump_rpm),
+            fan_rpm: cli_fan_rpm.or(startup.fan_rpm),
+            brightness: startup.brightness,
+            orientation: startup.orientation,
+            display_mode: startup.display_mode.to_lowercase(),
+            image_path: startup.image_path.clone(),
+            gif_path: startup.gif_path.clone(),
+        }
+    }
+}
+
+/// Radial gauge settings of the active LCD profile in config.json.
+fn load_gauge_config(
+    config: &storage::AppConfig,
+) -> Option<nzxt_rust_devices::utils::radial_gauge::RadialGaugeConfig> {
+    config
+        .active_profile
+        .as_ref()
+        .and_then(|name| config.lcd.get(name))
+        .or_else(|| config.lcd.get("default_gauge"))
+        .and_then(|p| p.radial_gauge.as_ref())
+        .map(nzxt_rust_devices::utils::radial_gauge::RadialGaugeConfig::from_stored)
+}
+
+fn cmd_start(
+    cli_profile: Option<&str>,
+    cli_source: &str,
+    cli_interval: u64,
+    cli_pump_rpm: Option<u16>,
+    cli_fan_rpm: Option<u16>,
+    cli_metrics: Option<&str>,
+) -> Result<()> {
+    use nzxt_rust_devices::cooling::TempSource;
+    use nzxt_rust_devices::device::BucketManager;
+    use nzxt_rust_devices::telemetry::Metric;
+    use nzxt_rust_devices::utils::stats_image;
+
+    // Ensure storage exists and load configs
+    storage::ensure_defaults_exist().context("Failed to initialize defaults")?;
+    nzxt_rust_devices::storage::ensure_config_exists()?;
+    migrate_config_files()?;
+
+    // Load config for fallback values; a broken file must not be ignored
+    let app_config =
+        nzxt_rust_devices::storage::load_config().context("Failed to load config.json")?;
+    app_config
+        .startup
+        .validate()
+        .context("Invalid startup settings in config.json")?;
+    let resolve = |startup: &storage::StartupConfig| {
+        StartSettings::resolve(
+            startup,
+            cli_profile,
+            cli_source,
+            cli_interval,
+            cli_pump_rpm,
+            cli_fan_rpm,
+        )
+    };
+    let mut settings = resolve(&app_config.startup);
+
+    // Initialize device
+    let mut kraken = KrakenZ63::open().context("Failed to open Kraken Z63")?;
+    kraken.initialize().context("Failed to initialize device")?;
+
+    // Apply configured brightness
+    kraken.set_brightness(settings.brightness)?;
+
+    // Get/apply orientation
+    let (_, current_orientation) = kraken.get_lcd_info().context("Failed to get LCD info")?;
+    let target_orientation = (settings.orientation / 90) as u8;
+    if target_orientation != current_orientation && settings.orientation > 0 {
+        kraken.set_orientation(target_orientation)?;
+    }
+    let mut orientation = if settings.orientation > 0 {
+        target_orientation
+    } else {
+        current_orientation
+    };
+
+    // Load cooling profile
+    let active = ActiveProfile::load(&settings.profile)?;
+
+    // Calibration: fan degradation warnings and RPM-target seeding
+    let calibration = load_device_calibration(&kraken);
+    let rpm_targets = RpmTargets::new(calibration.as_ref(), settings.pump_rpm, settings.fan_rpm)?;
+    let health = FanHealth::new(calibration.clone());
+
+    // Load gauge config
+    let mut gauge_config = load_gauge_config(&app_config);
+    let mut watch = ConfigWatch::new()?;
+
+    // Initialize sensors, telemetry and remote control
+    let (metrics, _metrics_server) = start_metrics(&kraken, &app_config, cli_metrics)?;
+    let mut daemon = DaemonLoop::new(&kraken, app_config, metrics, active, rpm_targets, health)?;
+
+    // Parse temperature source
+    let mut temp_source = TempSource::from(settings.source.as_str());
 
     // Setup Ctrl+C handler
     let running = Arc::new(AtomicBool::new(true));
@@ -1290,25 +4211,28 @@ fn cmd_start(cli_profile: &str, cli_source: &str, cli_interval: u64) -> Result<(
     // Print startup info
     println!("🚀 Unified Monitor Started (Ctrl+C to stop)");
     println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
-    println!("   Mode:     {}", display_mode);
-    println!("   Profile:  {}", profile_name);
+    println!("   Mode:     {}", settings.display_mode);
+    println!("   Profile:  {}", settings.profile);
     println!("   Source:   {}", temp_source);
-    println!("   Interval: {}s", interval);
+    println!("   Interval: {}s", settings.interval);
     println!(
         "   LCD:      {}° | {}%",
         orientation as u16 * 90,
-        brightness
+        settings.brightness
     );
     println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
     println!();
 
     // Handle display mode
-    let is_radial_mode = match display_mode.as_str() {
+    let is_radial_mode = match settings.display_mode.as_str() {
         "image" => {
-            if let Some(ref path) = startup.image_path {
+            if let Some(ref path) = settings.image_path {
                 println!("🖼️  Uploading static image: {}", path);
                 let path_buf = std::path::PathBuf::from(path);
-                if let Err(e) = cmd_upload_image(&path_buf) {
+                if let Err(e) = daemon
+                    .metrics
+                    .time_upload(|| upload_image(&kraken, &path_buf))
+                {
                     eprintln!("⚠️  Upload failed: {}. Falling back to radial.", e);
                     true
                 } else {
@@ -1321,10 +4245,13 @@ fn cmd_start(cli_profile: &str, cli_source: &str, cli_interval: u64) -> Result<(
             }
         }
         "gif" => {
-            if let Some(ref path) = startup.gif_path {
+            if let Some(ref path) = settings.gif_path {
                 println!("🎞️  Uploading GIF: {}", path);
                 let path_buf = std::path::PathBuf::from(path);
-                if let Err(e) = cmd_upload_image(&path_buf) {
+                if let Err(e) = daemon
+                    .metrics
+                    .time_upload(|| upload_image(&kraken, &path_buf))
+                {
                     eprintln!("⚠️  Upload failed: {}. Falling back to radial.", e);
                     true
                 } else {
@@ -1355,96 +4282,155 @@ fn cmd_start(cli_profile: &str, cli_source: &str, cli_interval: u64) -> Result<(
     while running.load(Ordering::SeqCst) {
         cycle_count += 1;
 
-        // Get status and temperatures
-        let status = match kraken.get_status() {
-            Ok(s) => s,
-            Err(e) => {
-                eprintln!("[{}] ⚠️  Failed to get status: {}", cycle_count, e);
-                std::thread::sleep(Duration::from_secs(interval));
-                continue;
+        // Pick up edits to config.json and defaults.json without restarting
+        // (a restart would clear the LCD buckets again)
+        if let Some((config, new_rules)) = watch.poll_config() {
+            let next = resolve(&config.startup);
+
+            // Build everything that can fail before applying anything
+            let prepared = (|| -> Result<_> {
+                let profile = (next.profile != settings.profile)
+                    .then(|| ActiveProfile::load(&next.profile))
+                    .transpose()?;
+                let targets = (next.pump_rpm != settings.pump_rpm
+                    || next.fan_rpm != settings.fan_rpm)
+                    .then(|| RpmTargets::new(calibration.as_ref(), next.pump_rpm, next.fan_rpm))
+                    .transpose()?;
+                Ok((profile, targets))
+            })();
+
+            match prepared {
+                Ok((profile, targets)) => {
+                    if let Some(profile) = profile {
+                        daemon.active = profile;
+                        println!("📊 Switched to profile {}", next.profile);
+                    }
+                    if let Some(targets) = targets {
+                        daemon.rpm_targets = targets;
+                    }
+                    if next.brightness != settings.brightness {
+                        let _ = kraken.set_brightness(next.brightness);
+                    }
+                    if next.orientation != settings.orientation && next.orientation > 0 {
+                        orientation = (next.orientation / 90) as u8;
+                        let _ = kraken.set_orientation(orientation);
+                    }
+                    if (&next.display_mode, &next.image_path, &next.gif_path)
+                        != (
+                            &settings.display_mode,
+                            &settings.image_path,
+                            &settings.gif_path,
+                        )
+                    {
+                        println!("ℹ️  Display mode changes apply after a restart");
+                    }
+                    temp_source = TempSource::from(next.source.as_str());
+                    gauge_config = load_gauge_config(&config);
+                    daemon.reload(&kraken, config, new_rules);
+                    settings = next;
+                }
+                Err(e) => eprintln!(
+                    "⚠️  config.json not applied, keeping current settings: {:#}",
+                    e
+                ),
             }
-        };
-
-        sensors.refresh();
-        let liquid_temp = status.liquid_temp_c as u8;
-        let cpu_temp = sensors.find_cpu_temp().unwrap_or(0.0) as u8;
+        }
+        if watch.defaults_changed() {
+            // The active profile id may have changed as well
+            settings.profile = resolve(&daemon.config.startup).profile;
+            daemon.refresh_profile(&settings.profile);
+        }
+        for changes in daemon.remote_changes(&kraken) {
+            if let Some(profile) = changes.profile {
+                settings.profile = profile;
+            }
+            if let Some(brightness) = changes.brightness {
+                // Restored when a scheduled LCD preset ends
+                settings.brightness = brightness;
+                daemon.control.brightness = Some(brightness);
+            }
+            if let Some(target) = changes.orientation {
+                orientation = target;
+            }
+        }
 
-        // Select temperature based on source
-        let current_temp = match temp_source {
-            TempSource::Liquid => liquid_temp,
-            TempSource::Cpu => cpu_temp,
-        };
+        // Apply schedule and process rules (profile, LCD brightness, fan cap)
+        daemon.apply_rules(&kraken, &settings.profile, Some(settings.brightness));
 
         // === Cooling: Calculate and apply duties ===
-        let pump_duty = if pump_curve.is_empty() {
-            70
-        } else {
-            interpolate_duty(&pump_curve, current_temp)
-        };
-        let _ = kraken.set_pump_speed(pump_duty.max(20));
-
-        let fan_duty = if fan_curve.is_empty() {
-            50
-        } else {
-            interpolate_duty(&fan_curve, current_temp)
+        let Some(tick) = daemon.cooling_tick(&kraken, cycle_count, temp_source) else {
+            daemon.wait(&kraken, settings.interval);
+            continue;
         };
-        let _ = kraken.set_fan_speed(fan_duty);
 
         // === LCD: Generate and upload radial gauge (only in radial mode) ===
-        if let Some(ref mut bm) = bucket_manager {
+        let mut lcd_bucket = None;
+        if let Some(bm) = bucket_manager
+            .as_mut()
+            .filter(|_| !daemon.control.rendering_paused)
+        {
             let bucket_idx = bm.acquire(&kraken);
+            lcd_bucket = Some(bucket_idx);
 
-            let (display_temp, display_label) = match temp_source {
-                TempSource::Liquid => (status.liquid_temp_c, "LIQUID"),
-                TempSource::Cpu => (cpu_temp as f32, "CPU"),
+            let (display_temp, display_label, trend_metric) = match temp_source {
+                TempSource::Cpu if tick.cpu_reading.is_some() => {
+                    (tick.cpu_temp as f32, "CPU", Metric::CpuTemp)
+                }
+                _ => (tick.status.liquid_temp_c, "LIQUID", Metric::LiquidTemp),
             };
 
-            if let Some(img) = stats_image::generate_radial_stats_image(
+            if let Some(img) = stats_image::generate_radial_trend_image(
                 display_temp,
                 display_label,
-                status.pump_rpm,
+                tick.status.pump_rpm,
+                daemon.telemetry.trend(trend_metric),
                 gauge_config.as_ref(),
             ) && img.save(&temp_path).is_ok()
                 && let Ok(image_data) =
                     nzxt_rust_devices::device::bulk::load_image(&temp_path, orientation)
             {
-                let _ = kraken.upload_image_bulk(bucket_idx, &image_data, 0x02);
+                let _ = daemon
+                    .metrics
+                    .time_upload(|| kraken.upload_image_bulk(bucket_idx, &image_data, 0x02));
             }
 
             println!(
-                "[{:4}] {} {:.0}°C | Pump: {:3}% ({} RPM) | Fan: {:3}% | LCD: bucket {}",
+                "[{:4}] {} {:.0}°C | Pump: {:3}% ({} RPM) | Fan: {:3}% | LCD: bucket {}{}",
                 cycle_count,
                 match temp_source {
                     TempSource::Liquid => "💧",
                     TempSource::Cpu => "🔥",
                 },
                 display_temp,
-                pump_duty,
-                status.pump_rpm,
-                fan_duty,
-                bucket_idx
+                tick.pump_duty,
+                tick.status.pump_rpm,
+                tick.fan_duty,
+                bucket_idx,
+                daemon.suffix(&tick)
             );
         } else {
             // Static mode (image/gif): only cooling updates
             let display_temp = match temp_source {
-                TempSource::Liquid => status.liquid_temp_c,
-                TempSource::Cpu => cpu_temp as f32,
+                TempSource::Liquid => tick.status.liquid_temp_c,
+                TempSource::Cpu => tick.cpu_temp as f32,
             };
             println!(
-                "[{:4}] {} {:.0}°C | Pump: {:3}% ({} RPM) | Fan: {:3}%",
+                "[{:4}] {} {:.0}°C | Pump: {:3}% ({} RPM) | Fan: {:3}%{}",
                 cycle_count,
                 match temp_source {
                     TempSource::Liquid => "💧",
                     TempSource::Cpu => "🔥",
                 },
                 display_temp,
-                pump_duty,
-                status.pump_rpm,
-                fan_duty
+                tick.pump_duty,
+                tick.status.pump_rpm,
+                tick.fan_duty,
+                daemon.suffix(&tick)
             );
         }
 
-        std::thread::sleep(Duration::from_secs(interval));
+        daemon.record(&tick, lcd_bucket);
+        daemon.wait(&kraken, settings.interval);
     }
 
     println!("\n✅ Unified monitor stopped after {} cycles.", cycle_count);
@@ -3,6 +3,9 @@
 //! Protocol based on reverse-engineering from liquidctl project:
 //! https://github.com/liquidctl/liquidctl/blob/main/liquidctl/driver/kraken3.py
 
+use serde::{Deserialize, Serialize};
+
+use crate::cooling::curve::Curve;
 use crate::error::{KrakenError, Result};
 
 // =============================================================================
@@ -139,7 +142,8 @@ pub const RESP_SUB_OK: u8 = 0x01;
 // =============================================================================
 
 /// Speed control channel identifiers.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Channel {
     /// Pump channel - minimum 20%, maximum 100%.
     Pump,
@@ -185,6 +189,34 @@ impl Channel {
 
         Ok(duty)
     }
+
+    /// Validate a sparse (temperature, duty) curve for this channel.
+    ///
+    /// Temperatures must lie in 20-59°C without duplicates, and every duty
+    /// must be within the channel limits.
+    pub fn validate_curve(&self, points: &[(u8, u8)]) -> Result<()> {
+        if points.is_empty() {
+            return Err(KrakenError::InvalidProfile(format!(
+                "{} curve cannot be empty",
+                self
+            )));
+        }
+
+        for (i, &(temp, duty)) in points.iter().enumerate() {
+            if !(MIN_CURVE_TEMP..=CRITICAL_TEMPERATURE).contains(&temp) {
+                return Err(KrakenError::InvalidTemperature(temp));
+            }
+            if points[..i].iter().any(|&(t, _)| t == temp) {
+                return Err(KrakenError::InvalidProfile(format!(
+                    "{} curve has more than one point at {}°C",
+                    self, temp
+                )));
+            }
+            self.validate_duty(duty)?;
+        }
+
+        Ok(())
+    }
 }
 
 impl std::fmt::Display for Channel {
@@ -250,57 +282,15 @@ pub fn build_fixed_speed_cmd(channel: Channel, duty: u8) -> Result<[u8; HID_REPO
 
 /// Interpolate a sparse profile into a full 40-point curve.
 ///
+/// Shorthand for a linear [`Curve`]'s device table.
+///
 /// # Arguments
 /// * `profile` - Sparse profile as (temperature, duty) pairs
 ///
 /// # Returns
 /// Full 40-point duty curve for temperatures 20-59°C.
 pub fn interpolate_profile(profile: &[(u8, u8)]) -> Result<[u8; CURVE_POINTS]> {
-    if profile.is_empty() {
-        return Err(KrakenError::InvalidProfile(
-            "Profile cannot be empty".into(),
-        ));
-    }
-
-    // Validate and sort profile by temperature
-    let mut sorted: Vec<(u8, u8)> = profile.to_vec();
-    sorted.sort_by_key(|(temp, _)| *temp);
-
-    // Validate temperature range
-    for (temp, _) in &sorted {
-        if *temp < MIN_CURVE_TEMP || *temp > CRITICAL_TEMPERATURE {
-            return Err(KrakenError::InvalidTemperature(*temp));
-        }
-    }
-
-    let mut duties = [0u8; CURVE_POINTS];
-
-    for (i, temp) in (MIN_CURVE_TEMP..=CRITICAL_TEMPERATURE).enumerate() {
-        // Find surrounding points for interpolation
-        let duty = if let Some(&(_, duty)) = sorted.iter().find(|(t, _)| *t == temp) {
-            // Exact match
-            duty
-        } else {
-            // Interpolate between surrounding points
-            let lower = sorted.iter().rfind(|(t, _)| *t < temp);
-            let upper = sorted.iter().find(|(t, _)| *t > temp);
-
-            match (lower, upper) {
-                (Some(&(t1, d1)), Some(&(t2, d2))) => {
-                    // Linear interpolation
-                    let ratio = (temp - t1) as f32 / (t2 - t1) as f32;
-                    (d1 as f32 + ratio * (d2 as f32 - d1 as f32)).round() as u8
-                }
-                (Some(&(_, duty)), None) => duty, // Use last known value
-                (None, Some(&(_, duty))) => duty, // Use first known value
-                (None, None) => unreachable!(),   // Profile is not empty
-            }
-        };
-
-        duties[i] = duty;
-    }
-
-    Ok(duties)
+    Curve::linear(profile)?.table()
 }
 
 #[cfg(test)]
@@ -326,6 +316,19 @@ mod tests {
         assert!(Channel::Fan.validate_duty(101).is_err());
     }
 
+    #[test]
+    fn test_curve_validation() {
+        assert!(Channel::Pump.validate_curve(&[(20, 30), (59, 100)]).is_ok());
+        assert!(
+            Channel::Pump
+                .validate_curve(&[(20, 10), (59, 100)])
+                .is_err()
+        ); // below pump minimum
+        assert!(Channel::Fan.validate_curve(&[(20, 0), (60, 100)]).is_err()); // above 59°C
+        assert!(Channel::Fan.validate_curve(&[(30, 20), (30, 40)]).is_err()); // duplicate
+        assert!(Channel::Fan.validate_curve(&[]).is_err());
+    }
+
     #[test]
     fn test_fixed_speed_cmd() {
         let cmd = build_fixed_speed_cmd(Channel::Pump, 50).unwrap();
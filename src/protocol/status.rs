@@ -3,9 +3,11 @@
 //! Parses HID response buffers into structured status data.
 //! Offsets verified via raw HID debug analysis.
 
+use serde::{Deserialize, Serialize};
+
 use crate::error::{KrakenError, Result};
 use crate::protocol::commands::{
-    RESP_FIRMWARE, RESP_SPEED_ACK, RESP_STATUS, RESP_STATUS_ALT, RESP_SUB_OK,
+    Channel, RESP_FIRMWARE, RESP_SPEED_ACK, RESP_STATUS, RESP_STATUS_ALT, RESP_SUB_OK,
 };
 
 // =============================================================================
@@ -37,7 +39,7 @@ const INVALID_TEMP_SENTINEL: [u8; 2] = [0xFF, 0xFF];
 // =============================================================================
 
 /// Device status readings.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct DeviceStatus {
     /// Liquid coolant temperature in Celsius.
     pub liquid_temp_c: f32,
@@ -145,10 +147,26 @@ impl DeviceStatus {
             message: format!("Unknown status header: [{:#04x}, {:#04x}]", buf[0], buf[1]),
         })
     }
+
+    /// Speed in RPM of a channel.
+    pub fn rpm(&self, channel: Channel) -> u16 {
+        match channel {
+            Channel::Pump => self.pump_rpm,
+            Channel::Fan => self.fan_rpm,
+        }
+    }
+
+    /// Duty cycle of a channel as reported by the device.
+    pub fn duty(&self, channel: Channel) -> u8 {
+        match channel {
+            Channel::Pump => self.pump_duty,
+            Channel::Fan => self.fan_duty,
+        }
+    }
 }
 
 /// Firmware version.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub struct FirmwareVersion {
     pub major: u8,
     pub minor: u8,
@@ -190,6 +208,28 @@ impl std::fmt::Display for FirmwareVersion {
     }
 }
 
+/// LCD memory bucket, as reported by a bucket query.
+///
+/// Memory is addressed in 1 KB pages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BucketInfo {
+    /// Bucket index (0-15).
+    pub index: u8,
+    /// True if the bucket holds an asset.
+    pub occupied: bool,
+    /// First memory page of the bucket.
+    pub start_page: u16,
+    /// Size of the bucket in pages.
+    pub size_pages: u16,
+}
+
+impl BucketInfo {
+    /// First page after the bucket.
+    pub fn end_page(&self) -> u16 {
+        self.start_page + self.size_pages
+    }
+}
+
 impl std::fmt::Display for DeviceStatus {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "+-----------------------------------+")?;
@@ -270,4 +310,35 @@ mod tests {
         let fw = FirmwareVersion::parse(&buf).unwrap();
         assert_eq!(fw.to_string(), "2.1.5");
     }
+
+    #[test]
+    fn test_serialize() {
+        let status = DeviceStatus {
+            liquid_temp_c: 31.5,
+            pump_rpm: 2100,
+            pump_duty: 70,
+            fan_rpm: 900,
+            fan_duty: 40,
+        };
+        assert_eq!(
+            serde_json::to_value(&status).unwrap(),
+            serde_json::json!({
+                "liquid_temp_c": 31.5,
+                "pump_rpm": 2100,
+                "pump_duty": 70,
+                "fan_rpm": 900,
+                "fan_duty": 40,
+            })
+        );
+
+        let fw = FirmwareVersion {
+            major: 2,
+            minor: 1,
+            patch: 5,
+        };
+        assert_eq!(
+            serde_json::to_value(fw).unwrap(),
+            serde_json::json!({"major": 2, "minor": 1, "patch": 5})
+        );
+    }
 }
@@ -0,0 +1,249 @@
+//! Fan/pump calibration data.
+//!
+//! A calibration is a duty→RPM table measured by the `calibrate` command,
+//! stored per device serial under `<config dir>/calibration/<serial>.json`.
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use super::atomic::write_atomic;
+use super::profiles::get_config_dir;
+use crate::error::{KrakenError, Result};
+use crate::protocol::Channel;
+
+const CALIBRATION_DIR: &str = "calibration";
+
+// =============================================================================
+// Calibration Structures
+// =============================================================================
+
+/// A measured duty/RPM pair.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CalibrationPoint {
+    /// Duty cycle percentage
+    pub duty: u8,
+    /// Settled speed in RPM
+    pub rpm: u16,
+}
+
+/// Calibration results for one channel.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChannelCalibration {
+    /// Duty→RPM table, sorted by duty
+    pub points: Vec<CalibrationPoint>,
+    /// Highest measured duty at which the rotor stood still (None = never stalled)
+    #[serde(default)]
+    pub stall_duty: Option<u8>,
+    /// Lowest duty that starts a stopped rotor (None = never stalled)
+    #[serde(default)]
+    pub start_duty: Option<u8>,
+    /// Highest measured RPM
+    pub max_rpm: u16,
+}
+
+impl ChannelCalibration {
+    /// Build a calibration from sweep measurements.
+    pub fn from_points(mut points: Vec<CalibrationPoint>, start_duty: Option<u8>) -> Self {
+        points.sort_by_key(|p| p.duty);
+        points.dedup_by_key(|p| p.duty);
+
+        let stall_duty = points.iter().filter(|p| p.rpm == 0).map(|p| p.duty).max();
+        let max_rpm = points.iter().map(|p| p.rpm).max().unwrap_or(0);
+
+        Self {
+            points,
+            stall_duty,
+            start_duty,
+            max_rpm,
+        }
+    }
+
+    /// Expected RPM at a duty, linearly interpolated from the table.
+    ///
+    /// Returns `None` if the table is empty.
+    pub fn expected_rpm(&self, duty: u8) -> Option<u16> {
+        let first = self.points.first()?;
+        let last = self.points.last()?;
+
+        if duty <= first.duty {
+            return Some(first.rpm);
+        }
+        if duty >= last.duty {
+            return Some(last.rpm);
+        }
+
+        for window in self.points.windows(2) {
+            let (a, b) = (window[0], window[1]);
+            if duty >= a.duty && duty <= b.duty {
+                let t = (duty - a.duty) as f32 / (b.duty - a.duty) as f32;
+                let rpm = a.rpm as f32 + t * (b.rpm as f32 - a.rpm as f32);
+                return Some(rpm.round() as u16);
+            }
+        }
+
+        Some(last.rpm)
+    }
+
+    /// Lowest duty expected to reach `rpm`, interpolated from the table.
+    ///
+    /// Returns `None` if the table is empty or `rpm` is above the maximum measured.
+    pub fn duty_for_rpm(&self, rpm: u16) -> Option<u8> {
+        let first = self.points.first()?;
+        if rpm <= first.rpm {
+            return Some(first.duty);
+        }
+
+        for window in self.points.windows(2) {
+            let (a, b) = (window[0], window[1]);
+            if rpm > a.rpm && rpm <= b.rpm {
+                let t = (rpm - a.rpm) as f32 / (b.rpm - a.rpm) as f32;
+                let duty = a.duty as f32 + t * (b.duty - a.duty) as f32;
+                return Some(duty.ceil() as u8);
+            }
+        }
+
+        None
+    }
+
+    /// Deviation of a measured RPM from the expected RPM, in percent.
+    ///
+    /// Negative values mean the rotor is slower than calibrated. Returns `None`
+    /// when no RPM is expected at this duty (empty table or stall region).
+    pub fn deviation(&self, duty: u8, rpm: u16) -> Option<f32> {
+        let expected = self.expected_rpm(duty)?;
+        if expected == 0 {
+            return None;
+        }
+        Some((rpm as f32 - expected as f32) / expected as f32 * 100.0)
+    }
+}
+
+/// Calibration of one device.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeviceCalibration {
+    /// Device serial number
+    pub serial: String,
+    /// Firmware version at calibration time
+    #[serde(default)]
+    pub firmware: Option<String>,
+    /// When the calibration was run (RFC 3339)
+    pub calibrated_at: String,
+    /// Pump calibration
+    #[serde(default)]
+    pub pump: Option<ChannelCalibration>,
+    /// Fan calibration
+    #[serde(default)]
+    pub fan: Option<ChannelCalibration>,
+}
+
+impl DeviceCalibration {
+    /// Calibration for a channel, if measured.
+    pub fn channel(&self, channel: Channel) -> Option<&ChannelCalibration> {
+        match channel {
+            Channel::Pump => self.pump.as_ref(),
+            Channel::Fan => self.fan.as_ref(),
+        }
+    }
+}
+
+// =============================================================================
+// Persistence
+// =============================================================================
+
+/// Get the calibration file path for a device serial.
+pub fn get_calibration_path(serial: &str) -> Result<PathBuf> {
+    // Serials are alphanumeric, but keep the file name safe regardless
+    let file_name: String = serial
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+
+    Ok(get_config_dir()?
+        .join(CALIBRATION_DIR)
+        .join(format!("{}.json", file_name)))
+}
+
+/// Load the calibration for a device serial.
+///
+/// Returns `Ok(None)` if the device has not been calibrated.
+pub fn load_calibration(serial: &str) -> Result<Option<DeviceCalibration>> {
+    let path = get_calibration_path(serial)?;
+
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| KrakenError::InvalidProfile(format!("Failed to read calibration: {}", e)))?;
+
+    serde_json::from_str(&content)
+        .map(Some)
+        .map_err(|e| KrakenError::InvalidProfile(format!("Failed to parse calibration: {}", e)))
+}
+
+/// Save a device calibration.
+pub fn save_calibration(calibration: &DeviceCalibration) -> Result<()> {
+    let path = get_calibration_path(&calibration.serial)?;
+
+    let content = serde_json::to_string_pretty(calibration).map_err(|e| {
+        KrakenError::InvalidProfile(format!("Failed to serialize calibration: {}", e))
+    })?;
+
+    write_atomic(&path, &content)
+        .map_err(|e| KrakenError::InvalidProfile(format!("Failed to write calibration: {}", e)))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fan_calibration() -> ChannelCalibration {
+        let points = [(0, 0), (10, 0), (20, 400), (60, 1200), (100, 1800)]
+            .into_iter()
+            .map(|(duty, rpm)| CalibrationPoint { duty, rpm })
+            .collect();
+        ChannelCalibration::from_points(points, Some(16))
+    }
+
+    #[test]
+    fn test_from_points() {
+        let cal = fan_calibration();
+        assert_eq!(cal.stall_duty, Some(10));
+        assert_eq!(cal.start_duty, Some(16));
+        assert_eq!(cal.max_rpm, 1800);
+    }
+
+    #[test]
+    fn test_expected_rpm() {
+        let cal = fan_calibration();
+        assert_eq!(cal.expected_rpm(5), Some(0));
+        assert_eq!(cal.expected_rpm(20), Some(400));
+        assert_eq!(cal.expected_rpm(40), Some(800));
+        assert_eq!(cal.expected_rpm(100), Some(1800));
+    }
+
+    #[test]
+    fn test_duty_for_rpm() {
+        let cal = fan_calibration();
+        assert_eq!(cal.duty_for_rpm(0), Some(0));
+        assert_eq!(cal.duty_for_rpm(400), Some(20));
+        assert_eq!(cal.duty_for_rpm(800), Some(40));
+        assert_eq!(cal.duty_for_rpm(1500), Some(80));
+        assert_eq!(cal.duty_for_rpm(1900), None);
+    }
+
+    #[test]
+    fn test_deviation() {
+        let cal = fan_calibration();
+        assert_eq!(cal.deviation(60, 900), Some(-25.0));
+        assert_eq!(cal.deviation(5, 100), None);
+    }
+}
@@ -0,0 +1,510 @@
+//! Import and export of NZXT CAM `coolingController.json` files.
+//!
+//! The storage types mirror CAM's format, but real CAM exports contain values
+//! this tool cannot use directly (other channels, CPU/GPU temperature sources,
+//! thresholds above the device's 59°C curve limit). Import maps what it can
+//! and reports the rest.
+
+use std::collections::HashSet;
+
+use crate::cooling::validate::{Issue, validate_profile};
+use crate::error::{KrakenError, Result};
+use crate::protocol::{CRITICAL_TEMPERATURE, Channel, MIN_CURVE_TEMP};
+use crate::storage::types::{ChannelSetting, CoolingController, Threshold};
+
+/// Mode types that carry a temperature curve.
+const CURVE_MODES: [&str; 3] = ["Silent", "Performance", "Custom"];
+
+/// Temperature sources CAM may store per channel.
+const TEMPERATURE_OPTIONS: [&str; 3] = ["Liquid", "CPU", "GPU"];
+
+/// Result of a CAM import.
+#[derive(Debug, Clone, Default)]
+pub struct ImportReport {
+    /// Ids of the profiles added
+    pub imported: Vec<String>,
+    /// Ids of existing profiles that were replaced
+    pub replaced: Vec<String>,
+    /// Duplicate ids in the file: (original, new id)
+    pub renamed: Vec<(String, String)>,
+    /// Everything that could not be mapped exactly
+    pub warnings: Vec<String>,
+}
+
+/// Parse a CAM `coolingController.json` and merge its profiles into `defaults`.
+///
+/// Profiles with the same id as an existing profile replace it; duplicate ids
+/// within the file get a numeric suffix that no profile in the file or in
+/// `defaults` uses. The CAM active profile becomes the active profile if it
+/// was imported.
+///
+/// # Errors
+/// Returns `InvalidProfile` if the file is not a CAM cooling controller or
+/// contains no usable profile.
+pub fn import_cam(content: &str, defaults: &mut CoolingController) -> Result<ImportReport> {
+    let cam: CoolingController = serde_json::from_str(content).map_err(|e| {
+        KrakenError::InvalidProfile(format!("Not a CAM coolingController file: {}", e))
+    })?;
+
+    let mut report = ImportReport::default();
+    let mut seen = HashSet::new();
+    let mut active = cam.active_profile_id.clone();
+
+    for mut profile in cam.profiles {
+        if profile.id.trim().is_empty() {
+            report
+                .warnings
+                .push("Skipped a profile without an id".to_string());
+            continue;
+        }
+
+        let label = profile.name.clone().unwrap_or_else(|| profile.id.clone());
+
+        // Duplicate ids within the file; the new id must not replace a profile
+        if !seen.insert(profile.id.to_lowercase()) {
+            let original = profile.id.clone();
+            let taken = |id: &str| {
+                seen.contains(&id.to_lowercase())
+                    || defaults
+                        .profiles
+                        .iter()
+                        .any(|p| p.id.eq_ignore_ascii_case(id))
+            };
+            let mut n = 2;
+            while taken(&format!("{}-{}", original, n)) {
+                n += 1;
+            }
+            profile.id = format!("{}-{}", original, n);
+            seen.insert(profile.id.to_lowercase());
+            report.renamed.push((original, profile.id.clone()));
+        }
+
+        let mut channels = Vec::new();
+        for setting in profile.channel_settings {
+            match map_channel(&label, setting, &mut report.warnings) {
+                Some(setting) => channels.push(setting),
+                None => continue,
+            }
+        }
+
+        if channels.is_empty() {
+            report.warnings.push(format!(
+                "{}: no pump or fan settings could be mapped, skipped",
+                label
+            ));
+            if active.as_deref() == Some(profile.id.as_str()) {
+                active = None;
+            }
+            continue;
+        }
+
+        profile.channel_settings = channels;
+        profile.zero_rpm = None;
+
+        let validation = validate_profile(&profile);
+        if !validation.is_valid() {
+            let errors: Vec<String> = validation.errors().map(Issue::to_string).collect();
+            report.warnings.push(format!(
+                "{}: invalid curve ({}), skipped",
+                label,
+                errors.join("; ")
+            ));
+            if active.as_deref() == Some(profile.id.as_str()) {
+                active = None;
+            }
+            continue;
+        }
+        report.warnings.extend(
+            validation
+                .warnings()
+                .map(|warning| format!("{}: {}", label, warning)),
+        );
+
+        match defaults
+            .profiles
+            .iter_mut()
+            .find(|p| p.id.eq_ignore_ascii_case(&profile.id))
+        {
+            Some(existing) => {
+                report.replaced.push(profile.id.clone());
+                *existing = profile;
+            }
+            None => {
+                report.imported.push(profile.id.clone());
+                defaults.profiles.push(profile);
+            }
+        }
+    }
+
+    if report.imported.is_empty() && report.replaced.is_empty() {
+        return Err(KrakenError::InvalidProfile(format!(
+            "No usable profiles in CAM file{}",
+            if report.warnings.is_empty() {
+                String::new()
+            } else {
+                format!(": {}", report.warnings.join("; "))
+            }
+        )));
+    }
+
+    if let Some(active) = active
+        && defaults.profiles.iter().any(|p| p.id == active)
+    {
+        defaults.active_profile_id = Some(active);
+    }
+
+    Ok(report)
+}
+
+/// Produce a CAM `coolingController.json` from the local profiles.
+///
+/// Settings CAM does not know (zero-RPM mode, schema version) are left out.
+pub fn export_cam(defaults: &CoolingController) -> Result<String> {
+    let mut cam = defaults.clone();
+    cam.schema_version = 0;
+    for profile in &mut cam.profiles {
+        profile.zero_rpm = None;
+        for setting in &mut profile.channel_settings {
+            setting.expression = None;
+            if let Some(mode) = &mut setting.mode {
+                mode.interpolation = None;
+            }
+        }
+    }
+
+    serde_json::to_string_pretty(&cam)
+        .map_err(|e| KrakenError::InvalidProfile(format!("Failed to serialize profiles: {}", e)))
+}
+
+/// Map a CAM channel setting. Returns `None` if it cannot be used.
+fn map_channel(
+    profile: &str,
+    mut setting: ChannelSetting,
+    warnings: &mut Vec<String>,
+) -> Option<ChannelSetting> {
+    let channel = match setting.channel_name.to_lowercase().as_str() {
+        "pump" => Channel::Pump,
+        "fan" => Channel::Fan,
+        other => {
+            warnings.push(format!(
+                "{}: channel '{}' is not supported, skipped",
+                profile, other
+            ));
+            return None;
+        }
+    };
+    setting.channel_name = channel.to_string().to_lowercase();
+    setting.expression = None;
+    let name = format!("{} {}", profile, setting.channel_name);
+
+    let Some(mut mode) = setting.mode.take() else {
+        warnings.push(format!("{}: no mode, skipped", name));
+        return None;
+    };
+    mode.interpolation = None;
+
+    if let Some(option) = &mode.temperature_option {
+        if !TEMPERATURE_OPTIONS
+            .iter()
+            .any(|o| o.eq_ignore_ascii_case(option))
+        {
+            warnings.push(format!(
+                "{}: unknown temperature source '{}', the daemon --source is used instead",
+                name, option
+            ));
+        } else if !option.eq_ignore_ascii_case("Liquid") {
+            warnings.push(format!(
+                "{}: temperature source '{}' kept, but the curve follows the daemon --source",
+                name, option
+            ));
+        }
+    }
+
+    let mode_type = mode.mode_type.clone().unwrap_or_default();
+    let has_curve = mode
+        .custom_thresholds
+        .as_ref()
+        .is_some_and(|t| !t.is_empty());
+
+    if mode_type.eq_ignore_ascii_case("Fixed") || (!has_curve && mode.fixed_percentage.is_some()) {
+        let Some(duty) = mode.fixed_percentage else {
+            warnings.push(format!(
+                "{}: fixed mode without a percentage, skipped",
+                name
+            ));
+            return None;
+        };
+        let clamped = duty.clamp(channel.min_duty(), channel.max_duty());
+        if clamped != duty {
+            warnings.push(format!("{}: fixed {}% clamped to {}%", name, duty, clamped));
+        }
+        if !mode_type.eq_ignore_ascii_case("Fixed") {
+            warnings.push(format!(
+                "{}: unknown mode '{}' treated as Fixed",
+                name, mode_type
+            ));
+        }
+        mode.mode_type = Some("Fixed".into());
+        mode.fixed_percentage = Some(clamped);
+        setting.mode = Some(mode);
+        return Some(setting);
+    }
+
+    if !has_curve {
+        warnings.push(format!(
+            "{}: mode '{}' has no curve or fixed percentage, skipped",
+            name, mode_type
+        ));
+        return None;
+    }
+
+    if !CURVE_MODES
+        .iter()
+        .any(|m| m.eq_ignore_ascii_case(&mode_type))
+    {
+        warnings.push(format!(
+            "{}: unknown mode '{}', using its curve",
+            name, mode_type
+        ));
+    }
+
+    let thresholds = mode.custom_thresholds.take().unwrap_or_default();
+    mode.custom_thresholds = Some(normalize_curve(&name, channel, thresholds, warnings));
+    setting.mode = Some(mode);
+    Some(setting)
+}
+
+/// Clamp a curve to the device range (20-59°C, channel duty limits), merging
+/// duplicate temperatures.
+fn normalize_curve(
+    name: &str,
+    channel: Channel,
+    thresholds: Vec<Threshold>,
+    warnings: &mut Vec<String>,
+) -> Vec<Threshold> {
+    let mut points: Vec<Threshold> = Vec::with_capacity(thresholds.len());
+    let mut clamped_temps = Vec::new();
+    let mut clamped_duties = 0;
+    let mut duplicates = Vec::new();
+
+    for threshold in thresholds {
+        let temperature = threshold
+            .temperature
+            .clamp(MIN_CURVE_TEMP, CRITICAL_TEMPERATURE);
+        if temperature != threshold.temperature {
+            clamped_temps.push(threshold.temperature);
+        }
+
+        let duty = threshold
+            .fan_percentage
+            .clamp(channel.min_duty(), channel.max_duty());
+        if duty != threshold.fan_percentage {
+            clamped_duties += 1;
+        }
+
+        match points.iter_mut().find(|p| p.temperature == temperature) {
+            // Keep the highest duty for merged points (safer)
+            Some(existing) => {
+                if temperature == threshold.temperature {
+                    duplicates.push(temperature);
+                }
+                existing.fan_percentage = existing.fan_percentage.max(duty);
+            }
+            None => points.push(Threshold {
+                temperature,
+                fan_percentage: duty,
+            }),
+        }
+    }
+
+    points.sort_by_key(|p| p.temperature);
+
+    if !clamped_temps.is_empty() {
+        warnings.push(format!(
+            "{}: thresholds at {:?}°C moved into the {}-{}°C device range",
+            name, clamped_temps, MIN_CURVE_TEMP, CRITICAL_TEMPERATURE
+        ));
+    }
+    if clamped_duties > 0 {
+        warnings.push(format!(
+            "{}: {} duty value(s) clamped to {}-{}%",
+            name,
+            clamped_duties,
+            channel.min_duty(),
+            channel.max_duty()
+        ));
+    }
+    if !duplicates.is_empty() {
+        warnings.push(format!(
+            "{}: duplicate thresholds at {:?}°C merged",
+            name, duplicates
+        ));
+    }
+
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CAM_FILE: &str = r#"{
+        "activeProfileId": "b2",
+        "profiles": [
+            {
+                "id": "b2",
+                "originId": "Custom",
+                "name": "Gaming",
+                "channelSettings": [
+                    {
+                        "channelName": "pump",
+                        "mode": {
+                            "modeType": "Custom",
+                            "customThresholds": [
+                                {"temperature": 20, "fanPercentage": 10},
+                                {"temperature": 40, "fanPercentage": 60},
+                                {"temperature": 40, "fanPercentage": 70},
+                                {"temperature": 75, "fanPercentage": 100}
+                            ],
+                            "temperatureOption": "Liquid"
+                        }
+                    },
+                    {
+                        "channelName": "fan",
+                        "mode": {"modeType": "Turbo", "fixedPercentage": 80, "temperatureOption": "CPU"}
+                    },
+                    {
+                        "channelName": "rgb",
+                        "mode": {"modeType": "Fixed", "fixedPercentage": 50}
+                    }
+                ]
+            },
+            {
+                "id": "b2",
+                "name": "Gaming copy",
+                "channelSettings": [
+                    {"channelName": "fan", "mode": {"modeType": "Silent", "customThresholds": [{"temperature": 30, "fanPercentage": 30}]}}
+                ]
+            },
+            {
+                "id": "broken",
+                "channelSettings": [{"channelName": "fan", "mode": {"modeType": "Mystery"}}]
+            }
+        ]
+    }"#;
+
+    fn empty() -> CoolingController {
+        CoolingController {
+            schema_version: 0,
+            active_profile_id: None,
+            profiles: vec![],
+        }
+    }
+
+    #[test]
+    fn test_import_maps_and_reports() {
+        let mut defaults = empty();
+        let report = import_cam(CAM_FILE, &mut defaults).unwrap();
+
+        assert_eq!(report.imported, vec!["b2", "b2-2"]);
+        assert_eq!(report.renamed, vec![("b2".into(), "b2-2".into())]);
+        assert_eq!(defaults.active_profile_id.as_deref(), Some("b2"));
+
+        let gaming = &defaults.profiles[0];
+        assert_eq!(gaming.channel_settings.len(), 2);
+        assert_eq!(gaming.curve("pump"), vec![(20, 20), (40, 70), (59, 100)]);
+
+        let fan = gaming.channel_settings[1].mode.as_ref().unwrap();
+        assert_eq!(fan.mode_type.as_deref(), Some("Fixed"));
+        assert_eq!(fan.fixed_percentage, Some(80));
+
+        let warnings = report.warnings.join("\n");
+        for expected in [
+            "'rgb' is not supported",
+            "unknown mode 'Turbo' treated as Fixed",
+            "temperature source 'CPU'",
+            "[75]°C moved",
+            "duplicate thresholds at [40]°C",
+            "duty value(s) clamped",
+            "broken: no pump or fan settings",
+        ] {
+            assert!(
+                warnings.contains(expected),
+                "missing '{}' in:\n{}",
+                expected,
+                warnings
+            );
+        }
+    }
+
+    #[test]
+    fn test_import_replaces_existing() {
+        let mut defaults = empty();
+        import_cam(CAM_FILE, &mut defaults).unwrap();
+        let report = import_cam(CAM_FILE, &mut defaults).unwrap();
+        assert_eq!(report.replaced, vec!["b2"]);
+        // The duplicate never takes the id of a profile already there
+        assert_eq!(report.renamed, vec![("b2".into(), "b2-3".into())]);
+        assert_eq!(report.imported, vec!["b2-3"]);
+        assert_eq!(defaults.profiles.len(), 3);
+    }
+
+    #[test]
+    fn test_import_rejects_garbage() {
+        assert!(import_cam("{}", &mut empty()).is_err());
+        assert!(import_cam(r#"{"profiles": []}"#, &mut empty()).is_err());
+    }
+
+    #[test]
+    fn test_import_validates_curves() {
+        let file = r#"{
+            "activeProfileId": "falling",
+            "profiles": [
+                {
+                    "id": "falling",
+                    "channelSettings": [{"channelName": "fan", "mode": {
+                        "modeType": "Custom",
+                        "customThresholds": [
+                            {"temperature": 20, "fanPercentage": 80},
+                            {"temperature": 59, "fanPercentage": 40}
+                        ]
+                    }}]
+                },
+                {
+                    "id": "quiet",
+                    "channelSettings": [{"channelName": "fan", "mode": {
+                        "modeType": "Custom",
+                        "customThresholds": [
+                            {"temperature": 20, "fanPercentage": 30},
+                            {"temperature": 59, "fanPercentage": 70}
+                        ]
+                    }}]
+                }
+            ]
+        }"#;
+        let mut defaults = empty();
+        let report = import_cam(file, &mut defaults).unwrap();
+
+        assert_eq!(report.imported, vec!["quiet"]);
+        assert_eq!(defaults.active_profile_id, None);
+        let warnings = report.warnings.join("\n");
+        assert!(warnings.contains("falling: invalid curve (Fan: duty drops from 80%"));
+        assert!(warnings.contains("quiet: Fan: only 70% at the critical temperature"));
+    }
+
+    #[test]
+    fn test_export_round_trip() {
+        let mut defaults = empty();
+        import_cam(CAM_FILE, &mut defaults).unwrap();
+        let exported = export_cam(&defaults).unwrap();
+
+        let mut again = empty();
+        let report = import_cam(&exported, &mut again).unwrap();
+        assert_eq!(report.imported.len(), 2);
+        assert_eq!(
+            again.profiles[0].curve("pump"),
+            defaults.profiles[0].curve("pump")
+        );
+        assert!(exported.contains("\"channelSettings\""));
+    }
+}
@@ -2,11 +2,17 @@
 //!
 //! Acts as the local "database" of profile definitions, matching NZXT CAM's coolingController.json format.
 
-use super::profiles::get_config_dir;
+use super::atomic::write_atomic;
+use super::migrate::{DEFAULTS_SCHEMA_VERSION, backup_file, migrate_defaults};
+use super::profiles::{AppConfig, get_config_dir};
 use crate::config::{
     PROFILE_PERFORMANCE, PROFILE_PUMP_PERFORMANCE, PROFILE_PUMP_SILENT, PROFILE_SILENT,
+    SpeedProfile,
 };
+use crate::cooling::expression::{ChannelExpr, CurveExpr, is_expression, parse_profile_expr};
+use crate::cooling::validate::validate_profile;
 use crate::error::{KrakenError, Result};
+use crate::protocol::Channel;
 use crate::storage::types::{
     ChannelSetting, CoolingController, CoolingMode, CoolingProfile, Threshold,
 };
@@ -32,25 +38,35 @@ pub fn load_defaults() -> Result<CoolingController> {
     let content = std::fs::read_to_string(&path)
         .map_err(|e| KrakenError::InvalidProfile(format!("Failed to read defaults: {}", e)))?;
 
-    serde_json::from_str(&content)
-        .map_err(|e| KrakenError::InvalidProfile(format!("Failed to parse defaults: {}", e)))
+    let mut value: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| KrakenError::InvalidProfile(format!("Failed to parse defaults: {}", e)))?;
+    let migrated_from = migrate_defaults(&mut value)?;
+
+    let controller: CoolingController = serde_json::from_value(value)
+        .map_err(|e| KrakenError::InvalidProfile(format!("Failed to parse defaults: {}", e)))?;
+
+    if let Some(version) = migrated_from {
+        backup_file(&path, version)?;
+        save_defaults(&controller)?;
+    }
+
+    Ok(controller)
 }
 
 /// Save defaults to disk.
+///
+/// The file is replaced atomically and always written with the current
+/// schema version.
 pub fn save_defaults(controller: &CoolingController) -> Result<()> {
     let path = get_defaults_path()?;
-    // Ensure dir exists (should be handled by storage, but good to be safe)
-    if let Some(parent) = path.parent() {
-        std::fs::create_dir_all(parent).ok();
-    }
 
-    let content = serde_json::to_string_pretty(controller)
+    let mut controller = controller.clone();
+    controller.schema_version = DEFAULTS_SCHEMA_VERSION;
+    let content = serde_json::to_string_pretty(&controller)
         .map_err(|e| KrakenError::InvalidProfile(format!("Failed to serialize defaults: {}", e)))?;
 
-    std::fs::write(&path, content)
-        .map_err(|e| KrakenError::InvalidProfile(format!("Failed to write defaults: {}", e)))?;
-
-    Ok(())
+    write_atomic(&path, &content)
+        .map_err(|e| KrakenError::InvalidProfile(format!("Failed to write defaults: {}", e)))
 }
 
 /// Ensure defaults.json exists, creating it with built-in defaults if missing.
@@ -62,6 +78,7 @@ pub fn ensure_defaults_exist() -> Result<()> {
 
     // Create default structure matching NZXT CAM
     let controller = CoolingController {
+        schema_version: DEFAULTS_SCHEMA_VERSION,
         active_profile_id: Some("Silent".into()),
         profiles: vec![
             create_profile("Silent", "Silent", &PROFILE_PUMP_SILENT, &PROFILE_SILENT),
@@ -105,6 +122,48 @@ pub fn get_profile(origin_id: &str) -> Result<CoolingProfile> {
         })
 }
 
+/// Get a profile by name, or build one from a profile expression.
+///
+/// Names containing `(`, `=` or `+` are parsed as expressions (see
+/// [`crate::cooling::expression`]); anything else is looked up with
+/// [`get_profile`].
+///
+/// # Errors
+/// Returns `InvalidProfile` if the profile does not pass
+/// [`validate_profile`]; warnings are left to the caller.
+pub fn resolve_profile(name: &str) -> Result<CoolingProfile> {
+    let profile = if is_expression(name) {
+        expression_profile(name)?
+    } else {
+        get_profile(name)?
+    };
+    validate_profile(&profile).into_result(name)?;
+    Ok(profile)
+}
+
+/// Build an unsaved profile from a profile expression (not validated).
+pub fn expression_profile(name: &str) -> Result<CoolingProfile> {
+    let expr = parse_profile_expr(name)?;
+    let channel_settings = [Channel::Pump, Channel::Fan]
+        .into_iter()
+        .filter_map(|channel| {
+            expr.channel(channel).map(|e| ChannelSetting {
+                channel_name: channel.to_string().to_lowercase(),
+                mode: None,
+                expression: Some(e.to_string()),
+            })
+        })
+        .collect();
+
+    Ok(CoolingProfile {
+        id: name.trim().to_string(),
+        origin_id: None,
+        name: None,
+        channel_settings,
+        zero_rpm: None,
+    })
+}
+
 /// Update fixed values for specific channel in "Fixed" profile.
 pub fn update_fixed(channel_name: &str, duty: u8) -> Result<()> {
     ensure_defaults_exist()?;
@@ -127,6 +186,7 @@ pub fn update_fixed(channel_name: &str, duty: u8) -> Result<()> {
             ))
         })?;
 
+    channel.expression = None;
     if let Some(mode) = &mut channel.mode {
         mode.fixed_percentage = Some(duty);
     } else {
@@ -136,12 +196,350 @@ pub fn update_fixed(channel_name: &str, duty: u8) -> Result<()> {
             fixed_percentage: Some(duty),
             custom_thresholds: Some(vec![]),
             temperature_option: None,
+            interpolation: None,
         });
     }
 
     save_defaults(&defaults)
 }
 
+// =============================================================================
+// Profile Management
+// =============================================================================
+
+/// Load defaults.json, apply `f` and save the result.
+///
+/// Nothing is written if `f` fails.
+pub fn modify_defaults<T>(f: impl FnOnce(&mut CoolingController) -> Result<T>) -> Result<T> {
+    ensure_defaults_exist()?;
+    let mut defaults = load_defaults()?;
+    let value = f(&mut defaults)?;
+    save_defaults(&defaults)?;
+    Ok(value)
+}
+
+/// Find a profile by id or originId (case-insensitive), as [`get_profile`] does.
+pub fn find_profile<'a>(
+    controller: &'a CoolingController,
+    name: &str,
+) -> Option<&'a CoolingProfile> {
+    find_profile_index(controller, name).map(|i| &controller.profiles[i])
+}
+
+fn find_profile_index(controller: &CoolingController, name: &str) -> Option<usize> {
+    controller.profiles.iter().position(|p| {
+        p.id.eq_ignore_ascii_case(name)
+            || p.origin_id
+                .as_deref()
+                .is_some_and(|id| id.eq_ignore_ascii_case(name))
+    })
+}
+
+fn require_profile(controller: &CoolingController, name: &str) -> Result<usize> {
+    find_profile_index(controller, name).ok_or_else(|| {
+        KrakenError::InvalidProfile(format!("Profile '{}' not found in defaults", name))
+    })
+}
+
+/// Check that `id` can be used for a new profile.
+fn check_new_id(controller: &CoolingController, id: &str) -> Result<()> {
+    if id.trim().is_empty() {
+        return Err(KrakenError::InvalidInput(
+            "Profile name cannot be empty".into(),
+        ));
+    }
+    if find_profile_index(controller, id).is_some() {
+        return Err(KrakenError::InvalidProfile(format!(
+            "Profile '{}' already exists",
+            id
+        )));
+    }
+    Ok(())
+}
+
+/// The built-in Fixed profile backs `set-pump`/`set-fan` and cannot be removed.
+fn is_fixed(profile: &CoolingProfile) -> bool {
+    profile.id == "Fixed" || profile.origin_id.as_deref() == Some("Fixed")
+}
+
+/// Set one channel of a profile, validating it against the channel limits.
+fn set_channel_mode(
+    profile: &mut CoolingProfile,
+    channel: Channel,
+    speed: &SpeedProfile,
+) -> Result<()> {
+    let mut expression = None;
+    let mode = match speed {
+        SpeedProfile::Fixed(duty) => fixed_mode(channel.validate_duty(*duty)?),
+        SpeedProfile::Custom(points) => {
+            channel.validate_curve(points)?;
+            let mut sorted = points.clone();
+            sorted.sort_by_key(|&(temp, _)| temp);
+            create_curve_mode("Custom", &sorted)
+        }
+        SpeedProfile::Silent | SpeedProfile::Performance => {
+            let points = builtin_curve(channel, speed);
+            create_curve_mode(speed.name(), points)
+        }
+        // A plain curve keeps its interpolation in the mode
+        SpeedProfile::Expression(
+            expr @ ChannelExpr {
+                curve: CurveExpr::Curve(curve),
+                ..
+            },
+        ) if expr.is_curve_only() => {
+            channel.validate_curve(curve.points())?;
+            CoolingMode {
+                interpolation: Some(curve.interpolation()),
+                ..create_curve_mode("Custom", curve.points())
+            }
+        }
+        SpeedProfile::Expression(expr) => {
+            // CAM only sees the curve points, clamped to the channel limits
+            expression = Some(expr.to_string());
+            let clamp = |duty: u8| duty.clamp(channel.min_duty(), channel.max_duty());
+            match expr.curve {
+                CurveExpr::Fixed(duty) => fixed_mode(clamp(duty)),
+                _ => {
+                    let points: Vec<(u8, u8)> = expr
+                        .points(channel)
+                        .into_iter()
+                        .map(|(temp, duty)| (temp, clamp(duty)))
+                        .collect();
+                    create_curve_mode("Custom", &points)
+                }
+            }
+        }
+    };
+
+    let name = channel.to_string().to_lowercase();
+    match profile
+        .channel_settings
+        .iter_mut()
+        .find(|c| c.channel_name.eq_ignore_ascii_case(&name))
+    {
+        Some(setting) => {
+            setting.mode = Some(mode);
+            setting.expression = expression;
+        }
+        None => profile.channel_settings.push(ChannelSetting {
+            channel_name: name,
+            mode: Some(mode),
+            expression,
+        }),
+    }
+    Ok(())
+}
+
+fn fixed_mode(duty: u8) -> CoolingMode {
+    CoolingMode {
+        mode_type: Some("Fixed".into()),
+        fixed_percentage: Some(duty),
+        custom_thresholds: Some(vec![]),
+        temperature_option: None,
+        interpolation: None,
+    }
+}
+
+fn builtin_curve(channel: Channel, speed: &SpeedProfile) -> &'static [(u8, u8)] {
+    match (channel, speed) {
+        (Channel::Pump, SpeedProfile::Performance) => &PROFILE_PUMP_PERFORMANCE,
+        (Channel::Pump, _) => &PROFILE_PUMP_SILENT,
+        (Channel::Fan, SpeedProfile::Performance) => &PROFILE_PERFORMANCE,
+        (Channel::Fan, _) => &PROFILE_SILENT,
+    }
+}
+
+/// Create a custom profile. Channels not given start from the Silent curves.
+pub fn create_custom_profile(
+    controller: &mut CoolingController,
+    id: &str,
+    channels: &[(Channel, SpeedProfile)],
+) -> Result<()> {
+    check_new_id(controller, id)?;
+
+    let mut profile = create_profile(id, id, &PROFILE_PUMP_SILENT, &PROFILE_SILENT);
+    for (channel, speed) in channels {
+        set_channel_mode(&mut profile, *channel, speed)?;
+    }
+    validate_profile(&profile).into_result(id)?;
+
+    controller.profiles.push(profile);
+    Ok(())
+}
+
+/// Replace the given channels of an existing profile.
+pub fn edit_profile(
+    controller: &mut CoolingController,
+    name: &str,
+    channels: &[(Channel, SpeedProfile)],
+) -> Result<()> {
+    let index = require_profile(controller, name)?;
+
+    // Validate everything before touching the stored profile
+    let mut profile = controller.profiles[index].clone();
+    for (channel, speed) in channels {
+        set_channel_mode(&mut profile, *channel, speed)?;
+    }
+    validate_profile(&profile).into_result(&profile.id)?;
+
+    controller.profiles[index] = profile;
+    Ok(())
+}
+
+/// Copy a profile under a new id.
+pub fn clone_profile(controller: &mut CoolingController, source: &str, id: &str) -> Result<()> {
+    let index = require_profile(controller, source)?;
+    check_new_id(controller, id)?;
+
+    let mut profile = controller.profiles[index].clone();
+    profile.id = id.into();
+    profile.origin_id = Some(id.into());
+    profile.name = Some(id.into());
+
+    controller.profiles.push(profile);
+    Ok(())
+}
+
+/// Delete a profile. The active profile and the Fixed profile cannot be deleted.
+pub fn delete_profile(controller: &mut CoolingController, name: &str) -> Result<CoolingProfile> {
+    let index = require_profile(controller, name)?;
+    let profile = &controller.profiles[index];
+
+    if is_fixed(profile) {
+        return Err(KrakenError::InvalidProfile(
+            "The Fixed profile is used by set-pump/set-fan and cannot be deleted".into(),
+        ));
+    }
+    if controller.active_profile_id.as_deref() == Some(profile.id.as_str()) {
+        return Err(KrakenError::InvalidProfile(format!(
+            "Profile '{}' is active; activate another profile first",
+            profile.id
+        )));
+    }
+
+    Ok(controller.profiles.remove(index))
+}
+
+/// Rename a profile, keeping it active if it was. Returns its previous id.
+///
+/// References in config.json are updated separately with
+/// [`rename_profile_references`].
+pub fn rename_profile(controller: &mut CoolingController, old: &str, new: &str) -> Result<String> {
+    let index = require_profile(controller, old)?;
+    if is_fixed(&controller.profiles[index]) {
+        return Err(KrakenError::InvalidProfile(
+            "The Fixed profile cannot be renamed".into(),
+        ));
+    }
+    // Renaming to a different case of the same name is allowed
+    if find_profile_index(controller, new).is_some_and(|i| i != index) {
+        return Err(KrakenError::InvalidProfile(format!(
+            "Profile '{}' already exists",
+            new
+        )));
+    }
+    if new.trim().is_empty() {
+        return Err(KrakenError::InvalidInput(
+            "Profile name cannot be empty".into(),
+        ));
+    }
+
+    let profile = &mut controller.profiles[index];
+    if controller.active_profile_id.as_deref() == Some(profile.id.as_str()) {
+        controller.active_profile_id = Some(new.into());
+    }
+    // Built-in and locally created profiles use their id as originId; CAM
+    // profiles keep the template they were derived from.
+    if profile.origin_id.as_deref() == Some(profile.id.as_str()) {
+        profile.origin_id = Some(new.into());
+    }
+    let previous = std::mem::replace(&mut profile.id, new.into());
+    profile.name = Some(new.into());
+    Ok(previous)
+}
+
+/// Point the startup profile, schedules and process rules of config.json
+/// that use profile `old` to `new`. Returns how many were changed.
+pub fn rename_profile_references(config: &mut AppConfig, old: &str, new: &str) -> usize {
+    let startup = std::iter::once(&mut config.startup.cooling_profile);
+    let schedules = config
+        .schedules
+        .iter_mut()
+        .filter_map(|rule| rule.cooling_profile.as_mut());
+    let processes = config
+        .process_rules
+        .iter_mut()
+        .map(|rule| &mut rule.profile);
+
+    let mut renamed = 0;
+    for name in startup.chain(schedules).chain(processes) {
+        if name.eq_ignore_ascii_case(old) {
+            *name = new.into();
+            renamed += 1;
+        }
+    }
+    renamed
+}
+
+/// Make a profile the default for `start`. Returns its id.
+pub fn activate_profile(controller: &mut CoolingController, name: &str) -> Result<String> {
+    let index = require_profile(controller, name)?;
+    let id = controller.profiles[index].id.clone();
+    controller.active_profile_id = Some(id.clone());
+    Ok(id)
+}
+
+/// Add profiles migrated from an older config.json.
+///
+/// Ids already in use get a numeric suffix. A profile that is already there
+/// with the same settings, under its id or a suffixed one, is not added
+/// again, so an interrupted migration can run twice. Returns the ids of the
+/// migrated profiles in defaults.json.
+pub fn add_migrated_profiles(
+    controller: &mut CoolingController,
+    profiles: Vec<CoolingProfile>,
+) -> Vec<String> {
+    let is_copy = |existing: &CoolingProfile, profile: &CoolingProfile| {
+        let suffixed = existing
+            .id
+            .get(..profile.id.len())
+            .is_some_and(|base| base.eq_ignore_ascii_case(&profile.id))
+            && existing.id[profile.id.len()..]
+                .strip_prefix('-')
+                .is_some_and(|n| n.parse::<u32>().is_ok());
+        (existing.id.eq_ignore_ascii_case(&profile.id) || suffixed)
+            && existing.channel_settings == profile.channel_settings
+    };
+
+    let mut added = Vec::new();
+    for mut profile in profiles {
+        if let Some(existing) = controller.profiles.iter().find(|p| is_copy(p, &profile)) {
+            added.push(existing.id.clone());
+            continue;
+        }
+        if find_profile_index(controller, &profile.id).is_some() {
+            let base = profile.id.clone();
+            let mut n = 2;
+            while find_profile_index(controller, &format!("{}-{}", base, n)).is_some() {
+                n += 1;
+            }
+            profile.id = format!("{}-{}", base, n);
+            profile.origin_id = Some(profile.id.clone());
+            profile.name = Some(profile.id.clone());
+        }
+        added.push(profile.id.clone());
+        controller.profiles.push(profile);
+    }
+    added
+}
+
+/// Get the id of the active profile, if defaults.json sets one.
+pub fn active_profile_id() -> Result<Option<String>> {
+    ensure_defaults_exist()?;
+    Ok(load_defaults()?.active_profile_id)
+}
+
 // Helper to create profiles
 fn create_profile(
     id: &str,
@@ -153,14 +551,17 @@ fn create_profile(
         id: id.into(),
         origin_id: Some(id.into()),
         name: Some(name.into()),
+        zero_rpm: None,
         channel_settings: vec![
             ChannelSetting {
                 channel_name: "pump".into(),
                 mode: Some(create_curve_mode(id, pump_curve)),
+                expression: None,
             },
             ChannelSetting {
                 channel_name: "fan".into(),
                 mode: Some(create_curve_mode(id, fan_curve)),
+                expression: None,
             },
         ],
     }
@@ -180,6 +581,7 @@ fn create_curve_mode(mode_type: &str, points: &[(u8, u8)]) -> CoolingMode {
                 .collect(),
         ),
         temperature_option: Some("Liquid".into()),
+        interpolation: None,
     }
 }
 
@@ -188,6 +590,7 @@ fn create_fixed_profile() -> CoolingProfile {
         id: "Fixed".into(),
         origin_id: Some("Fixed".into()),
         name: Some("Fixed".into()),
+        zero_rpm: None,
         channel_settings: vec![
             ChannelSetting {
                 channel_name: "pump".into(),
@@ -196,7 +599,9 @@ fn create_fixed_profile() -> CoolingProfile {
                     fixed_percentage: Some(100), // Default safe pump speed
                     custom_thresholds: Some(vec![]),
                     temperature_option: None,
+                    interpolation: None,
                 }),
+                expression: None,
             },
             ChannelSetting {
                 channel_name: "fan".into(),
@@ -205,8 +610,125 @@ fn create_fixed_profile() -> CoolingProfile {
                     fixed_percentage: Some(50), // Default fan speed
                     custom_thresholds: Some(vec![]),
                     temperature_option: None,
+                    interpolation: None,
                 }),
+                expression: None,
             },
         ],
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn controller() -> CoolingController {
+        CoolingController {
+            schema_version: DEFAULTS_SCHEMA_VERSION,
+            active_profile_id: Some("Silent".into()),
+            profiles: vec![
+                create_profile("Silent", "Silent", &PROFILE_PUMP_SILENT, &PROFILE_SILENT),
+                create_fixed_profile(),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_create_and_edit() {
+        let mut c = controller();
+        let curve = SpeedProfile::Custom(vec![(59, 100), (20, 70), (45, 80)]);
+        create_custom_profile(&mut c, "Quiet", &[(Channel::Pump, curve)]).unwrap();
+
+        let quiet = find_profile(&c, "quiet").unwrap();
+        assert_eq!(quiet.curve("pump"), vec![(20, 70), (45, 80), (59, 100)]);
+        assert_eq!(quiet.curve("fan"), PROFILE_SILENT.to_vec());
+
+        // Duplicate names and channel limits are rejected
+        assert!(create_custom_profile(&mut c, "silent", &[]).is_err());
+        let low_pump = SpeedProfile::Custom(vec![(20, 10)]);
+        assert!(edit_profile(&mut c, "Quiet", &[(Channel::Pump, low_pump)]).is_err());
+        assert_eq!(
+            find_profile(&c, "Quiet").unwrap().curve("pump"),
+            vec![(20, 70), (45, 80), (59, 100)]
+        );
+
+        edit_profile(&mut c, "Quiet", &[(Channel::Fan, SpeedProfile::Fixed(40))]).unwrap();
+        let fan = find_profile(&c, "Quiet").unwrap().channel_settings[1].clone();
+        assert_eq!(fan.mode.unwrap().fixed_percentage, Some(40));
+
+        // Non-linear curves keep their interpolation instead of an expression
+        let spline = crate::utils::parsing::parse_speed_profile("spline(20:30,59:100)").unwrap();
+        edit_profile(&mut c, "Quiet", &[(Channel::Fan, spline)]).unwrap();
+        let fan = find_profile(&c, "Quiet").unwrap().channel_settings[1].clone();
+        assert!(fan.expression.is_none());
+        assert_eq!(
+            fan.mode.unwrap().interpolation,
+            Some(crate::cooling::Interpolation::MonotoneCubic)
+        );
+    }
+
+    #[test]
+    fn test_clone_rename_delete_activate() {
+        let mut c = controller();
+        clone_profile(&mut c, "silent", "Night").unwrap();
+        assert!(clone_profile(&mut c, "silent", "night").is_err());
+
+        activate_profile(&mut c, "night").unwrap();
+        assert_eq!(c.active_profile_id.as_deref(), Some("Night"));
+        assert!(delete_profile(&mut c, "Night").is_err());
+
+        assert_eq!(rename_profile(&mut c, "night", "Sleep").unwrap(), "Night");
+        assert_eq!(c.active_profile_id.as_deref(), Some("Sleep"));
+        assert!(find_profile(&c, "Night").is_none());
+
+        delete_profile(&mut c, "Silent").unwrap();
+        assert!(delete_profile(&mut c, "Fixed").is_err());
+        assert!(rename_profile(&mut c, "Fixed", "Other").is_err());
+        assert_eq!(c.profiles.len(), 2);
+    }
+
+    #[test]
+    fn test_rename_profile_references() {
+        let mut config: AppConfig = serde_json::from_str(
+            r#"{
+                "startup": { "cooling_profile": "night" },
+                "schedules": [
+                    { "name": "Evening", "cooling_profile": "Night" },
+                    { "name": "Dim", "lcd_profile": "night" }
+                ],
+                "process_rules": [
+                    { "name": "Render", "process": "blender", "profile": "Night" },
+                    { "name": "Game", "process": "steam", "profile": "Performance" }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(rename_profile_references(&mut config, "Night", "Sleep"), 3);
+        assert_eq!(config.startup.cooling_profile, "Sleep");
+        assert_eq!(
+            config.schedules[0].cooling_profile.as_deref(),
+            Some("Sleep")
+        );
+        assert_eq!(config.schedules[1].lcd_profile.as_deref(), Some("night"));
+        assert_eq!(config.process_rules[0].profile, "Sleep");
+        assert_eq!(config.process_rules[1].profile, "Performance");
+    }
+
+    #[test]
+    fn test_add_migrated_profiles_twice() {
+        let mut c = controller();
+        let mut silent = create_profile("Silent", "Silent", &PROFILE_SILENT, &PROFILE_SILENT);
+        let night = create_profile("Night", "Night", &PROFILE_SILENT, &PROFILE_SILENT);
+        silent.origin_id = None;
+        let migrated = vec![silent, night];
+
+        let ids = add_migrated_profiles(&mut c, migrated.clone());
+        assert_eq!(ids, vec!["Silent-2", "Night"]);
+
+        // A migration interrupted before config.json was rewritten runs again
+        let ids = add_migrated_profiles(&mut c, migrated);
+        assert_eq!(ids, vec!["Silent-2", "Night"]);
+        assert_eq!(c.profiles.len(), 4);
+    }
+}
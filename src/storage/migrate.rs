@@ -0,0 +1,285 @@
+//! Schema versions and migrations for config.json and defaults.json.
+//!
+//! Both files carry a schema version. Files written by older releases (no
+//! version field) are migrated in place on load; the original is kept as a
+//! `.v<N>.bak` copy next to it. Files from a newer release are rejected
+//! rather than silently losing fields.
+//!
+//! Cooling profiles live only in defaults.json. Version 0 of config.json had
+//! its own `profiles` map that the daemons never read; migration moves those
+//! profiles into defaults.json.
+
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::error::{KrakenError, Result};
+use crate::protocol::{CRITICAL_TEMPERATURE, Channel, MIN_CURVE_TEMP};
+use crate::storage::types::{ChannelSetting, CoolingMode, CoolingProfile, Threshold};
+
+/// Current config.json schema version.
+pub const CONFIG_SCHEMA_VERSION: u32 = 1;
+
+/// Current defaults.json schema version.
+pub const DEFAULTS_SCHEMA_VERSION: u32 = 1;
+
+/// Outcome of migrating config.json.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigMigration {
+    /// Schema version the file had before migration
+    pub from_version: u32,
+    /// Cooling profiles moved out of config.json, to be merged into defaults.json
+    pub profiles: Vec<CoolingProfile>,
+}
+
+/// What migrating config.json on disk changed, for the caller to report.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationReport {
+    /// The migrated file
+    pub path: PathBuf,
+    /// Schema version the file had before migration
+    pub from_version: u32,
+    /// Ids in defaults.json of the profiles moved out of config.json
+    pub moved_profiles: Vec<String>,
+}
+
+/// Cooling profile as stored in config.json before schema version 1.
+#[derive(Debug, Deserialize)]
+struct LegacyProfile {
+    pump: Option<LegacyChannel>,
+    fan: Option<LegacyChannel>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LegacyChannel {
+    mode: String,
+    fixed: Option<u8>,
+    #[serde(default)]
+    curve: Vec<(u8, u8)>,
+    temperature_source: Option<String>,
+}
+
+fn schema_version(value: &Value, key: &str, file: &str, current: u32) -> Result<u32> {
+    let version = match value.get(key) {
+        None => 0,
+        Some(v) => v
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or_else(|| KrakenError::InvalidProfile(format!("Invalid {} in {}", key, file)))?,
+    };
+
+    if version > current {
+        return Err(KrakenError::InvalidProfile(format!(
+            "{} has schema version {}, but this build only understands up to {}",
+            file, version, current
+        )));
+    }
+    Ok(version)
+}
+
+/// Migrate a parsed config.json to [`CONFIG_SCHEMA_VERSION`].
+///
+/// Returns `None` if the file is already current.
+///
+/// # Errors
+/// Returns `InvalidProfile` if the file is from a newer version or its legacy
+/// profiles cannot be read.
+pub fn migrate_config(value: &mut Value) -> Result<Option<ConfigMigration>> {
+    let from_version = schema_version(
+        value,
+        "schema_version",
+        "config.json",
+        CONFIG_SCHEMA_VERSION,
+    )?;
+    if from_version == CONFIG_SCHEMA_VERSION {
+        return Ok(None);
+    }
+
+    let Some(object) = value.as_object_mut() else {
+        return Err(KrakenError::InvalidProfile(
+            "config.json is not a JSON object".into(),
+        ));
+    };
+
+    // v0 -> v1: cooling profiles move to defaults.json
+    let mut profiles = Vec::new();
+    if let Some(legacy) = object.remove("profiles") {
+        let legacy: BTreeMap<String, LegacyProfile> =
+            serde_json::from_value(legacy).map_err(|e| {
+                KrakenError::InvalidProfile(format!("Failed to read config.json profiles: {}", e))
+            })?;
+        profiles = legacy
+            .into_iter()
+            .map(|(name, profile)| convert_legacy_profile(&name, profile))
+            .collect();
+    }
+
+    object.insert("schema_version".into(), CONFIG_SCHEMA_VERSION.into());
+    Ok(Some(ConfigMigration {
+        from_version,
+        profiles,
+    }))
+}
+
+/// Migrate a parsed defaults.json to [`DEFAULTS_SCHEMA_VERSION`].
+///
+/// Returns the version the file had, or `None` if it is already current.
+///
+/// # Errors
+/// Returns `InvalidProfile` if the file is from a newer version.
+pub fn migrate_defaults(value: &mut Value) -> Result<Option<u32>> {
+    let from_version = schema_version(
+        value,
+        "schemaVersion",
+        "defaults.json",
+        DEFAULTS_SCHEMA_VERSION,
+    )?;
+    if from_version == DEFAULTS_SCHEMA_VERSION {
+        return Ok(None);
+    }
+
+    let Some(object) = value.as_object_mut() else {
+        return Err(KrakenError::InvalidProfile(
+            "defaults.json is not a JSON object".into(),
+        ));
+    };
+
+    // v0 -> v1: only the version field is new
+    object.insert("schemaVersion".into(), DEFAULTS_SCHEMA_VERSION.into());
+    Ok(Some(from_version))
+}
+
+/// Bring config.json and defaults.json up to the current schema.
+///
+/// Loading either file migrates it; this loads both so that profiles moved
+/// out of config.json show up before defaults.json is used. Returns what
+/// migrating config.json changed, `None` if it was current.
+pub fn migrate_all() -> Result<Option<MigrationReport>> {
+    let (_, report) = super::profiles::load_config_with_report()?;
+    if super::defaults::get_defaults_path()?.exists() {
+        super::defaults::load_defaults()?;
+    }
+    Ok(report)
+}
+
+/// Copy `path` to `<path>.v<version>.bak` before it is rewritten.
+pub fn backup_file(path: &Path, version: u32) -> Result<()> {
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(format!(".v{}.bak", version));
+
+    std::fs::copy(path, &backup)
+        .map(|_| ())
+        .map_err(|e| KrakenError::InvalidProfile(format!("Failed to back up {:?}: {}", path, e)))
+}
+
+fn convert_legacy_profile(name: &str, legacy: LegacyProfile) -> CoolingProfile {
+    let channels = [(Channel::Pump, legacy.pump), (Channel::Fan, legacy.fan)];
+
+    CoolingProfile {
+        id: name.into(),
+        origin_id: Some(name.into()),
+        name: Some(name.into()),
+        zero_rpm: None,
+        channel_settings: channels
+            .into_iter()
+            .filter_map(|(channel, setting)| {
+                setting.map(|s| ChannelSetting {
+                    channel_name: channel.to_string().to_lowercase(),
+                    mode: Some(convert_legacy_channel(channel, s)),
+                    expression: None,
+                })
+            })
+            .collect(),
+    }
+}
+
+/// Convert a legacy channel, clamping it to the device limits.
+fn convert_legacy_channel(channel: Channel, legacy: LegacyChannel) -> CoolingMode {
+    let clamp_duty = |duty: u8| duty.clamp(channel.min_duty(), channel.max_duty());
+
+    if legacy.mode.eq_ignore_ascii_case("fixed") || legacy.curve.is_empty() {
+        return CoolingMode {
+            mode_type: Some("Fixed".into()),
+            fixed_percentage: Some(clamp_duty(legacy.fixed.unwrap_or(channel.max_duty()))),
+            custom_thresholds: Some(vec![]),
+            temperature_option: None,
+            interpolation: None,
+        };
+    }
+
+    let mut thresholds: Vec<Threshold> = Vec::new();
+    for (temp, duty) in legacy.curve {
+        let temperature = temp.clamp(MIN_CURVE_TEMP, CRITICAL_TEMPERATURE);
+        let fan_percentage = clamp_duty(duty);
+        match thresholds.iter_mut().find(|t| t.temperature == temperature) {
+            Some(t) => t.fan_percentage = t.fan_percentage.max(fan_percentage),
+            None => thresholds.push(Threshold {
+                temperature,
+                fan_percentage,
+            }),
+        }
+    }
+    thresholds.sort_by_key(|t| t.temperature);
+
+    CoolingMode {
+        mode_type: Some("Custom".into()),
+        fixed_percentage: None,
+        custom_thresholds: Some(thresholds),
+        temperature_option: legacy.temperature_source.or(Some("Liquid".into())),
+        interpolation: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_migrate_config_moves_profiles() {
+        let mut value = json!({
+            "startup": { "interval": 5 },
+            "profiles": {
+                "gaming": {
+                    "pump": { "mode": "custom", "fixed": null, "curve": [[20, 10], [70, 100]] },
+                    "fan": { "mode": "fixed", "fixed": 40, "curve": [] }
+                }
+            },
+            "lcd": {},
+            "active_profile": null
+        });
+
+        let migration = migrate_config(&mut value).unwrap().unwrap();
+        assert_eq!(migration.from_version, 0);
+        assert_eq!(value["schema_version"], 1);
+        assert!(value.get("profiles").is_none());
+        assert_eq!(value["startup"]["interval"], 5);
+
+        let gaming = &migration.profiles[0];
+        assert_eq!(gaming.id, "gaming");
+        // Clamped to the pump minimum and the 59°C curve end
+        assert_eq!(gaming.curve("pump"), vec![(20, 20), (59, 100)]);
+        let fan = gaming.channel_settings[1].mode.as_ref().unwrap();
+        assert_eq!(fan.fixed_percentage, Some(40));
+
+        // Already current
+        assert!(migrate_config(&mut value).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_migrate_defaults() {
+        let mut value = json!({ "activeProfileId": "Silent", "profiles": [] });
+        assert_eq!(migrate_defaults(&mut value).unwrap(), Some(0));
+        assert_eq!(value["schemaVersion"], 1);
+        assert_eq!(migrate_defaults(&mut value).unwrap(), None);
+    }
+
+    #[test]
+    fn test_newer_schema_rejected() {
+        let mut value = json!({ "schema_version": 99 });
+        assert!(migrate_config(&mut value).is_err());
+        let mut value = json!({ "schemaVersion": 99, "profiles": [] });
+        assert!(migrate_defaults(&mut value).is_err());
+    }
+}
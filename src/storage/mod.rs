@@ -2,12 +2,33 @@
 //!
 //! Handles saving and loading profiles to/from disk.
 //! Includes defaults management and profile persistence.
+//!
+//! defaults.json is the single store for cooling profiles; config.json holds
+//! startup, LCD and daemon settings. Both are versioned and migrated on load.
 
+pub mod atomic;
+pub mod calibration;
+pub mod cam;
 pub mod defaults;
+pub mod migrate;
 pub mod profiles;
 pub mod types;
+pub mod watch;
 
 // Re-export commonly used items
-pub use defaults::{ensure_defaults_exist, get_defaults_path, get_profile, update_fixed};
+pub use atomic::write_atomic;
+pub use calibration::{
+    CalibrationPoint, ChannelCalibration, DeviceCalibration, get_calibration_path,
+    load_calibration, save_calibration,
+};
+pub use cam::{ImportReport, export_cam, import_cam};
+pub use defaults::{
+    activate_profile, active_profile_id, clone_profile, create_custom_profile, delete_profile,
+    edit_profile, ensure_defaults_exist, expression_profile, find_profile, get_defaults_path,
+    get_profile, modify_defaults, rename_profile, rename_profile_references, resolve_profile,
+    update_fixed,
+};
+pub use migrate::{CONFIG_SCHEMA_VERSION, DEFAULTS_SCHEMA_VERSION};
 pub use profiles::*;
 pub use types::*;
+pub use watch::FileWatcher;
@@ -7,6 +7,8 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
 
+use super::atomic::write_atomic;
+use super::migrate::{CONFIG_SCHEMA_VERSION, MigrationReport, backup_file, migrate_config};
 use crate::error::{KrakenError, Result};
 
 // =============================================================================
@@ -25,6 +27,25 @@ pub fn get_config_dir() -> Result<PathBuf> {
         .ok_or_else(|| KrakenError::InvalidProfile("Could not find config directory".into()))
 }
 
+/// Get the data directory path (history logs).
+/// - Linux: ~/.local/share/nzxt-rust/
+/// - Windows: %APPDATA%\nzxt-rust\
+pub fn get_data_dir() -> Result<PathBuf> {
+    dirs::data_dir()
+        .map(|p| p.join(APP_NAME))
+        .ok_or_else(|| KrakenError::InvalidProfile("Could not find data directory".into()))
+}
+
+/// Get the runtime directory path (state shared with the running daemon).
+/// - Linux: $XDG_RUNTIME_DIR/nzxt-rust/
+/// - Elsewhere: the data directory
+pub fn get_runtime_dir() -> Result<PathBuf> {
+    match dirs::runtime_dir() {
+        Some(dir) => Ok(dir.join(APP_NAME)),
+        None => get_data_dir(),
+    }
+}
+
 /// Get the full path to the config file.
 pub fn get_config_path() -> Result<PathBuf> {
     Ok(get_config_dir()?.join(CONFIG_FILE))
@@ -68,6 +89,14 @@ pub struct StartupConfig {
     /// LCD orientation (0, 90, 180, 270)
     #[serde(default)]
     pub orientation: u16,
+
+    /// Hold the pump at this RPM instead of following the curve
+    #[serde(default)]
+    pub pump_rpm: Option<u16>,
+
+    /// Hold the fan at this RPM instead of following the curve
+    #[serde(default)]
+    pub fan_rpm: Option<u16>,
 }
 
 fn default_display_mode() -> String {
@@ -86,6 +115,34 @@ fn default_brightness() -> u8 {
     100
 }
 
+impl StartupConfig {
+    /// Check values the device would reject.
+    ///
+    /// # Errors
+    /// Returns `InvalidInput` for a brightness above 100, an orientation other
+    /// than 0/90/180/270, or a zero interval.
+    pub fn validate(&self) -> Result<()> {
+        if self.brightness > 100 {
+            return Err(KrakenError::InvalidInput(format!(
+                "brightness {} out of range (0-100)",
+                self.brightness
+            )));
+        }
+        if !matches!(self.orientation, 0 | 90 | 180 | 270) {
+            return Err(KrakenError::InvalidInput(format!(
+                "orientation {} must be 0, 90, 180 or 270",
+                self.orientation
+            )));
+        }
+        if self.interval == 0 {
+            return Err(KrakenError::InvalidInput(
+                "interval must be at least 1 second".into(),
+            ));
+        }
+        Ok(())
+    }
+}
+
 impl Default for StartupConfig {
     fn default() -> Self {
         Self {
@@ -97,40 +154,466 @@ impl Default for StartupConfig {
             interval: default_interval(),
             brightness: default_brightness(),
             orientation: 0,
+            pump_rpm: None,
+            fan_rpm: None,
         }
     }
 }
 
 /// Main configuration file structure
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+///
+/// Cooling profiles are stored in defaults.json, not here.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppConfig {
+    /// config.json schema version (see [`super::migrate`])
+    #[serde(default)]
+    pub schema_version: u32,
     /// Startup configuration for the `start` command
     #[serde(default)]
     pub startup: StartupConfig,
-    /// Cooling profiles by name
-    pub profiles: HashMap<String, StoredCoolingProfile>,
     /// LCD profiles by name
+    #[serde(default)]
     pub lcd: HashMap<String, StoredLcdProfile>,
     /// Currently active profile name
     pub active_profile: Option<String>,
+    /// Time-of-day / weekday rules evaluated by the daemons
+    #[serde(default)]
+    pub schedules: Vec<ScheduleRule>,
+    /// Rules that switch the cooling profile while specific processes run
+    #[serde(default)]
+    pub process_rules: Vec<ProcessRule>,
+    /// Load-aware feed-forward for the daemons
+    #[serde(default)]
+    pub load_control: LoadControlConfig,
+    /// Prometheus metrics endpoint of the daemons
+    #[serde(default)]
+    pub metrics: MetricsConfig,
+    /// Per-tick history log of the daemons and `monitor`
+    #[serde(default)]
+    pub history: HistoryConfig,
+    /// MQTT publisher of the daemons (Home Assistant discovery)
+    #[serde(default)]
+    pub mqtt: MqttConfig,
+    /// Control socket of the daemons
+    #[serde(default)]
+    pub control: ControlConfig,
+    /// D-Bus service of the daemons
+    #[serde(default)]
+    pub dbus: DbusConfig,
+    /// Alert rules evaluated by the daemons
+    #[serde(default)]
+    pub alerts: Vec<AlertRule>,
+    /// System sensors read for the CPU / GPU temperatures
+    #[serde(default)]
+    pub sensors: SensorConfig,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            schema_version: CONFIG_SCHEMA_VERSION,
+            startup: StartupConfig::default(),
+            lcd: HashMap::new(),
+            active_profile: None,
+            schedules: Vec::new(),
+            process_rules: Vec::new(),
+            load_control: LoadControlConfig::default(),
+            metrics: MetricsConfig::default(),
+            history: HistoryConfig::default(),
+            mqtt: MqttConfig::default(),
+            control: ControlConfig::default(),
+            dbus: DbusConfig::default(),
+            alerts: Vec::new(),
+            sensors: SensorConfig::default(),
+        }
+    }
+}
+
+/// Load-aware feed-forward settings.
+///
+/// Adds duty when CPU load jumps above its recent baseline, before the
+/// temperature has had time to react.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LoadControlConfig {
+    /// Enable feed-forward in `start` / `cooling-daemon`
+    #[serde(default)]
+    pub enabled: bool,
+    /// Pump duty % added per % of load above the baseline
+    #[serde(default = "default_pump_gain")]
+    pub pump_gain: f32,
+    /// Fan duty % added per % of load above the baseline
+    #[serde(default = "default_fan_gain")]
+    pub fan_gain: f32,
+    /// Seconds for the baseline to catch up with the load (roughly the thermal lag)
+    #[serde(default = "default_time_constant_secs")]
+    pub time_constant_secs: f32,
+    /// Package power (RAPL) counted as 100% load. 0 = CPU utilisation only
+    #[serde(default = "default_tdp_watts")]
+    pub tdp_watts: f32,
+}
+
+fn default_pump_gain() -> f32 {
+    0.3
+}
+
+fn default_fan_gain() -> f32 {
+    0.6
+}
+
+fn default_time_constant_secs() -> f32 {
+    30.0
+}
+
+fn default_tdp_watts() -> f32 {
+    125.0
+}
+
+impl Default for LoadControlConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            pump_gain: default_pump_gain(),
+            fan_gain: default_fan_gain(),
+            time_constant_secs: default_time_constant_secs(),
+            tdp_watts: default_tdp_watts(),
+        }
+    }
+}
+
+/// Prometheus metrics endpoint settings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricsConfig {
+    /// Serve `/metrics` from `start` / `cooling-daemon`
+    #[serde(default)]
+    pub enabled: bool,
+    /// Address to listen on
+    #[serde(default = "default_metrics_listen")]
+    pub listen: String,
+}
+
+fn default_metrics_listen() -> String {
+    "127.0.0.1:9185".into()
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            listen: default_metrics_listen(),
+        }
+    }
+}
+
+/// File format of the history log.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HistoryFormat {
+    #[default]
+    Csv,
+    Jsonl,
+}
+
+/// History log settings.
+///
+/// A new file is started when the day changes (if `daily`) or the current
+/// file reaches `max_file_mb`; only the newest `retain` files are kept.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryConfig {
+    /// Log one record per tick
+    #[serde(default)]
+    pub enabled: bool,
+    /// "csv" or "jsonl"
+    #[serde(default)]
+    pub format: HistoryFormat,
+    /// Directory for the log files (default: `<data dir>/nzxt-rust/history`)
+    #[serde(default)]
+    pub directory: Option<String>,
+    /// Start a new file every day
+    #[serde(default = "default_true")]
+    pub daily: bool,
+    /// Start a new file at this size in MB (0 = no size limit)
+    #[serde(default = "default_max_file_mb")]
+    pub max_file_mb: u64,
+    /// Number of files to keep (0 = keep all)
+    #[serde(default = "default_retain")]
+    pub retain: usize,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_max_file_mb() -> u64 {
+    10
+}
+
+fn default_retain() -> usize {
+    14
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            format: HistoryFormat::default(),
+            directory: None,
+            daily: default_true(),
+            max_file_mb: default_max_file_mb(),
+            retain: default_retain(),
+        }
+    }
 }
 
-/// Stored cooling profile
+/// MQTT publisher settings.
+///
+/// State is published under `<base_topic>/<device>/`; Home Assistant
+/// discovery configs go under `<discovery_prefix>/`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MqttConfig {
+    /// Connect from `start` / `cooling-daemon`
+    #[serde(default)]
+    pub enabled: bool,
+    /// Broker host name or address
+    #[serde(default = "default_mqtt_host")]
+    pub host: String,
+    /// Broker port
+    #[serde(default = "default_mqtt_port")]
+    pub port: u16,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    /// Prefix of the state and command topics
+    #[serde(default = "default_mqtt_base_topic")]
+    pub base_topic: String,
+    /// Home Assistant discovery prefix (empty = no discovery)
+    #[serde(default = "default_mqtt_discovery_prefix")]
+    pub discovery_prefix: String,
+}
+
+fn default_mqtt_host() -> String {
+    "localhost".into()
+}
+
+fn default_mqtt_port() -> u16 {
+    1883
+}
+
+fn default_mqtt_base_topic() -> String {
+    "nzxt-kraken".into()
+}
+
+fn default_mqtt_discovery_prefix() -> String {
+    "homeassistant".into()
+}
+
+impl Default for MqttConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: default_mqtt_host(),
+            port: default_mqtt_port(),
+            username: None,
+            password: None,
+            base_topic: default_mqtt_base_topic(),
+            discovery_prefix: default_mqtt_discovery_prefix(),
+        }
+    }
+}
+
+/// Control socket settings.
+///
+/// The daemons listen on a Unix socket; CLI commands that find it route
+/// through the daemon instead of opening the device a second time.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ControlConfig {
+    /// Listen from `start` / `cooling-daemon`
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Socket path. Missing = `control.sock` in the runtime directory
+    #[serde(default)]
+    pub path: Option<PathBuf>,
+    /// Group (name or id) given access to the socket
+    #[serde(default)]
+    pub group: Option<String>,
+    /// Octal permissions of the socket. Missing = "0660" with a group, else "0600"
+    #[serde(default)]
+    pub mode: Option<String>,
+}
+
+impl Default for ControlConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            path: None,
+            group: None,
+            mode: None,
+        }
+    }
+}
+
+/// D-Bus service settings.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DbusConfig {
+    /// Serve from `start` / `cooling-daemon`
+    #[serde(default)]
+    pub enabled: bool,
+    /// Bus to own the service name on
+    #[serde(default)]
+    pub bus: DbusBus,
+}
+
+/// Message bus of the D-Bus service.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DbusBus {
+    /// The user's session bus
+    #[default]
+    Session,
+    /// The system bus (needs a policy allowing the service name)
+    System,
+}
+
+/// System sensors read as the CPU and GPU temperatures.
+///
+/// A source without a selector uses the first sensor whose label looks like
+/// it (see [`crate::utils::sensors`]).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SensorConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu: Option<SensorSelector>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gpu: Option<SensorSelector>,
+    /// What the daemons do at start when a selected sensor is missing
+    #[serde(default)]
+    pub on_missing: MissingSensor,
+}
+
+/// How a system sensor is selected, e.g. `{"label": "k10temp Tctl"}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SensorSelector {
+    /// Exact sensor label
+    Label(String),
+    /// Regex matched against the labels; the first match wins
+    Regex(String),
+    /// Position in the `sensors` list
+    Index(usize),
+}
+
+impl std::fmt::Display for SensorSelector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SensorSelector::Label(label) => write!(f, "label \"{}\"", label),
+            SensorSelector::Regex(regex) => write!(f, "regex /{}/", regex),
+            SensorSelector::Index(index) => write!(f, "index {}", index),
+        }
+    }
+}
+
+/// Reaction of the daemons to a selected sensor that is missing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MissingSensor {
+    /// Start anyway and print a warning; the source reads as missing
+    #[default]
+    Warn,
+    /// Refuse to start
+    Refuse,
+}
+
+/// Alert rule evaluated by the daemons every tick.
+///
+/// `condition` is one or more clauses joined by `and`, `while` or `&&`, e.g.
+/// `liquid > 45`, `pump_rpm < 1000 while pump_duty > 50`, `fan_stalled` or
+/// `disconnected`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlertRule {
+    pub name: String,
+    pub condition: String,
+    /// Seconds the condition must hold before the alert fires
+    #[serde(default)]
+    pub for_secs: u64,
+    /// Minimum seconds between two notifications that the alert fired
+    #[serde(default = "default_alert_cooldown")]
+    pub cooldown_secs: u64,
+    /// Notify again every N seconds while the alert keeps firing (0 = once)
+    #[serde(default)]
+    pub repeat_secs: u64,
+    /// What to do when the alert fires or resolves
+    #[serde(default)]
+    pub actions: Vec<AlertAction>,
+}
+
+fn default_alert_cooldown() -> u64 {
+    300
+}
+
+/// Action of an alert rule.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum AlertAction {
+    /// Run a shell command with `KRAKEN_*` environment variables
+    Command { command: String },
+    /// Show a desktop notification
+    Notify,
+    /// POST the event as JSON
+    Webhook { url: String },
+}
+
+/// Time-based rule that overrides the cooling and LCD profile while active.
+///
+/// Rules are evaluated in order; for each setting the first active rule that
+/// defines it wins.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct StoredCoolingProfile {
-    pub pump: Option<StoredChannel>,
-    pub fan: Option<StoredChannel>,
+pub struct ScheduleRule {
+    /// Rule name (shown in daemon output)
+    pub name: String,
+    /// Days the rule applies to ("mon".."sun", "weekdays", "weekend"). Empty = every day
+    #[serde(default)]
+    pub days: Vec<String>,
+    /// Start time "HH:MM" (inclusive). Missing = 00:00
+    #[serde(default)]
+    pub start: Option<String>,
+    /// End time "HH:MM" (exclusive). Missing = end of day. May wrap past midnight
+    #[serde(default)]
+    pub end: Option<String>,
+    /// Cooling profile from defaults.json to use while active
+    #[serde(default)]
+    pub cooling_profile: Option<String>,
+    /// LCD preset to use while active: "off", "night", "day", "max"
+    #[serde(default)]
+    pub lcd_profile: Option<String>,
+    /// Upper limit for the fan duty while active (0-100)
+    #[serde(default)]
+    pub max_fan_duty: Option<u8>,
 }
 
-/// Stored channel configuration
+/// Rule that switches to a cooling profile while a matching process is running.
+///
+/// At least one of `process` or `cmdline` must be set; if both are set, both must match.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct StoredChannel {
-    pub mode: String,
-    pub fixed: Option<u8>,
-    pub curve: Vec<(u8, u8)>,
-    /// Temperature source: "Liquid" (default) or "CPU"
-    #[serde(default = "default_temp_source")]
-    pub temperature_source: String,
+pub struct ProcessRule {
+    /// Rule name (shown in daemon output)
+    pub name: String,
+    /// Process name glob, e.g. "blender" or "steam_app_*" (case-insensitive)
+    #[serde(default)]
+    pub process: Option<String>,
+    /// Regular expression matched against the full command line
+    #[serde(default)]
+    pub cmdline: Option<String>,
+    /// Cooling profile from defaults.json to use while matched
+    pub profile: String,
+    /// Higher priority wins when several rules match
+    #[serde(default)]
+    pub priority: i32,
+    /// Seconds a match must persist (or be gone) before switching
+    #[serde(default = "default_debounce_secs")]
+    pub debounce_secs: u64,
+}
+
+fn default_debounce_secs() -> u64 {
+    5
 }
 
 fn default_temp_source() -> String {
@@ -204,36 +687,71 @@ pub struct StoredGradientStop {
 // =============================================================================
 
 /// Load configuration from disk.
+///
+/// Files from older versions are migrated and rewritten (the original is kept
+/// as a backup); cooling profiles found in them move to defaults.json.
 pub fn load_config() -> Result<AppConfig> {
+    Ok(load_config_with_report()?.0)
+}
+
+/// Load configuration from disk, with what migrating it changed.
+///
+/// Moved profiles are merged into defaults.json before config.json is
+/// rewritten, so an interrupted migration loses nothing and is picked up
+/// again on the next load.
+pub fn load_config_with_report() -> Result<(AppConfig, Option<MigrationReport>)> {
     let path = get_config_path()?;
 
     if !path.exists() {
-        return Ok(AppConfig::default());
+        return Ok((AppConfig::default(), None));
     }
 
     let content = std::fs::read_to_string(&path)
         .map_err(|e| KrakenError::InvalidProfile(format!("Failed to read config: {}", e)))?;
 
-    serde_json::from_str(&content)
-        .map_err(|e| KrakenError::InvalidProfile(format!("Failed to parse config: {}", e)))
+    let mut value: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| KrakenError::InvalidProfile(format!("Failed to parse config: {}", e)))?;
+    let migration = migrate_config(&mut value)?;
+
+    let config: AppConfig = serde_json::from_value(value)
+        .map_err(|e| KrakenError::InvalidProfile(format!("Failed to parse config: {}", e)))?;
+
+    let Some(migration) = migration else {
+        return Ok((config, None));
+    };
+
+    backup_file(&path, migration.from_version)?;
+    let mut moved_profiles = Vec::new();
+    if !migration.profiles.is_empty() {
+        moved_profiles = super::defaults::modify_defaults(|defaults| {
+            Ok(super::defaults::add_migrated_profiles(
+                defaults,
+                migration.profiles,
+            ))
+        })?;
+    }
+    save_config(&config)?;
+
+    let report = MigrationReport {
+        path,
+        from_version: migration.from_version,
+        moved_profiles,
+    };
+    Ok((config, Some(report)))
 }
 
 /// Save configuration to disk.
+///
+/// The file is replaced atomically, so an interrupted save leaves the previous
+/// version intact.
 pub fn save_config(config: &AppConfig) -> Result<()> {
-    let dir = get_config_dir()?;
-    let path = dir.join(CONFIG_FILE);
-
-    // Create directory if needed
-    std::fs::create_dir_all(&dir)
-        .map_err(|e| KrakenError::InvalidProfile(format!("Failed to create config dir: {}", e)))?;
+    let path = get_config_path()?;
 
     let content = serde_json::to_string_pretty(config)
         .map_err(|e| KrakenError::InvalidProfile(format!("Failed to serialize config: {}", e)))?;
 
-    std::fs::write(&path, content)
-        .map_err(|e| KrakenError::InvalidProfile(format!("Failed to write config: {}", e)))?;
-
-    Ok(())
+    write_atomic(&path, &content)
+        .map_err(|e| KrakenError::InvalidProfile(format!("Failed to write config: {}", e)))
 }
 
 /// Ensure that the configuration file exists.
@@ -1,12 +1,24 @@
 use serde::{Deserialize, Serialize};
 
+use crate::cooling::curve::{Curve, Interpolation};
+use crate::cooling::expression::{ChannelExpr, CurveExpr, parse_channel_expr};
+use crate::error::Result;
+use crate::protocol::Channel;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CoolingController {
+    /// defaults.json schema version (not part of the CAM format)
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub schema_version: u32,
     pub active_profile_id: Option<String>,
     pub profiles: Vec<CoolingProfile>,
 }
 
+fn is_zero(value: &u32) -> bool {
+    *value == 0
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CoolingProfile {
@@ -14,25 +26,128 @@ pub struct CoolingProfile {
     pub origin_id: Option<String>,
     pub name: Option<String>,
     pub channel_settings: Vec<ChannelSetting>,
+    /// Zero-RPM fan mode (not part of the CAM format)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub zero_rpm: Option<ZeroRpmConfig>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl CoolingProfile {
+    /// Get the (temperature, duty) curve for a channel ("pump" or "fan").
+    ///
+    /// Returns an empty curve if the channel has no custom thresholds.
+    pub fn curve(&self, channel_name: &str) -> Vec<(u8, u8)> {
+        self.channel_settings
+            .iter()
+            .find(|c| c.channel_name.eq_ignore_ascii_case(channel_name))
+            .and_then(|c| c.mode.as_ref())
+            .and_then(|m| m.custom_thresholds.as_ref())
+            .map(|t| {
+                t.iter()
+                    .map(|th| (th.temperature, th.fan_percentage))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Get the control expression for a channel.
+    ///
+    /// A channel `expression` takes precedence; otherwise custom thresholds
+    /// become a curve with the mode's interpolation and a fixed percentage a
+    /// fixed duty. Returns
+    /// `None` if the channel is not set.
+    ///
+    /// # Errors
+    /// Returns `InvalidProfile` if the expression does not parse, or the
+    /// error from [`Channel::validate_curve`] for an invalid stored curve.
+    pub fn channel_expr(&self, channel: Channel) -> Result<Option<ChannelExpr>> {
+        let Some(setting) = self
+            .channel_settings
+            .iter()
+            .find(|c| c.channel_name.eq_ignore_ascii_case(&channel.to_string()))
+        else {
+            return Ok(None);
+        };
+
+        if let Some(expression) = &setting.expression {
+            return Ok(Some(parse_channel_expr(expression)?));
+        }
+
+        let curve = self.curve(&channel.to_string());
+        if !curve.is_empty() {
+            channel.validate_curve(&curve)?;
+            let interpolation = setting
+                .mode
+                .as_ref()
+                .and_then(|m| m.interpolation)
+                .unwrap_or_default();
+            let curve = Curve::new(&curve, interpolation)?;
+            return Ok(Some(ChannelExpr::from_curve(CurveExpr::Curve(curve))));
+        }
+
+        Ok(setting
+            .mode
+            .as_ref()
+            .and_then(|m| m.fixed_percentage)
+            .map(|duty| ChannelExpr::from_curve(CurveExpr::Fixed(duty))))
+    }
+}
+
+/// Zero-RPM fan mode: the fan stops below `stop_temperature` and restarts
+/// with a short kick above `start_temperature`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ZeroRpmConfig {
+    /// Stop the fan at or below this temperature (°C)
+    pub stop_temperature: u8,
+    /// Restart the fan at or above this temperature (°C)
+    pub start_temperature: u8,
+    /// Duty used to restart a stopped fan (%)
+    #[serde(default = "default_kick_duty")]
+    pub kick_duty: u8,
+    /// How long the kick is held (seconds)
+    #[serde(default = "default_kick_secs")]
+    pub kick_secs: u64,
+    /// Minimum time the fan runs once started (seconds)
+    #[serde(default = "default_min_on_secs")]
+    pub min_on_secs: u64,
+}
+
+fn default_kick_duty() -> u8 {
+    60
+}
+
+fn default_kick_secs() -> u64 {
+    3
+}
+
+fn default_min_on_secs() -> u64 {
+    60
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ChannelSetting {
     pub channel_name: String,
     pub mode: Option<CoolingMode>,
+    /// Profile expression that overrides `mode` (not part of the CAM format)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expression: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CoolingMode {
     pub mode_type: Option<String>,
     pub fixed_percentage: Option<u8>,
     pub custom_thresholds: Option<Vec<Threshold>>,
     pub temperature_option: Option<String>,
+    /// How custom thresholds are interpolated, linear if unset (not part of
+    /// the CAM format)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interpolation: Option<Interpolation>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Threshold {
     pub temperature: u8,
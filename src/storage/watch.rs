@@ -0,0 +1,74 @@
+//! Change detection for the configuration files.
+//!
+//! Polls the modification time and size, which works the same on every
+//! platform and also catches atomic saves (a new file renamed over the old).
+
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Reports when a file has changed on disk since the last check.
+#[derive(Debug, Clone)]
+pub struct FileWatcher {
+    path: PathBuf,
+    stamp: Option<(SystemTime, u64)>,
+}
+
+impl FileWatcher {
+    /// Start watching `path`. The current state counts as unchanged.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let stamp = Self::stamp(&path);
+        Self { path, stamp }
+    }
+
+    /// Path being watched.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns true if the file was modified, created or removed since the
+    /// last call.
+    pub fn changed(&mut self) -> bool {
+        let stamp = Self::stamp(&self.path);
+        if stamp == self.stamp {
+            return false;
+        }
+        self.stamp = stamp;
+        true
+    }
+
+    fn stamp(path: &Path) -> Option<(SystemTime, u64)> {
+        let meta = std::fs::metadata(path).ok()?;
+        Some((meta.modified().ok()?, meta.len()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_detects_modification() {
+        let dir = std::env::temp_dir().join(format!("nzxt-watch-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.json");
+        std::fs::write(&path, "{}").unwrap();
+
+        let mut watcher = FileWatcher::new(&path);
+        assert!(!watcher.changed());
+
+        // Same size, newer modification time
+        std::fs::write(&path, "[]").unwrap();
+        let file = std::fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(5))
+            .unwrap();
+        assert!(watcher.changed());
+        assert!(!watcher.changed());
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(watcher.changed());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
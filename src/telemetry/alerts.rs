@@ -0,0 +1,623 @@
+//! Threshold alerts.
+//!
+//! [`AlertEngine`] evaluates the alert rules of `config.json` against each
+//! daemon tick. A rule fires once its condition has held for `for_secs` and
+//! resolves when the condition clears. Notifications are rate limited per
+//! rule by `cooldown_secs`; `repeat_secs` re-notifies while still firing.
+//!
+//! | Clause | Example |
+//! |--------|---------|
+//! | `METRIC OP VALUE` | `liquid > 45`, `pump_rpm < 1000`, `fan_duty >= 80%` |
+//! | `fan_stalled` | fan at 0 RPM while driven |
+//! | `pump_stalled` | pump at 0 RPM |
+//! | `disconnected` | the device status could not be read |
+//!
+//! Metrics are the [`Metric`] names (`liquid`, `cpu`, `gpu`, `pump_rpm`,
+//! `pump_duty`, `fan_rpm`, `fan_duty`); operators are `>`, `>=`, `<`, `<=`,
+//! `==` and `!=`. Clauses are joined by `and`, `while` or `&&`.
+
+use std::fmt;
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Local};
+use regex::Regex;
+use serde_json::json;
+
+use super::stats::{Metric, Sample};
+use crate::error::{KrakenError, Result};
+use crate::storage::{AlertAction, AlertRule};
+
+/// Longest wait for a webhook response.
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+// =============================================================================
+// Conditions
+// =============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Eq,
+    Ne,
+}
+
+impl Op {
+    /// Operators in matching order (two-character ones first).
+    const ALL: [(&'static str, Op); 7] = [
+        (">=", Op::Ge),
+        ("<=", Op::Le),
+        ("==", Op::Eq),
+        ("!=", Op::Ne),
+        (">", Op::Gt),
+        ("<", Op::Lt),
+        ("=", Op::Eq),
+    ];
+
+    fn compare(self, a: f32, b: f32) -> bool {
+        match self {
+            Op::Gt => a > b,
+            Op::Ge => a >= b,
+            Op::Lt => a < b,
+            Op::Le => a <= b,
+            Op::Eq => a == b,
+            Op::Ne => a != b,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Clause {
+    Compare { metric: Metric, op: Op, value: f32 },
+    FanStalled,
+    PumpStalled,
+    Disconnected,
+}
+
+/// A parsed alert condition: all clauses must hold.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Condition {
+    clauses: Vec<Clause>,
+}
+
+impl Condition {
+    /// Parse a condition such as `pump_rpm < 1000 while pump_duty > 50`.
+    ///
+    /// # Errors
+    /// Returns `InvalidInput` for unknown metrics, operators or values.
+    pub fn parse(text: &str) -> Result<Self> {
+        let invalid = |clause: &str, reason: String| {
+            KrakenError::InvalidInput(format!(
+                "Invalid alert condition '{}' at '{}': {}",
+                text, clause, reason
+            ))
+        };
+
+        let separator = Regex::new(r"(?i)\s+(?:and|while)\s+|&&").expect("valid regex");
+        let clauses = separator
+            .split(text.trim())
+            .map(|clause| {
+                let clause = clause.trim();
+                match clause.to_lowercase().as_str() {
+                    "fan_stalled" => return Ok(Clause::FanStalled),
+                    "pump_stalled" => return Ok(Clause::PumpStalled),
+                    "disconnected" => return Ok(Clause::Disconnected),
+                    _ => {}
+                }
+
+                let (pos, symbol, op) = Op::ALL
+                    .iter()
+                    .filter_map(|&(symbol, op)| clause.find(symbol).map(|pos| (pos, symbol, op)))
+                    .min_by_key(|&(pos, symbol, _)| (pos, std::cmp::Reverse(symbol.len())))
+                    .ok_or_else(|| invalid(clause, "expected METRIC OP VALUE".into()))?;
+                let metric = clause[..pos]
+                    .parse::<Metric>()
+                    .map_err(|e| invalid(clause, e.to_string()))?;
+
+                // Units are allowed: "45°C", "50%", "1000 rpm"
+                let value = clause[pos + symbol.len()..].trim().to_lowercase();
+                let value = ["°c", "c", "%", "rpm"]
+                    .iter()
+                    .find_map(|unit| value.strip_suffix(unit))
+                    .unwrap_or(&value)
+                    .trim();
+                let value = value
+                    .parse::<f32>()
+                    .map_err(|_| invalid(clause, format!("'{}' is not a number", value)))?;
+
+                Ok(Clause::Compare { metric, op, value })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self { clauses })
+    }
+
+    /// True if every clause holds for `input`.
+    pub fn matches(&self, input: &AlertInput) -> bool {
+        self.clauses.iter().all(|clause| match clause {
+            Clause::Compare { metric, op, value } => input
+                .sample
+                .and_then(|s| s.value(*metric))
+                .is_some_and(|v| op.compare(v, *value)),
+            Clause::FanStalled => input.fan_stalled,
+            Clause::PumpStalled => input.sample.is_some_and(|s| s.pump_rpm == 0),
+            Clause::Disconnected => input.sample.is_none(),
+        })
+    }
+}
+
+/// What the alerts are evaluated against each tick.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct AlertInput {
+    /// Readings of this tick, `None` if the device could not be read
+    pub sample: Option<Sample>,
+    /// The fan stall detector reports a stall
+    pub fan_stalled: bool,
+}
+
+// =============================================================================
+// Engine
+// =============================================================================
+
+/// Whether an alert started or stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertState {
+    Firing,
+    Resolved,
+}
+
+impl fmt::Display for AlertState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AlertState::Firing => write!(f, "firing"),
+            AlertState::Resolved => write!(f, "resolved"),
+        }
+    }
+}
+
+/// A notification to send.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlertEvent {
+    /// Name of the rule
+    pub name: String,
+    pub condition: String,
+    pub state: AlertState,
+    pub timestamp: DateTime<Local>,
+    /// Readings that triggered the event (`None` while disconnected)
+    pub sample: Option<Sample>,
+    /// Actions of the rule
+    pub actions: Vec<AlertAction>,
+}
+
+#[derive(Debug, Clone)]
+struct Alert {
+    rule: AlertRule,
+    condition: Condition,
+    /// When the condition started to hold
+    since: Option<Instant>,
+    firing: bool,
+    /// The current firing was notified (not suppressed by the cooldown)
+    notified: bool,
+    last_notified: Option<Instant>,
+}
+
+/// Evaluates alert rules and tracks their state.
+#[derive(Debug, Clone, Default)]
+pub struct AlertEngine {
+    alerts: Vec<Alert>,
+}
+
+impl AlertEngine {
+    /// Parse the conditions of `rules`.
+    ///
+    /// # Errors
+    /// Returns `InvalidInput` naming the first rule with an invalid condition.
+    pub fn new(rules: &[AlertRule]) -> Result<Self> {
+        let alerts = rules
+            .iter()
+            .map(|rule| {
+                let condition = Condition::parse(&rule.condition).map_err(|e| {
+                    KrakenError::InvalidInput(format!("Alert '{}': {}", rule.name, e))
+                })?;
+                Ok(Alert {
+                    rule: rule.clone(),
+                    condition,
+                    since: None,
+                    firing: false,
+                    notified: false,
+                    last_notified: None,
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { alerts })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.alerts.is_empty()
+    }
+
+    /// Names of the rules currently firing.
+    pub fn firing(&self) -> impl Iterator<Item = &str> {
+        self.alerts
+            .iter()
+            .filter(|a| a.firing)
+            .map(|a| a.rule.name.as_str())
+    }
+
+    /// Evaluate every rule at `now` and return the notifications to send.
+    pub fn evaluate(&mut self, input: &AlertInput, now: Instant) -> Vec<AlertEvent> {
+        let mut events = Vec::new();
+
+        for alert in &mut self.alerts {
+            let cooled_down = |alert: &Alert, period: u64| {
+                alert
+                    .last_notified
+                    .is_none_or(|t| now.duration_since(t) >= Duration::from_secs(period))
+            };
+
+            let state = if alert.condition.matches(input) {
+                let since = *alert.since.get_or_insert(now);
+                if !alert.firing {
+                    if now.duration_since(since) < Duration::from_secs(alert.rule.for_secs) {
+                        continue;
+                    }
+                    alert.firing = true;
+                    alert.notified = false;
+                }
+                let notify = if alert.notified {
+                    alert.rule.repeat_secs > 0 && cooled_down(alert, alert.rule.repeat_secs)
+                } else {
+                    cooled_down(alert, alert.rule.cooldown_secs)
+                };
+                if !notify {
+                    continue;
+                }
+                alert.notified = true;
+                alert.last_notified = Some(now);
+                AlertState::Firing
+            } else {
+                alert.since = None;
+                let was_notified = alert.firing && alert.notified;
+                alert.firing = false;
+                alert.notified = false;
+                if !was_notified {
+                    continue;
+                }
+                AlertState::Resolved
+            };
+
+            events.push(AlertEvent {
+                name: alert.rule.name.clone(),
+                condition: alert.rule.condition.clone(),
+                state,
+                timestamp: Local::now(),
+                sample: input.sample,
+                actions: alert.rule.actions.clone(),
+            });
+        }
+
+        events
+    }
+}
+
+// =============================================================================
+// Actions
+// =============================================================================
+
+impl AlertEvent {
+    /// JSON body of a webhook.
+    pub fn to_json(&self) -> serde_json::Value {
+        json!({
+            "alert": self.name,
+            "state": self.state.to_string(),
+            "condition": self.condition,
+            "timestamp": self.timestamp.to_rfc3339(),
+            "sample": self.sample,
+        })
+    }
+
+    /// Environment of a command action: `KRAKEN_ALERT`, `KRAKEN_ALERT_STATE`,
+    /// `KRAKEN_ALERT_CONDITION`, `KRAKEN_ALERT_TIME` and the readings
+    /// (`KRAKEN_LIQUID_TEMP`, `KRAKEN_PUMP_RPM`, ...; unset if unknown).
+    pub fn env(&self) -> Vec<(String, String)> {
+        let mut env = vec![
+            ("KRAKEN_ALERT".to_string(), self.name.clone()),
+            ("KRAKEN_ALERT_STATE".to_string(), self.state.to_string()),
+            ("KRAKEN_ALERT_CONDITION".to_string(), self.condition.clone()),
+            ("KRAKEN_ALERT_TIME".to_string(), self.timestamp.to_rfc3339()),
+        ];
+        if let Some(sample) = &self.sample {
+            for (name, metric) in [
+                ("KRAKEN_LIQUID_TEMP", Metric::LiquidTemp),
+                ("KRAKEN_CPU_TEMP", Metric::CpuTemp),
+                ("KRAKEN_GPU_TEMP", Metric::GpuTemp),
+                ("KRAKEN_PUMP_RPM", Metric::PumpRpm),
+                ("KRAKEN_PUMP_DUTY", Metric::PumpDuty),
+                ("KRAKEN_FAN_RPM", Metric::FanRpm),
+                ("KRAKEN_FAN_DUTY", Metric::FanDuty),
+            ] {
+                if let Some(value) = sample.value(metric) {
+                    env.push((name.to_string(), value.to_string()));
+                }
+            }
+        }
+        env
+    }
+
+    /// Run every action of the rule, returning the failures. Blocks until
+    /// commands exit and webhooks respond.
+    pub fn run_actions(&self) -> Vec<KrakenError> {
+        self.actions
+            .iter()
+            .filter_map(|action| self.run_action(action).err())
+            .collect()
+    }
+
+    /// Run one action.
+    pub fn run_action(&self, action: &AlertAction) -> Result<()> {
+        match action {
+            AlertAction::Command { command } => {
+                let mut shell = if cfg!(windows) {
+                    let mut shell = Command::new("cmd");
+                    shell.arg("/C");
+                    shell
+                } else {
+                    let mut shell = Command::new("sh");
+                    shell.arg("-c");
+                    shell
+                };
+                run(shell.arg(command).envs(self.env()), command)
+            }
+            AlertAction::Notify => self.notify(),
+            AlertAction::Webhook { url } => {
+                let agent: ureq::Agent = ureq::Agent::config_builder()
+                    .timeout_global(Some(WEBHOOK_TIMEOUT))
+                    .build()
+                    .into();
+                agent
+                    .post(url)
+                    .content_type("application/json")
+                    .send(self.to_json().to_string())
+                    .map(drop)
+                    .map_err(|e| {
+                        KrakenError::InvalidInput(format!("Webhook {} failed: {}", url, e))
+                    })
+            }
+        }
+    }
+
+    fn notify(&self) -> Result<()> {
+        let title = format!("Kraken alert: {}", self.name);
+        let body = match self.state {
+            AlertState::Firing => format!("Firing: {}", self.condition),
+            AlertState::Resolved => format!("Resolved: {}", self.condition),
+        };
+
+        if cfg!(target_os = "macos") {
+            let script = format!("display notification {:?} with title {:?}", body, title);
+            run(Command::new("osascript").arg("-e").arg(script), "osascript")
+        } else if cfg!(windows) {
+            Err(KrakenError::InvalidInput(
+                "Desktop notifications are not supported on Windows; use a command action".into(),
+            ))
+        } else {
+            let urgency = match self.state {
+                AlertState::Firing => "critical",
+                AlertState::Resolved => "normal",
+            };
+            run(
+                Command::new("notify-send")
+                    .args(["--app-name", "nzxt-kraken", "--urgency", urgency])
+                    .arg(title)
+                    .arg(body),
+                "notify-send",
+            )
+        }
+    }
+}
+
+/// Run a command to completion, failing on a non-zero exit.
+fn run(command: &mut Command, name: &str) -> Result<()> {
+    let status = command
+        .status()
+        .map_err(|e| KrakenError::InvalidInput(format!("Cannot run '{}': {}", name, e)))?;
+    if !status.success() {
+        return Err(KrakenError::InvalidInput(format!(
+            "'{}' exited with {}",
+            name, status
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::status::DeviceStatus;
+
+    fn rule(condition: &str, for_secs: u64, cooldown_secs: u64, repeat_secs: u64) -> AlertRule {
+        AlertRule {
+            name: "test".into(),
+            condition: condition.into(),
+            for_secs,
+            cooldown_secs,
+            repeat_secs,
+            actions: Vec::new(),
+        }
+    }
+
+    fn input(liquid: f32, pump_rpm: u16, pump_duty: u8) -> AlertInput {
+        AlertInput {
+            sample: Some(Sample::new(
+                0,
+                &DeviceStatus {
+                    liquid_temp_c: liquid,
+                    pump_rpm,
+                    pump_duty,
+                    fan_rpm: 800,
+                    fan_duty: 40,
+                },
+            )),
+            fan_stalled: false,
+        }
+    }
+
+    #[test]
+    fn test_conditions() {
+        let dead = Condition::parse("pump_rpm < 1000 while pump_duty > 50%").unwrap();
+        assert!(dead.matches(&input(30.0, 900, 60)));
+        assert!(!dead.matches(&input(30.0, 900, 40)));
+        assert!(!dead.matches(&input(30.0, 1200, 60)));
+        assert!(!dead.matches(&AlertInput::default()));
+
+        let hot = Condition::parse("liquid >= 45°C && cpu > 80").unwrap();
+        assert!(!hot.matches(&input(50.0, 2000, 60)), "cpu is unknown");
+        assert!(
+            Condition::parse("LIQUID>45")
+                .unwrap()
+                .matches(&input(46.0, 0, 0))
+        );
+
+        assert!(
+            Condition::parse("disconnected")
+                .unwrap()
+                .matches(&AlertInput::default())
+        );
+        assert!(
+            Condition::parse("pump_stalled")
+                .unwrap()
+                .matches(&input(30.0, 0, 60))
+        );
+        let stalled = AlertInput {
+            fan_stalled: true,
+            ..input(30.0, 2000, 60)
+        };
+        assert!(Condition::parse("fan_stalled").unwrap().matches(&stalled));
+
+        for invalid in ["liquid", "water > 40", "liquid > hot", "liquid > 40 and"] {
+            assert!(Condition::parse(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_firing_and_resolved() {
+        let mut engine = AlertEngine::new(&[rule("liquid > 45", 30, 0, 0)]).unwrap();
+        let t0 = Instant::now();
+        let at = |secs| t0 + Duration::from_secs(secs);
+        let (hot, cool) = (input(46.0, 2000, 60), input(40.0, 2000, 60));
+
+        // Must hold for 30 s
+        assert!(engine.evaluate(&hot, at(0)).is_empty());
+        assert!(engine.evaluate(&hot, at(29)).is_empty());
+        let events = engine.evaluate(&hot, at(30));
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].state, AlertState::Firing);
+        assert_eq!(engine.firing().collect::<Vec<_>>(), vec!["test"]);
+        assert!(engine.evaluate(&hot, at(60)).is_empty());
+
+        let events = engine.evaluate(&cool, at(61));
+        assert_eq!(events[0].state, AlertState::Resolved);
+        assert!(engine.evaluate(&cool, at(62)).is_empty());
+
+        // A short spike does not fire
+        engine.evaluate(&hot, at(70));
+        engine.evaluate(&cool, at(80));
+        assert!(engine.evaluate(&hot, at(90)).is_empty());
+        assert!(AlertEngine::new(&[rule("water > 1", 0, 0, 0)]).is_err());
+    }
+
+    #[test]
+    fn test_rate_limiting() {
+        let mut engine = AlertEngine::new(&[rule("liquid > 45", 0, 300, 120)]).unwrap();
+        let t0 = Instant::now();
+        let at = |secs| t0 + Duration::from_secs(secs);
+        let (hot, cool) = (input(46.0, 2000, 60), input(40.0, 2000, 60));
+        let states = |events: Vec<AlertEvent>| -> Vec<AlertState> {
+            events.into_iter().map(|e| e.state).collect()
+        };
+
+        assert_eq!(
+            states(engine.evaluate(&hot, at(0))),
+            vec![AlertState::Firing]
+        );
+        // Repeated while firing
+        assert!(engine.evaluate(&hot, at(60)).is_empty());
+        assert_eq!(
+            states(engine.evaluate(&hot, at(120))),
+            vec![AlertState::Firing]
+        );
+        assert_eq!(
+            states(engine.evaluate(&cool, at(130))),
+            vec![AlertState::Resolved]
+        );
+
+        // Flapping within the cooldown is silent, including its resolve...
+        assert!(engine.evaluate(&hot, at(140)).is_empty());
+        assert!(engine.evaluate(&cool, at(150)).is_empty());
+        // ...but an alert still firing when the cooldown ends is notified
+        assert!(engine.evaluate(&hot, at(200)).is_empty());
+        assert_eq!(
+            states(engine.evaluate(&hot, at(420))),
+            vec![AlertState::Firing]
+        );
+    }
+
+    #[test]
+    fn test_actions() {
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", server.server_addr().to_ip().unwrap());
+        let event = AlertEvent {
+            name: "Pump dead".into(),
+            condition: "pump_rpm < 500".into(),
+            state: AlertState::Firing,
+            timestamp: Local::now(),
+            sample: input(35.0, 0, 60).sample,
+            actions: vec![AlertAction::Webhook { url: url.clone() }],
+        };
+
+        let client = {
+            let event = event.clone();
+            std::thread::spawn(move || event.run_actions())
+        };
+        let mut request = server.recv().unwrap();
+        assert_eq!(request.url(), "/hook");
+        let mut body = String::new();
+        request.as_reader().read_to_string(&mut body).unwrap();
+        let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body["alert"], "Pump dead");
+        assert_eq!(body["state"], "firing");
+        assert_eq!(body["sample"]["pump_rpm"], 0);
+        request.respond(tiny_http::Response::empty(200)).unwrap();
+        assert!(client.join().unwrap().is_empty());
+
+        // Error responses are failures
+        let client = {
+            let event = event.clone();
+            std::thread::spawn(move || event.run_actions())
+        };
+        let request = server.recv().unwrap();
+        request.respond(tiny_http::Response::empty(500)).unwrap();
+        assert_eq!(client.join().unwrap().len(), 1);
+
+        let env = event.env();
+        assert!(env.contains(&("KRAKEN_ALERT_STATE".into(), "firing".into())));
+        assert!(env.contains(&("KRAKEN_PUMP_RPM".into(), "0".into())));
+
+        #[cfg(unix)]
+        {
+            let command = AlertAction::Command {
+                command:
+                    "test \"$KRAKEN_ALERT\" = 'Pump dead' && test \"$KRAKEN_LIQUID_TEMP\" = 35"
+                        .into(),
+            };
+            assert!(event.run_action(&command).is_ok());
+            let failing = AlertAction::Command {
+                command: "exit 3".into(),
+            };
+            assert!(event.run_action(&failing).is_err());
+        }
+    }
+}
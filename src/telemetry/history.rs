@@ -0,0 +1,427 @@
+//! History log.
+//!
+//! Appends one [`HistoryRecord`] per tick to CSV or JSONL files in the data
+//! directory. The columns use the names the simulator reads
+//! ([`crate::cooling::simulate::parse_trace`]), so a log can be replayed with
+//! `simulate` directly.
+//!
+//! Files are named `history-YYYYMMDD-HHMMSS.<csv|jsonl>` after the time they
+//! were started. A new file is started when the day changes or the size limit
+//! is reached, and the oldest files beyond the retention count are deleted.
+//! After a restart the newest file is continued if it is still current.
+
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Local, NaiveDate, SecondsFormat};
+
+use crate::error::{KrakenError, Result};
+use crate::protocol::status::DeviceStatus;
+use crate::storage::{HistoryConfig, HistoryFormat, get_data_dir};
+
+const FILE_PREFIX: &str = "history-";
+const FILE_TIME_FORMAT: &str = "%Y%m%d-%H%M%S";
+
+/// CSV header; JSONL records use the same keys.
+const CSV_HEADER: &str = "timestamp,liquid_temp,pump_rpm,pump_duty,fan_rpm,fan_duty,\
+cpu_temp,gpu_temp,cpu_load,profile,commanded_pump_duty,commanded_fan_duty,lcd_bucket";
+
+/// One tick of a daemon or `monitor`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryRecord {
+    pub timestamp: DateTime<Local>,
+    /// Device status as read this tick
+    pub status: DeviceStatus,
+    pub cpu_temp: Option<f32>,
+    pub gpu_temp: Option<f32>,
+    /// CPU load (0-100%)
+    pub cpu_load: Option<f32>,
+    /// Cooling profile in use
+    pub profile: Option<String>,
+    /// Duties sent by the daemon: (pump, fan)
+    pub commanded: Option<(u8, u8)>,
+    /// LCD bucket uploaded this tick
+    pub lcd_bucket: Option<u8>,
+}
+
+impl HistoryRecord {
+    /// Record of a status read now, with everything else unset.
+    pub fn new(status: &DeviceStatus) -> Self {
+        Self {
+            timestamp: Local::now(),
+            status: status.clone(),
+            cpu_temp: None,
+            gpu_temp: None,
+            cpu_load: None,
+            profile: None,
+            commanded: None,
+            lcd_bucket: None,
+        }
+    }
+
+    fn timestamp(&self) -> String {
+        self.timestamp.to_rfc3339_opts(SecondsFormat::Millis, false)
+    }
+
+    /// CSV row matching [`CSV_HEADER`]; missing values are empty.
+    pub fn to_csv(&self) -> String {
+        let temp = |t: Option<f32>| t.map(|t| format!("{:.1}", t)).unwrap_or_default();
+        let duty = |d: Option<u8>| d.map(|d| d.to_string()).unwrap_or_default();
+        [
+            self.timestamp(),
+            format!("{:.1}", self.status.liquid_temp_c),
+            self.status.pump_rpm.to_string(),
+            self.status.pump_duty.to_string(),
+            self.status.fan_rpm.to_string(),
+            self.status.fan_duty.to_string(),
+            temp(self.cpu_temp),
+            temp(self.gpu_temp),
+            temp(self.cpu_load),
+            csv_field(self.profile.as_deref().unwrap_or_default()),
+            duty(self.commanded.map(|(pump, _)| pump)),
+            duty(self.commanded.map(|(_, fan)| fan)),
+            duty(self.lcd_bucket),
+        ]
+        .join(",")
+    }
+
+    /// JSON object with the CSV column names as keys; missing values are null.
+    pub fn to_json(&self) -> String {
+        let round = |t: f32| (t * 10.0).round() / 10.0;
+        serde_json::json!({
+            "timestamp": self.timestamp(),
+            "liquid_temp": round(self.status.liquid_temp_c),
+            "pump_rpm": self.status.pump_rpm,
+            "pump_duty": self.status.pump_duty,
+            "fan_rpm": self.status.fan_rpm,
+            "fan_duty": self.status.fan_duty,
+            "cpu_temp": self.cpu_temp.map(round),
+            "gpu_temp": self.gpu_temp.map(round),
+            "cpu_load": self.cpu_load.map(round),
+            "profile": self.profile,
+            "commanded_pump_duty": self.commanded.map(|(pump, _)| pump),
+            "commanded_fan_duty": self.commanded.map(|(_, fan)| fan),
+            "lcd_bucket": self.lcd_bucket,
+        })
+        .to_string()
+    }
+}
+
+/// Quote a CSV field if it contains a comma or quote.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\"").replace('\n', " "))
+    } else {
+        value.to_string()
+    }
+}
+
+struct OpenFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    day: NaiveDate,
+}
+
+/// Appends records to rotating history files.
+pub struct HistoryLogger {
+    directory: PathBuf,
+    format: HistoryFormat,
+    daily: bool,
+    max_bytes: u64,
+    retain: usize,
+    current: Option<OpenFile>,
+}
+
+impl HistoryLogger {
+    /// Create a logger; the directory is created if needed.
+    ///
+    /// # Errors
+    /// Returns `InvalidInput` if the directory cannot be created.
+    pub fn new(config: &HistoryConfig) -> Result<Self> {
+        let directory = match &config.directory {
+            Some(dir) => PathBuf::from(dir),
+            None => get_data_dir()?.join("history"),
+        };
+        std::fs::create_dir_all(&directory).map_err(|e| {
+            KrakenError::InvalidInput(format!("Cannot create {}: {}", directory.display(), e))
+        })?;
+
+        Ok(Self {
+            directory,
+            format: config.format,
+            daily: config.daily,
+            max_bytes: config.max_file_mb * 1024 * 1024,
+            retain: config.retain,
+            current: None,
+        })
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// The file being written, once a record was logged.
+    pub fn path(&self) -> Option<&Path> {
+        self.current.as_ref().map(|f| f.path.as_path())
+    }
+
+    /// Append a record, rotating first if needed.
+    ///
+    /// # Errors
+    /// Returns `InvalidInput` if the file cannot be opened or written.
+    pub fn log(&mut self, record: &HistoryRecord) -> Result<()> {
+        let now = record.timestamp;
+        if self
+            .current
+            .as_ref()
+            .is_none_or(|f| !self.is_current(f.size, f.day, now))
+        {
+            self.current = None;
+            let path = self
+                .latest_file()
+                .filter(|path| {
+                    let size = std::fs::metadata(path).map_or(0, |m| m.len());
+                    let day = file_day(path).unwrap_or(now.date_naive());
+                    self.is_current(size, day, now)
+                })
+                .unwrap_or_else(|| self.file_path(now));
+            self.current = Some(self.open(path, now)?);
+            self.prune();
+        }
+
+        let line = match self.format {
+            HistoryFormat::Csv => record.to_csv(),
+            HistoryFormat::Jsonl => record.to_json(),
+        };
+        let current = self.current.as_mut().expect("file opened above");
+        writeln!(current.file, "{}", line).map_err(|e| {
+            KrakenError::InvalidInput(format!("Cannot write {}: {}", current.path.display(), e))
+        })?;
+        current.size += line.len() as u64 + 1;
+        Ok(())
+    }
+
+    /// True if a file of this size started on `day` can take more records.
+    fn is_current(&self, size: u64, day: NaiveDate, now: DateTime<Local>) -> bool {
+        let full = self.max_bytes > 0 && size >= self.max_bytes;
+        let old = self.daily && day != now.date_naive();
+        !full && !old
+    }
+
+    fn extension(&self) -> &'static str {
+        match self.format {
+            HistoryFormat::Csv => "csv",
+            HistoryFormat::Jsonl => "jsonl",
+        }
+    }
+
+    /// Path of a new file started at `now`. Names have one-second
+    /// resolution, so a file rotated within the same second gets a `-N`
+    /// sequence suffix.
+    fn file_path(&self, now: DateTime<Local>) -> PathBuf {
+        let stem = format!("{}{}", FILE_PREFIX, now.format(FILE_TIME_FORMAT));
+        let mut path = self
+            .directory
+            .join(format!("{}.{}", stem, self.extension()));
+        let mut sequence = 2;
+        while path.exists() {
+            path = self
+                .directory
+                .join(format!("{}-{}.{}", stem, sequence, self.extension()));
+            sequence += 1;
+        }
+        path
+    }
+
+    /// History files of this format, oldest first.
+    fn files(&self) -> Vec<PathBuf> {
+        let extension = self.extension();
+        let mut files: Vec<PathBuf> = std::fs::read_dir(&self.directory)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.extension().is_some_and(|e| e == extension)
+                    && path
+                        .file_name()
+                        .and_then(|n| n.to_str())
+                        .is_some_and(|n| n.starts_with(FILE_PREFIX))
+            })
+            .collect();
+        // The names sort chronologically, then by sequence suffix
+        files.sort_by_cached_key(|path| file_order(path));
+        files
+    }
+
+    fn latest_file(&self) -> Option<PathBuf> {
+        self.files().pop()
+    }
+
+    fn open(&self, path: PathBuf, now: DateTime<Local>) -> Result<OpenFile> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| {
+                KrakenError::InvalidInput(format!("Cannot open {}: {}", path.display(), e))
+            })?;
+        let mut size = file.metadata().map_or(0, |m| m.len());
+
+        if size == 0 && self.format == HistoryFormat::Csv {
+            writeln!(file, "{}", CSV_HEADER).map_err(|e| {
+                KrakenError::InvalidInput(format!("Cannot write {}: {}", path.display(), e))
+            })?;
+            size = CSV_HEADER.len() as u64 + 1;
+        }
+
+        Ok(OpenFile {
+            day: file_day(&path).unwrap_or(now.date_naive()),
+            path,
+            file,
+            size,
+        })
+    }
+
+    /// Delete the oldest files beyond the retention count.
+    fn prune(&self) {
+        if self.retain == 0 {
+            return;
+        }
+        let files = self.files();
+        let excess = files.len().saturating_sub(self.retain);
+        for path in &files[..excess] {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// Sort key of a history file: its time stamp, then its sequence suffix.
+fn file_order(path: &Path) -> (String, u32) {
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default();
+    let split = (FILE_PREFIX.len() + "YYYYmmdd-HHMMSS".len()).min(stem.len());
+    let (time, suffix) = stem.split_at(split);
+    let sequence = suffix
+        .strip_prefix('-')
+        .and_then(|n| n.parse().ok())
+        .unwrap_or(1);
+    (time.to_string(), sequence)
+}
+
+/// Day a history file was started, from its name.
+fn file_day(path: &Path) -> Option<NaiveDate> {
+    let name = path.file_stem()?.to_str()?.strip_prefix(FILE_PREFIX)?;
+    NaiveDate::parse_from_str(name.get(..8)?, "%Y%m%d").ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cooling::simulate::parse_trace;
+    use chrono::{Duration, TimeZone};
+
+    fn record(time: DateTime<Local>, liquid: f32) -> HistoryRecord {
+        HistoryRecord {
+            cpu_temp: Some(55.0),
+            cpu_load: Some(12.5),
+            profile: Some("max(cpu,liquid)+silent".into()),
+            commanded: Some((70, 40)),
+            timestamp: time,
+            ..HistoryRecord::new(&DeviceStatus {
+                liquid_temp_c: liquid,
+                pump_rpm: 2000,
+                pump_duty: 70,
+                fan_rpm: 800,
+                fan_duty: 40,
+            })
+        }
+    }
+
+    fn logger(dir: &Path, format: HistoryFormat, max_file_mb: u64, retain: usize) -> HistoryLogger {
+        HistoryLogger::new(&HistoryConfig {
+            enabled: true,
+            format,
+            directory: Some(dir.to_string_lossy().into_owned()),
+            daily: true,
+            max_file_mb,
+            retain,
+        })
+        .unwrap()
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("nzxt-history-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_logs_replay_in_simulate() {
+        let start = Local.with_ymd_and_hms(2026, 3, 1, 12, 0, 0).unwrap();
+        for format in [HistoryFormat::Csv, HistoryFormat::Jsonl] {
+            let dir = temp_dir(&format!("{:?}", format));
+            let mut log = logger(&dir, format, 10, 5);
+            log.log(&record(start, 30.0)).unwrap();
+            log.log(&record(start + Duration::seconds(2), 31.0))
+                .unwrap();
+
+            let content = std::fs::read_to_string(log.path().unwrap()).unwrap();
+            let trace = parse_trace(&content, 1.0).unwrap();
+            assert_eq!(trace.len(), 2);
+            assert_eq!(trace[1].time, 2.0);
+            assert_eq!(trace[1].liquid_temp, Some(31.0));
+            assert_eq!(trace[1].cpu_temp, Some(55.0));
+            assert_eq!(trace[1].cpu_load, Some(12.5));
+            let _ = std::fs::remove_dir_all(&dir);
+        }
+    }
+
+    #[test]
+    fn test_rotation_and_retention() {
+        let dir = temp_dir("rotate");
+        let day = Local.with_ymd_and_hms(2026, 3, 1, 23, 59, 58).unwrap();
+
+        // Day change starts a new file; a restart continues the current one
+        let mut log = logger(&dir, HistoryFormat::Csv, 10, 2);
+        log.log(&record(day, 30.0)).unwrap();
+        log.log(&record(day + Duration::seconds(4), 30.0)).unwrap();
+        let mut log = logger(&dir, HistoryFormat::Csv, 10, 2);
+        log.log(&record(day + Duration::seconds(6), 30.0)).unwrap();
+        assert_eq!(log.files().len(), 2);
+        let content = std::fs::read_to_string(log.path().unwrap()).unwrap();
+        assert_eq!(content.lines().count(), 3); // header + 2 records
+
+        // Size limit: every record gets a new file, only 2 are kept
+        let mut log = logger(&dir, HistoryFormat::Csv, 0, 2);
+        log.max_bytes = 1;
+        for i in 0..4 {
+            log.log(&record(day + Duration::seconds(10 + i), 30.0))
+                .unwrap();
+        }
+        let files = log.files();
+        assert_eq!(files.len(), 2);
+        assert!(files[1].ends_with("history-20260302-000011.csv"));
+
+        // Rotations within one second get a sequence suffix
+        let mut log = logger(&dir, HistoryFormat::Jsonl, 0, 0);
+        log.max_bytes = 1;
+        for _ in 0..11 {
+            log.log(&record(day, 30.0)).unwrap();
+        }
+        let files = log.files();
+        assert_eq!(files.len(), 11);
+        assert!(files[0].ends_with("history-20260301-235958.jsonl"));
+        assert!(files[10].ends_with("history-20260301-235958-11.jsonl"));
+        for file in &files {
+            let content = std::fs::read_to_string(file).unwrap();
+            assert_eq!(content.lines().count(), 1);
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,375 @@
+//! Prometheus metrics.
+//!
+//! [`Metrics`] holds the latest values recorded by a daemon loop and renders
+//! them in the Prometheus text format. [`MetricsServer`] serves them on
+//! `GET /metrics` from a background thread. Every series carries the device
+//! `serial` and `firmware` labels.
+//!
+//! | Metric | Type | Labels |
+//! |--------|------|--------|
+//! | `kraken_device_info` | gauge (always 1) | |
+//! | `kraken_liquid_temperature_celsius` | gauge | |
+//! | `kraken_speed_rpm` | gauge | `channel` |
+//! | `kraken_duty_percent` | gauge, reported by the device | `channel` |
+//! | `kraken_commanded_duty_percent` | gauge, sent by the daemon | `channel` |
+//! | `kraken_system_temperature_celsius` | gauge | `sensor` (`cpu`, `gpu`) |
+//! | `kraken_lcd_uploads_total` | counter | |
+//! | `kraken_lcd_upload_failures_total` | counter | |
+//! | `kraken_lcd_upload_duration_seconds` | summary (`_sum`, `_count`) | |
+
+use std::fmt::Write;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use crate::error::{KrakenError, Result};
+use crate::protocol::status::DeviceStatus;
+
+/// Content type of the Prometheus text format.
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Labels identifying the device on every series.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DeviceLabels {
+    pub serial: String,
+    pub firmware: String,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    status: Option<DeviceStatus>,
+    commanded: Option<(u8, u8)>,
+    cpu_temp: Option<f32>,
+    gpu_temp: Option<f32>,
+    uploads: u64,
+    upload_failures: u64,
+    upload_seconds: f64,
+}
+
+/// Latest daemon readings, shared between the daemon loop and the server.
+///
+/// Cloning is cheap; all clones record into the same values.
+#[derive(Debug, Clone)]
+pub struct Metrics {
+    labels: Arc<DeviceLabels>,
+    state: Arc<Mutex<State>>,
+}
+
+impl Metrics {
+    pub fn new(labels: DeviceLabels) -> Self {
+        Self {
+            labels: Arc::new(labels),
+            state: Arc::default(),
+        }
+    }
+
+    /// Record a status reading of the device.
+    pub fn record_status(&self, status: &DeviceStatus) {
+        self.state().status = Some(status.clone());
+    }
+
+    /// Record the duties the daemon sent.
+    pub fn record_duties(&self, pump_duty: u8, fan_duty: u8) {
+        self.state().commanded = Some((pump_duty, fan_duty));
+    }
+
+    /// Record the system temperatures (`None` if a sensor is missing).
+    pub fn record_temperatures(&self, cpu_temp: Option<f32>, gpu_temp: Option<f32>) {
+        let mut state = self.state();
+        state.cpu_temp = cpu_temp;
+        state.gpu_temp = gpu_temp;
+    }
+
+    /// Record one LCD upload.
+    pub fn record_upload(&self, duration: Duration, ok: bool) {
+        let mut state = self.state();
+        state.uploads += 1;
+        state.upload_seconds += duration.as_secs_f64();
+        if !ok {
+            state.upload_failures += 1;
+        }
+    }
+
+    /// Run an LCD upload and record its duration and outcome.
+    pub fn time_upload<T, E>(
+        &self,
+        upload: impl FnOnce() -> std::result::Result<T, E>,
+    ) -> std::result::Result<T, E> {
+        let start = Instant::now();
+        let result = upload();
+        self.record_upload(start.elapsed(), result.is_ok());
+        result
+    }
+
+    /// Render all series in the Prometheus text format.
+    pub fn render(&self) -> String {
+        let state = self.state();
+        let device = format!(
+            "serial=\"{}\",firmware=\"{}\"",
+            escape(&self.labels.serial),
+            escape(&self.labels.firmware)
+        );
+        let mut out = String::new();
+
+        let mut metric = |name: &str, kind: &str, help: &str, samples: &[(&str, String)]| {
+            if samples.is_empty() {
+                return;
+            }
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} {}", name, kind);
+            for (labels, value) in samples {
+                let _ = writeln!(out, "{}{{{}{}}} {}", name, device, labels, value);
+            }
+        };
+
+        metric(
+            "kraken_device_info",
+            "gauge",
+            "Device serial and firmware",
+            &[("", "1".into())],
+        );
+
+        if let Some(status) = &state.status {
+            metric(
+                "kraken_liquid_temperature_celsius",
+                "gauge",
+                "Liquid temperature",
+                &[("", status.liquid_temp_c.to_string())],
+            );
+            metric(
+                "kraken_speed_rpm",
+                "gauge",
+                "Pump and fan speed",
+                &[
+                    (",channel=\"pump\"", status.pump_rpm.to_string()),
+                    (",channel=\"fan\"", status.fan_rpm.to_string()),
+                ],
+            );
+            metric(
+                "kraken_duty_percent",
+                "gauge",
+                "Duty reported by the device",
+                &[
+                    (",channel=\"pump\"", status.pump_duty.to_string()),
+                    (",channel=\"fan\"", status.fan_duty.to_string()),
+                ],
+            );
+        }
+
+        if let Some((pump, fan)) = state.commanded {
+            metric(
+                "kraken_commanded_duty_percent",
+                "gauge",
+                "Duty last sent by the daemon",
+                &[
+                    (",channel=\"pump\"", pump.to_string()),
+                    (",channel=\"fan\"", fan.to_string()),
+                ],
+            );
+        }
+
+        let temps: Vec<(&str, String)> = [
+            (",sensor=\"cpu\"", state.cpu_temp),
+            (",sensor=\"gpu\"", state.gpu_temp),
+        ]
+        .into_iter()
+        .filter_map(|(labels, temp)| temp.map(|t| (labels, t.to_string())))
+        .collect();
+        metric(
+            "kraken_system_temperature_celsius",
+            "gauge",
+            "CPU and GPU temperature from the system sensors",
+            &temps,
+        );
+
+        metric(
+            "kraken_lcd_uploads_total",
+            "counter",
+            "LCD image uploads",
+            &[("", state.uploads.to_string())],
+        );
+        metric(
+            "kraken_lcd_upload_failures_total",
+            "counter",
+            "Failed LCD image uploads",
+            &[("", state.upload_failures.to_string())],
+        );
+        let _ = writeln!(
+            out,
+            "# HELP kraken_lcd_upload_duration_seconds Time spent uploading LCD images"
+        );
+        let _ = writeln!(out, "# TYPE kraken_lcd_upload_duration_seconds summary");
+        let _ = writeln!(
+            out,
+            "kraken_lcd_upload_duration_seconds_sum{{{}}} {}",
+            device, state.upload_seconds
+        );
+        let _ = writeln!(
+            out,
+            "kraken_lcd_upload_duration_seconds_count{{{}}} {}",
+            device, state.uploads
+        );
+
+        out
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        // A panic while recording leaves plain numbers behind; keep serving them
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Escape a label value (backslash, quote and newline).
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+// =============================================================================
+// HTTP Server
+// =============================================================================
+
+/// Serves `GET /metrics` until dropped.
+pub struct MetricsServer {
+    server: Arc<tiny_http::Server>,
+    addr: SocketAddr,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl MetricsServer {
+    /// Listen on `addr` (e.g. "127.0.0.1:9185") and serve `metrics`.
+    ///
+    /// # Errors
+    /// Returns `InvalidInput` if the address cannot be bound.
+    pub fn start(addr: &str, metrics: Metrics) -> Result<Self> {
+        let server = tiny_http::Server::http(addr).map_err(|e| {
+            KrakenError::InvalidInput(format!("Cannot serve metrics on {}: {}", addr, e))
+        })?;
+        let server = Arc::new(server);
+        let addr = server.server_addr().to_ip().ok_or_else(|| {
+            KrakenError::InvalidInput(format!("Metrics address {} is not an IP address", addr))
+        })?;
+
+        let thread = {
+            let server = server.clone();
+            std::thread::spawn(move || {
+                for request in server.incoming_requests() {
+                    let response = match (request.method(), request.url()) {
+                        (tiny_http::Method::Get, "/metrics") => {
+                            tiny_http::Response::from_string(metrics.render()).with_header(
+                                tiny_http::Header::from_bytes("Content-Type", CONTENT_TYPE)
+                                    .expect("static header is valid"),
+                            )
+                        }
+                        _ => tiny_http::Response::from_string("Not found\n").with_status_code(404),
+                    };
+                    let _ = request.respond(response);
+                }
+            })
+        };
+
+        Ok(Self {
+            server,
+            addr,
+            thread: Some(thread),
+        })
+    }
+
+    /// The address actually bound (useful with port 0).
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+}
+
+impl Drop for MetricsServer {
+    fn drop(&mut self) {
+        self.server.unblock();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write as _};
+
+    fn metrics() -> Metrics {
+        Metrics::new(DeviceLabels {
+            serial: "ABC\"1".into(),
+            firmware: "2.0.1".into(),
+        })
+    }
+
+    #[test]
+    fn test_render() {
+        let metrics = metrics();
+        assert!(!metrics.render().contains("kraken_speed_rpm"));
+
+        metrics.record_status(&DeviceStatus {
+            liquid_temp_c: 31.5,
+            pump_rpm: 2100,
+            pump_duty: 70,
+            fan_rpm: 900,
+            fan_duty: 40,
+        });
+        metrics.record_duties(70, 45);
+        metrics.record_temperatures(Some(55.0), None);
+        metrics.record_upload(Duration::from_millis(250), true);
+        let _ = metrics.time_upload(|| -> Result<()> { Err(KrakenError::Timeout) });
+
+        let text = metrics.render();
+        let device = r#"serial="ABC\"1",firmware="2.0.1""#;
+        for line in [
+            format!("kraken_device_info{{{}}} 1", device),
+            format!("kraken_liquid_temperature_celsius{{{}}} 31.5", device),
+            format!("kraken_speed_rpm{{{},channel=\"fan\"}} 900", device),
+            format!("kraken_duty_percent{{{},channel=\"pump\"}} 70", device),
+            format!(
+                "kraken_commanded_duty_percent{{{},channel=\"fan\"}} 45",
+                device
+            ),
+            format!(
+                "kraken_system_temperature_celsius{{{},sensor=\"cpu\"}} 55",
+                device
+            ),
+            format!("kraken_lcd_uploads_total{{{}}} 2", device),
+            format!("kraken_lcd_upload_failures_total{{{}}} 1", device),
+            format!("kraken_lcd_upload_duration_seconds_count{{{}}} 2", device),
+        ] {
+            assert!(text.contains(&line), "missing '{}' in:\n{}", line, text);
+        }
+        assert!(!text.contains("sensor=\"gpu\""));
+        assert!(text.contains("# TYPE kraken_lcd_uploads_total counter"));
+    }
+
+    #[test]
+    fn test_server() {
+        let metrics = metrics();
+        metrics.record_duties(60, 30);
+        let server = MetricsServer::start("127.0.0.1:0", metrics).unwrap();
+
+        let get = |path: &str| {
+            let mut stream = std::net::TcpStream::connect(server.addr()).unwrap();
+            write!(
+                stream,
+                "GET {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n",
+                path
+            )
+            .unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        };
+
+        let response = get("/metrics");
+        assert!(response.starts_with("HTTP/1.1 200"));
+        assert!(response.contains("text/plain; version=0.0.4"));
+        assert!(response.contains("kraken_commanded_duty_percent"));
+        assert!(get("/").starts_with("HTTP/1.1 404"));
+    }
+}
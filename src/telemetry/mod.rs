@@ -0,0 +1,17 @@
+//! Telemetry export for the daemons.
+//!
+//! The daemon loops record what they read and command each tick; this module
+//! keeps recent samples for statistics and makes them available outside the
+//! process.
+
+pub mod alerts;
+pub mod history;
+pub mod metrics;
+pub mod mqtt;
+pub mod stats;
+
+pub use alerts::{AlertEngine, AlertEvent, AlertInput, AlertState};
+pub use history::{HistoryLogger, HistoryRecord};
+pub use metrics::{DeviceLabels, Metrics, MetricsServer};
+pub use mqtt::{MqttBridge, MqttCommand, MqttEvent, MqttState};
+pub use stats::{Metric, Sample, Stats, StatsReport, TelemetryHistory, TimeAbove};
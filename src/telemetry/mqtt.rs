@@ -0,0 +1,595 @@
+//! MQTT publisher with Home Assistant discovery.
+//!
+//! [`MqttBridge`] publishes the daemon state to `<base>/<node>/state` and
+//! announces the cooler to Home Assistant under the discovery prefix:
+//!
+//! | Entity | Component | Value |
+//! |--------|-----------|-------|
+//! | Liquid, CPU and GPU temperature | `sensor` | °C |
+//! | Pump and fan speed | `sensor` | RPM |
+//! | Pump and fan duty | `sensor` | % reported by the device |
+//! | Pump and fan duty setpoint | `number` | % sent by the daemon, settable |
+//! | LCD brightness | `number` | %, settable |
+//! | Cooling profile | `select` | stored profiles, settable |
+//!
+//! Commands arrive on `<base>/<node>/<entity>/set` and are returned by
+//! [`MqttBridge::poll`]; applying them is up to the daemon loop.
+//! `<base>/<node>/availability` is `online` while connected and `offline`
+//! after the daemon stops (also as the last will).
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Receiver, channel};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use rumqttc::{Client, Event, LastWill, MqttOptions, Outgoing, Packet, QoS};
+use serde::Serialize;
+use serde_json::{Value, json};
+
+use super::history::HistoryRecord;
+use super::metrics::DeviceLabels;
+use crate::error::{KrakenError, Result};
+use crate::protocol::status::DeviceStatus;
+use crate::storage::MqttConfig;
+
+/// Delay before reconnecting after a connection error.
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Longest wait for the `offline` message to go out when dropped.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Requests queued for the connection thread.
+const REQUEST_CAPACITY: usize = 64;
+
+/// Settable entities and their command topic suffix.
+const PUMP_DUTY: &str = "pump_duty";
+const FAN_DUTY: &str = "fan_duty";
+const BRIGHTNESS: &str = "brightness";
+const PROFILE: &str = "profile";
+
+/// A command received from Home Assistant (or any MQTT client).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MqttCommand {
+    /// Hold the pump at this duty
+    PumpDuty(u8),
+    /// Hold the fan at this duty
+    FanDuty(u8),
+    /// Switch to this cooling profile
+    Profile(String),
+    /// Set the LCD brightness
+    Brightness(u8),
+}
+
+/// Something that happened on the connection since the last poll.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MqttEvent {
+    Connected,
+    /// The connection failed or dropped; it is retried
+    Disconnected(String),
+    Command(MqttCommand),
+}
+
+/// Payload of the state topic.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MqttState {
+    #[serde(flatten)]
+    pub status: DeviceStatus,
+    pub cpu_temp: Option<f32>,
+    pub gpu_temp: Option<f32>,
+    pub commanded_pump_duty: Option<u8>,
+    pub commanded_fan_duty: Option<u8>,
+    pub profile: Option<String>,
+}
+
+impl From<&HistoryRecord> for MqttState {
+    fn from(record: &HistoryRecord) -> Self {
+        Self {
+            status: record.status.clone(),
+            cpu_temp: record.cpu_temp,
+            gpu_temp: record.gpu_temp,
+            commanded_pump_duty: record.commanded.map(|(pump, _)| pump),
+            commanded_fan_duty: record.commanded.map(|(_, fan)| fan),
+            profile: record.profile.clone(),
+        }
+    }
+}
+
+// =============================================================================
+// Topics and Discovery
+// =============================================================================
+
+/// Topic names of one device.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Topics {
+    /// `<base>/<node>`
+    root: String,
+    /// Node id, e.g. `kraken_abc123`
+    node: String,
+    discovery_prefix: String,
+}
+
+impl Topics {
+    pub fn new(base_topic: &str, discovery_prefix: &str, serial: &str) -> Self {
+        let serial: String = serial
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .collect::<String>()
+            .to_lowercase();
+        let node = if serial.is_empty() {
+            "kraken".to_string()
+        } else {
+            format!("kraken_{}", serial)
+        };
+        Self {
+            root: format!("{}/{}", base_topic.trim_end_matches('/'), node),
+            node,
+            discovery_prefix: discovery_prefix.trim_end_matches('/').to_string(),
+        }
+    }
+
+    pub fn state(&self) -> String {
+        format!("{}/state", self.root)
+    }
+
+    pub fn availability(&self) -> String {
+        format!("{}/availability", self.root)
+    }
+
+    /// Command topic of a settable entity.
+    pub fn command(&self, entity: &str) -> String {
+        format!("{}/{}/set", self.root, entity)
+    }
+
+    /// Filter matching every command topic.
+    pub fn command_filter(&self) -> String {
+        format!("{}/+/set", self.root)
+    }
+
+    /// Parse a message on a command topic, `None` if it is not a valid
+    /// command.
+    pub fn parse_command(&self, topic: &str, payload: &[u8]) -> Option<MqttCommand> {
+        let entity = topic
+            .strip_prefix(&self.root)?
+            .strip_prefix('/')?
+            .strip_suffix("/set")?;
+        let payload = std::str::from_utf8(payload).ok()?.trim();
+        // Home Assistant sends numbers as "45" or "45.0"
+        let percent = || {
+            payload
+                .parse::<f32>()
+                .ok()
+                .filter(|v| (0.0..=100.0).contains(v))
+                .map(|v| v.round() as u8)
+        };
+
+        match entity {
+            PUMP_DUTY => percent().map(MqttCommand::PumpDuty),
+            FAN_DUTY => percent().map(MqttCommand::FanDuty),
+            BRIGHTNESS => percent().map(MqttCommand::Brightness),
+            PROFILE if !payload.is_empty() => Some(MqttCommand::Profile(payload.to_string())),
+            _ => None,
+        }
+    }
+
+    /// Home Assistant discovery configs as (topic, payload), empty if the
+    /// discovery prefix is empty.
+    pub fn discovery(&self, device: &DeviceLabels, profiles: &[String]) -> Vec<(String, Value)> {
+        if self.discovery_prefix.is_empty() {
+            return Vec::new();
+        }
+
+        let device = json!({
+            "identifiers": [self.node],
+            "name": "NZXT Kraken Z63",
+            "manufacturer": "NZXT",
+            "model": "Kraken Z63",
+            "serial_number": device.serial,
+            "sw_version": device.firmware,
+        });
+        let entity = |component: &str, object: &str, name: &str, extra: Value| {
+            let mut config = json!({
+                "name": name,
+                "unique_id": format!("{}_{}", self.node, object),
+                "availability_topic": self.availability(),
+                "device": device,
+            });
+            if let (Some(config), Value::Object(extra)) = (config.as_object_mut(), extra) {
+                config.extend(extra);
+            }
+            (
+                format!(
+                    "{}/{}/{}/{}/config",
+                    self.discovery_prefix, component, self.node, object
+                ),
+                config,
+            )
+        };
+        let sensor = |object: &str, name: &str, field: &str, unit: &str| {
+            let mut extra = json!({
+                "state_topic": self.state(),
+                "value_template": format!("{{{{ value_json.{} }}}}", field),
+                "unit_of_measurement": unit,
+                "state_class": "measurement",
+            });
+            if unit == "°C" {
+                extra["device_class"] = json!("temperature");
+            }
+            entity("sensor", object, name, extra)
+        };
+        let duty = |object: &str, name: &str, field: &str, min: u8| {
+            entity(
+                "number",
+                &format!("{}_setpoint", object),
+                name,
+                json!({
+                    "state_topic": self.state(),
+                    "value_template": format!("{{{{ value_json.{} }}}}", field),
+                    "command_topic": self.command(object),
+                    "min": min,
+                    "max": 100,
+                    "step": 1,
+                    "unit_of_measurement": "%",
+                }),
+            )
+        };
+
+        vec![
+            sensor(
+                "liquid_temperature",
+                "Liquid temperature",
+                "liquid_temp_c",
+                "°C",
+            ),
+            sensor("cpu_temperature", "CPU temperature", "cpu_temp", "°C"),
+            sensor("gpu_temperature", "GPU temperature", "gpu_temp", "°C"),
+            sensor("pump_speed", "Pump speed", "pump_rpm", "RPM"),
+            sensor("fan_speed", "Fan speed", "fan_rpm", "RPM"),
+            sensor(PUMP_DUTY, "Pump duty", "pump_duty", "%"),
+            sensor(FAN_DUTY, "Fan duty", "fan_duty", "%"),
+            duty(
+                PUMP_DUTY,
+                "Pump duty setpoint",
+                "commanded_pump_duty",
+                crate::protocol::Channel::Pump.min_duty(),
+            ),
+            duty(
+                FAN_DUTY,
+                "Fan duty setpoint",
+                "commanded_fan_duty",
+                crate::protocol::Channel::Fan.min_duty(),
+            ),
+            // The device cannot report its brightness
+            entity(
+                "number",
+                BRIGHTNESS,
+                "LCD brightness",
+                json!({
+                    "command_topic": self.command(BRIGHTNESS),
+                    "optimistic": true,
+                    "min": 0,
+                    "max": 100,
+                    "step": 1,
+                    "unit_of_measurement": "%",
+                }),
+            ),
+            entity(
+                "select",
+                PROFILE,
+                "Cooling profile",
+                json!({
+                    "state_topic": self.state(),
+                    "value_template": "{{ value_json.profile }}",
+                    "command_topic": self.command(PROFILE),
+                    "options": profiles,
+                }),
+            ),
+        ]
+    }
+}
+
+// =============================================================================
+// Bridge
+// =============================================================================
+
+/// Connection to the broker, kept alive (and reconnected) by a background
+/// thread until dropped.
+pub struct MqttBridge {
+    client: Client,
+    topics: Topics,
+    events: Receiver<MqttEvent>,
+    running: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl MqttBridge {
+    /// Start connecting to the broker of `config`.
+    ///
+    /// Returns immediately; [`MqttBridge::poll`] reports when the connection
+    /// is up. Discovery configs for `profiles` are published on every
+    /// (re)connect.
+    ///
+    /// # Errors
+    /// Returns `InvalidInput` if no broker host is configured.
+    pub fn connect(
+        config: &MqttConfig,
+        device: DeviceLabels,
+        profiles: Vec<String>,
+    ) -> Result<Self> {
+        if config.host.trim().is_empty() {
+            return Err(KrakenError::InvalidInput("MQTT host is empty".into()));
+        }
+        let topics = Topics::new(&config.base_topic, &config.discovery_prefix, &device.serial);
+
+        let mut options = MqttOptions::new(
+            format!("nzxt-rust-{}", topics.node),
+            config.host.trim(),
+            config.port,
+        );
+        options.set_keep_alive(Duration::from_secs(30));
+        options.set_last_will(LastWill::new(
+            topics.availability(),
+            "offline",
+            QoS::AtLeastOnce,
+            true,
+        ));
+        if let Some(username) = &config.username {
+            options.set_credentials(username, config.password.clone().unwrap_or_default());
+        }
+
+        let (client, mut connection) = Client::new(options, REQUEST_CAPACITY);
+        let (sender, events) = channel();
+        let running = Arc::new(AtomicBool::new(true));
+
+        let thread = {
+            let client = client.clone();
+            let topics = topics.clone();
+            let running = running.clone();
+            std::thread::spawn(move || {
+                let discovery = topics.discovery(&device, &profiles);
+                for event in connection.iter() {
+                    match event {
+                        Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                            announce(&client, &topics, &discovery);
+                            let _ = sender.send(MqttEvent::Connected);
+                        }
+                        Ok(Event::Incoming(Packet::Publish(publish))) => {
+                            if let Some(command) =
+                                topics.parse_command(&publish.topic, &publish.payload)
+                            {
+                                let _ = sender.send(MqttEvent::Command(command));
+                            }
+                        }
+                        Ok(Event::Outgoing(Outgoing::Disconnect)) => break,
+                        Ok(_) => {}
+                        Err(_) if !running.load(Ordering::SeqCst) => break,
+                        Err(e) => {
+                            let _ = sender.send(MqttEvent::Disconnected(e.to_string()));
+                            std::thread::sleep(RECONNECT_DELAY);
+                        }
+                    }
+                }
+            })
+        };
+
+        Ok(Self {
+            client,
+            topics,
+            events,
+            running,
+            thread: Some(thread),
+        })
+    }
+
+    pub fn topics(&self) -> &Topics {
+        &self.topics
+    }
+
+    /// Publish the state of this tick. Dropped if the broker is unreachable
+    /// and the queue is full.
+    pub fn publish_state(&self, state: &MqttState) {
+        if let Ok(payload) = serde_json::to_vec(state) {
+            let _ = self
+                .client
+                .try_publish(self.topics.state(), QoS::AtMostOnce, false, payload);
+        }
+    }
+
+    /// Connection changes and commands received since the last call.
+    pub fn poll(&self) -> Vec<MqttEvent> {
+        self.events.try_iter().collect()
+    }
+}
+
+/// Publish discovery and availability, and subscribe to the commands.
+fn announce(client: &Client, topics: &Topics, discovery: &[(String, Value)]) {
+    for (topic, config) in discovery {
+        let _ = client.try_publish(topic, QoS::AtLeastOnce, true, config.to_string());
+    }
+    let _ = client.try_publish(topics.availability(), QoS::AtLeastOnce, true, "online");
+    let _ = client.try_subscribe(topics.command_filter(), QoS::AtLeastOnce);
+}
+
+impl Drop for MqttBridge {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        let _ = self.client.try_publish(
+            self.topics.availability(),
+            QoS::AtLeastOnce,
+            true,
+            "offline",
+        );
+        let _ = self.client.try_disconnect();
+
+        // Give the thread a moment to send "offline"; don't hang on a dead broker
+        if let Some(thread) = self.thread.take() {
+            let deadline = std::time::Instant::now() + SHUTDOWN_TIMEOUT;
+            while !thread.is_finished() && std::time::Instant::now() < deadline {
+                std::thread::sleep(Duration::from_millis(20));
+            }
+            if thread.is_finished() {
+                let _ = thread.join();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::{TcpListener, TcpStream};
+
+    fn device() -> DeviceLabels {
+        DeviceLabels {
+            serial: "ABC-123".into(),
+            firmware: "2.0.1".into(),
+        }
+    }
+
+    #[test]
+    fn test_topics_and_commands() {
+        let topics = Topics::new("nzxt-kraken/", "homeassistant", "ABC-123");
+        assert_eq!(topics.state(), "nzxt-kraken/kraken_abc123/state");
+        assert_eq!(
+            topics.command(FAN_DUTY),
+            "nzxt-kraken/kraken_abc123/fan_duty/set"
+        );
+
+        let parse = |entity: &str, payload: &str| {
+            topics.parse_command(&topics.command(entity), payload.as_bytes())
+        };
+        assert_eq!(parse(FAN_DUTY, "45.0"), Some(MqttCommand::FanDuty(45)));
+        assert_eq!(parse(PUMP_DUTY, "70"), Some(MqttCommand::PumpDuty(70)));
+        assert_eq!(parse(BRIGHTNESS, " 80 "), Some(MqttCommand::Brightness(80)));
+        assert_eq!(
+            parse(PROFILE, "quiet"),
+            Some(MqttCommand::Profile("quiet".into()))
+        );
+        assert_eq!(parse(FAN_DUTY, "120"), None);
+        assert_eq!(parse(PROFILE, ""), None);
+        assert_eq!(parse("lights", "on"), None);
+        assert_eq!(topics.parse_command("other/fan_duty/set", b"40"), None);
+        assert_eq!(Topics::new("base", "", "").state(), "base/kraken/state");
+    }
+
+    #[test]
+    fn test_discovery() {
+        let topics = Topics::new("nzxt-kraken", "homeassistant", "ABC-123");
+        let configs = topics.discovery(&device(), &["silent".into(), "performance".into()]);
+
+        let find = |topic: &str| {
+            configs
+                .iter()
+                .find(|(t, _)| t == topic)
+                .map(|(_, c)| c.clone())
+                .unwrap_or_else(|| panic!("missing {}", topic))
+        };
+        let liquid = find("homeassistant/sensor/kraken_abc123/liquid_temperature/config");
+        assert_eq!(liquid["device_class"], "temperature");
+        assert_eq!(liquid["value_template"], "{{ value_json.liquid_temp_c }}");
+        assert_eq!(liquid["device"]["sw_version"], "2.0.1");
+        assert_eq!(
+            liquid["availability_topic"],
+            "nzxt-kraken/kraken_abc123/availability"
+        );
+
+        let pump = find("homeassistant/number/kraken_abc123/pump_duty_setpoint/config");
+        assert_eq!(pump["min"], 20);
+        assert_eq!(
+            pump["command_topic"],
+            "nzxt-kraken/kraken_abc123/pump_duty/set"
+        );
+
+        let profile = find("homeassistant/select/kraken_abc123/profile/config");
+        assert_eq!(profile["options"], json!(["silent", "performance"]));
+
+        assert!(
+            Topics::new("b", "", "x")
+                .discovery(&device(), &[])
+                .is_empty()
+        );
+    }
+
+    /// Read one MQTT packet: (first header byte, body).
+    fn read_packet(stream: &mut TcpStream) -> (u8, Vec<u8>) {
+        let mut byte = [0u8; 1];
+        stream.read_exact(&mut byte).unwrap();
+        let header = byte[0];
+        let (mut len, mut shift) = (0usize, 0);
+        loop {
+            stream.read_exact(&mut byte).unwrap();
+            len |= ((byte[0] & 0x7F) as usize) << shift;
+            shift += 7;
+            if byte[0] & 0x80 == 0 {
+                break;
+            }
+        }
+        let mut body = vec![0u8; len];
+        stream.read_exact(&mut body).unwrap();
+        (header, body)
+    }
+
+    #[test]
+    fn test_bridge() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let config = MqttConfig {
+            enabled: true,
+            host: "127.0.0.1".into(),
+            port: listener.local_addr().unwrap().port(),
+            ..MqttConfig::default()
+        };
+        let bridge = MqttBridge::connect(&config, device(), vec!["silent".into()]).unwrap();
+        let topics = bridge.topics().clone();
+
+        // Minimal broker: accept CONNECT, then collect the published topics
+        let (mut stream, _) = listener.accept().unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        assert_eq!(read_packet(&mut stream).0 >> 4, 1);
+        stream.write_all(&[0x20, 0x02, 0x00, 0x00]).unwrap();
+
+        let mut published = Vec::new();
+        loop {
+            let (header, body) = read_packet(&mut stream);
+            match header >> 4 {
+                // PUBLISH: topic length, topic, [packet id], payload
+                3 => {
+                    let len = u16::from_be_bytes([body[0], body[1]]) as usize;
+                    published.push(String::from_utf8(body[2..2 + len].to_vec()).unwrap());
+                    if header & 0x06 != 0 {
+                        stream
+                            .write_all(&[0x40, 0x02, body[2 + len], body[3 + len]])
+                            .unwrap();
+                    }
+                }
+                // SUBSCRIBE: ack, then send a command
+                8 => {
+                    stream
+                        .write_all(&[0x90, 0x03, body[0], body[1], 0x01])
+                        .unwrap();
+                    break;
+                }
+                _ => {}
+            }
+        }
+        assert!(published.contains(&"homeassistant/select/kraken_abc123/profile/config".into()));
+        assert!(published.contains(&topics.availability()));
+
+        let topic = topics.command(FAN_DUTY);
+        let mut packet = vec![0x30, (2 + topic.len() + 2) as u8, 0, topic.len() as u8];
+        packet.extend(topic.as_bytes());
+        packet.extend(b"55");
+        stream.write_all(&packet).unwrap();
+
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        let mut events = Vec::new();
+        while !events.contains(&MqttEvent::Command(MqttCommand::FanDuty(55))) {
+            assert!(std::time::Instant::now() < deadline, "events: {:?}", events);
+            std::thread::sleep(Duration::from_millis(20));
+            events.extend(bridge.poll());
+        }
+        assert_eq!(events[0], MqttEvent::Connected);
+    }
+}
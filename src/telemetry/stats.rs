@@ -0,0 +1,458 @@
+//! In-process telemetry history and statistics.
+//!
+//! [`TelemetryHistory`] is a ring buffer of the last samples read by a daemon
+//! loop. It answers min/max/mean/percentile queries over a time window, the
+//! time spent above a threshold, and the rate of change of a reading.
+//!
+//! The daemons answer the `stats` command from it with a [`StatsReport`]
+//! over the control socket.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use super::history::HistoryRecord;
+use crate::error::{KrakenError, Result};
+use crate::protocol::status::DeviceStatus;
+
+/// Default number of samples kept: an hour at one sample per second.
+pub const DEFAULT_CAPACITY: usize = 3600;
+
+// =============================================================================
+// Samples
+// =============================================================================
+
+/// One telemetry sample.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Sample {
+    /// Unix time in milliseconds
+    pub time_ms: i64,
+    pub liquid_temp: f32,
+    pub pump_rpm: u16,
+    pub pump_duty: u8,
+    pub fan_rpm: u16,
+    pub fan_duty: u8,
+    pub cpu_temp: Option<f32>,
+    pub gpu_temp: Option<f32>,
+}
+
+impl Sample {
+    /// Sample of a status read at `time_ms`, without system temperatures.
+    pub fn new(time_ms: i64, status: &DeviceStatus) -> Self {
+        Self {
+            time_ms,
+            liquid_temp: status.liquid_temp_c,
+            pump_rpm: status.pump_rpm,
+            pump_duty: status.pump_duty,
+            fan_rpm: status.fan_rpm,
+            fan_duty: status.fan_duty,
+            cpu_temp: None,
+            gpu_temp: None,
+        }
+    }
+
+    /// Value of a metric, `None` if it was not read.
+    pub fn value(&self, metric: Metric) -> Option<f32> {
+        match metric {
+            Metric::LiquidTemp => Some(self.liquid_temp),
+            Metric::PumpRpm => Some(self.pump_rpm as f32),
+            Metric::PumpDuty => Some(self.pump_duty as f32),
+            Metric::FanRpm => Some(self.fan_rpm as f32),
+            Metric::FanDuty => Some(self.fan_duty as f32),
+            Metric::CpuTemp => self.cpu_temp,
+            Metric::GpuTemp => self.gpu_temp,
+        }
+    }
+}
+
+impl From<&HistoryRecord> for Sample {
+    fn from(record: &HistoryRecord) -> Self {
+        Self {
+            cpu_temp: record.cpu_temp,
+            gpu_temp: record.gpu_temp,
+            ..Sample::new(record.timestamp.timestamp_millis(), &record.status)
+        }
+    }
+}
+
+/// A reading that statistics can be computed over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Metric {
+    LiquidTemp,
+    PumpRpm,
+    PumpDuty,
+    FanRpm,
+    FanDuty,
+    CpuTemp,
+    GpuTemp,
+}
+
+impl Metric {
+    /// All metrics, in display order.
+    pub const ALL: [Metric; 7] = [
+        Metric::LiquidTemp,
+        Metric::CpuTemp,
+        Metric::GpuTemp,
+        Metric::PumpRpm,
+        Metric::PumpDuty,
+        Metric::FanRpm,
+        Metric::FanDuty,
+    ];
+
+    /// Unit of the metric ("°C", "RPM" or "%").
+    pub fn unit(self) -> &'static str {
+        match self {
+            Metric::LiquidTemp | Metric::CpuTemp | Metric::GpuTemp => "°C",
+            Metric::PumpRpm | Metric::FanRpm => "RPM",
+            Metric::PumpDuty | Metric::FanDuty => "%",
+        }
+    }
+}
+
+impl fmt::Display for Metric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Metric::LiquidTemp => "liquid",
+            Metric::PumpRpm => "pump-rpm",
+            Metric::PumpDuty => "pump-duty",
+            Metric::FanRpm => "fan-rpm",
+            Metric::FanDuty => "fan-duty",
+            Metric::CpuTemp => "cpu",
+            Metric::GpuTemp => "gpu",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Metric {
+    type Err = KrakenError;
+
+    /// Accepts the names with `-` or `_` (e.g. `pump-rpm` or `pump_rpm`).
+    fn from_str(s: &str) -> Result<Self> {
+        let name = s.trim().replace('_', "-");
+        Metric::ALL
+            .into_iter()
+            .find(|m| m.to_string().eq_ignore_ascii_case(&name))
+            .ok_or_else(|| {
+                let names: Vec<String> = Metric::ALL.iter().map(Metric::to_string).collect();
+                KrakenError::InvalidInput(format!(
+                    "Unknown metric '{}'. Use one of: {}",
+                    s,
+                    names.join(", ")
+                ))
+            })
+    }
+}
+
+/// Summary of a metric over a window.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Stats {
+    /// Samples that had a value
+    pub count: usize,
+    pub min: f32,
+    pub max: f32,
+    pub mean: f32,
+    pub p50: f32,
+    pub p95: f32,
+    pub p99: f32,
+}
+
+/// Time a metric spent above a threshold.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TimeAbove {
+    pub threshold: f32,
+    pub secs: f64,
+}
+
+/// Statistics of a history over a window, as answered to `stats`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatsReport {
+    /// Samples in the window
+    pub samples: usize,
+    /// Time between the oldest and the newest sample kept
+    pub span_secs: f64,
+    pub window_secs: Option<u64>,
+    /// Statistics by metric name, for the metrics that had values
+    pub metrics: BTreeMap<String, Stats>,
+    /// Liquid temperature trend (°C/min)
+    pub liquid_trend_per_min: Option<f32>,
+    /// Time the liquid temperature spent above the requested threshold
+    pub liquid_above: Option<TimeAbove>,
+}
+
+impl StatsReport {
+    /// Statistics of a metric, `None` if it had no values.
+    pub fn stats(&self, metric: Metric) -> Option<&Stats> {
+        self.metrics.get(&metric.to_string())
+    }
+}
+
+/// Percentile `p` (0-100) of sorted values, interpolating between ranks.
+fn percentile(sorted: &[f32], p: f32) -> f32 {
+    let rank = (p.clamp(0.0, 100.0) / 100.0) * (sorted.len() - 1) as f32;
+    let (lo, hi) = (rank.floor() as usize, rank.ceil() as usize);
+    sorted[lo] + (sorted[hi] - sorted[lo]) * (rank - lo as f32)
+}
+
+// =============================================================================
+// History
+// =============================================================================
+
+/// Ring buffer of the most recent samples.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TelemetryHistory {
+    capacity: usize,
+    samples: VecDeque<Sample>,
+}
+
+impl Default for TelemetryHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl TelemetryHistory {
+    /// Empty history keeping at most `capacity` samples (at least one).
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            capacity,
+            samples: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Add a sample, dropping the oldest one when full.
+    pub fn push(&mut self, sample: Sample) {
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// The most recent sample.
+    pub fn latest(&self) -> Option<&Sample> {
+        self.samples.back()
+    }
+
+    /// Time between the oldest and the newest sample.
+    pub fn span(&self) -> Duration {
+        match (self.samples.front(), self.samples.back()) {
+            (Some(first), Some(last)) => millis(last.time_ms - first.time_ms),
+            _ => Duration::ZERO,
+        }
+    }
+
+    /// Samples within `window` of the newest one (all samples for `None`),
+    /// oldest first.
+    pub fn window(&self, window: Option<Duration>) -> impl Iterator<Item = &Sample> {
+        let start = match (window, self.latest()) {
+            (Some(window), Some(last)) => last
+                .time_ms
+                .saturating_sub(i64::try_from(window.as_millis()).unwrap_or(i64::MAX)),
+            _ => i64::MIN,
+        };
+        self.samples.iter().filter(move |s| s.time_ms >= start)
+    }
+
+    /// Min, max, mean and percentiles of a metric, `None` without values.
+    pub fn stats(&self, metric: Metric, window: Option<Duration>) -> Option<Stats> {
+        let mut values: Vec<f32> = self
+            .window(window)
+            .filter_map(|s| s.value(metric))
+            .collect();
+        if values.is_empty() {
+            return None;
+        }
+        values.sort_by(f32::total_cmp);
+
+        Some(Stats {
+            count: values.len(),
+            min: values[0],
+            max: values[values.len() - 1],
+            mean: values.iter().sum::<f32>() / values.len() as f32,
+            p50: percentile(&values, 50.0),
+            p95: percentile(&values, 95.0),
+            p99: percentile(&values, 99.0),
+        })
+    }
+
+    /// Time a metric spent above `threshold`.
+    ///
+    /// Each sample above the threshold counts until the next sample.
+    pub fn time_above(&self, metric: Metric, threshold: f32, window: Option<Duration>) -> Duration {
+        let samples: Vec<&Sample> = self.window(window).collect();
+        let above_ms: i64 = samples
+            .windows(2)
+            .filter(|pair| pair[0].value(metric).is_some_and(|v| v > threshold))
+            .map(|pair| pair[1].time_ms - pair[0].time_ms)
+            .sum();
+        millis(above_ms)
+    }
+
+    /// Rate of change of a metric per minute (least-squares slope), `None`
+    /// with fewer than two values or no elapsed time.
+    pub fn rate_of_change(&self, metric: Metric, window: Option<Duration>) -> Option<f32> {
+        let points: Vec<(f64, f64)> = self
+            .window(window)
+            .filter_map(|s| s.value(metric).map(|v| (s.time_ms as f64, v as f64)))
+            .collect();
+        if points.len() < 2 {
+            return None;
+        }
+
+        // Center the times to keep the sums small
+        let n = points.len() as f64;
+        let mean_t = points.iter().map(|p| p.0).sum::<f64>() / n;
+        let mean_v = points.iter().map(|p| p.1).sum::<f64>() / n;
+        let (mut cov, mut var) = (0.0, 0.0);
+        for (t, v) in &points {
+            cov += (t - mean_t) * (v - mean_v);
+            var += (t - mean_t) * (t - mean_t);
+        }
+        if var == 0.0 {
+            return None;
+        }
+        Some((cov / var * 60_000.0) as f32)
+    }
+
+    /// Statistics of every metric, the liquid trend and, with `above`, the
+    /// time the liquid temperature spent above it.
+    pub fn report(&self, window: Option<Duration>, above: Option<f32>) -> StatsReport {
+        StatsReport {
+            samples: self.window(window).count(),
+            span_secs: self.span().as_secs_f64(),
+            window_secs: window.map(|w| w.as_secs()),
+            metrics: Metric::ALL
+                .into_iter()
+                .filter_map(|m| self.stats(m, window).map(|s| (m.to_string(), s)))
+                .collect(),
+            liquid_trend_per_min: self.rate_of_change(Metric::LiquidTemp, window),
+            liquid_above: above.map(|threshold| TimeAbove {
+                threshold,
+                secs: self
+                    .time_above(Metric::LiquidTemp, threshold, window)
+                    .as_secs_f64(),
+            }),
+        }
+    }
+}
+
+fn millis(ms: i64) -> Duration {
+    Duration::from_millis(ms.max(0) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// One sample per second, liquid temperature from `temps`.
+    fn history(temps: &[f32]) -> TelemetryHistory {
+        let mut history = TelemetryHistory::new(100);
+        for (i, &temp) in temps.iter().enumerate() {
+            history.push(Sample {
+                cpu_temp: (i % 2 == 0).then_some(50.0 + i as f32),
+                ..Sample::new(
+                    i as i64 * 1000,
+                    &DeviceStatus {
+                        liquid_temp_c: temp,
+                        pump_rpm: 2000,
+                        pump_duty: 60,
+                        fan_rpm: 800,
+                        fan_duty: 40,
+                    },
+                )
+            });
+        }
+        history
+    }
+
+    #[test]
+    fn test_ring_buffer() {
+        let mut history = TelemetryHistory::new(3);
+        let status = DeviceStatus {
+            liquid_temp_c: 30.0,
+            pump_rpm: 0,
+            pump_duty: 0,
+            fan_rpm: 0,
+            fan_duty: 0,
+        };
+        for t in 0..5 {
+            history.push(Sample::new(t * 1000, &status));
+        }
+        assert_eq!(history.len(), 3);
+        assert_eq!(history.window(None).next().unwrap().time_ms, 2000);
+        assert_eq!(history.latest().unwrap().time_ms, 4000);
+        assert_eq!(history.span(), Duration::from_secs(2));
+        assert_eq!(history.window(Some(Duration::from_secs(1))).count(), 2);
+    }
+
+    #[test]
+    fn test_stats() {
+        let history = history(&[30.0, 32.0, 31.0, 35.0, 34.0]);
+        let stats = history.stats(Metric::LiquidTemp, None).unwrap();
+        assert_eq!((stats.count, stats.min, stats.max), (5, 30.0, 35.0));
+        assert!((stats.mean - 32.4).abs() < 1e-4);
+        assert_eq!(stats.p50, 32.0);
+        assert!((stats.p95 - 34.8).abs() < 1e-4);
+
+        // Last 2 seconds: 31, 35, 34
+        let recent = history
+            .stats(Metric::LiquidTemp, Some(Duration::from_secs(2)))
+            .unwrap();
+        assert_eq!((recent.count, recent.min), (3, 31.0));
+
+        // Missing readings are skipped
+        assert_eq!(history.stats(Metric::CpuTemp, None).unwrap().count, 3);
+        assert_eq!(history.stats(Metric::GpuTemp, None), None);
+    }
+
+    #[test]
+    fn test_time_above_and_rate() {
+        let history = history(&[30.0, 32.0, 34.0, 36.0, 38.0]);
+        assert_eq!(
+            history.time_above(Metric::LiquidTemp, 33.0, None),
+            Duration::from_secs(2)
+        );
+        assert_eq!(
+            history.time_above(Metric::LiquidTemp, 40.0, None),
+            Duration::ZERO
+        );
+
+        // 2°C per second
+        let rate = history.rate_of_change(Metric::LiquidTemp, None).unwrap();
+        assert!((rate - 120.0).abs() < 1e-3);
+        assert_eq!(
+            TelemetryHistory::new(5).rate_of_change(Metric::LiquidTemp, None),
+            None
+        );
+    }
+
+    #[test]
+    fn test_report() {
+        let history = history(&[30.0, 32.0, 34.0, 36.0, 38.0]);
+        let report = history.report(Some(Duration::from_secs(2)), Some(33.0));
+        assert_eq!((report.samples, report.span_secs), (3, 4.0));
+        assert_eq!(report.stats(Metric::LiquidTemp).unwrap().min, 34.0);
+        assert_eq!(report.stats(Metric::GpuTemp), None);
+        assert_eq!(report.liquid_above.unwrap().secs, 2.0);
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["metrics"]["liquid"]["max"], 38.0);
+        assert_eq!(serde_json::from_value::<StatsReport>(json).unwrap(), report);
+    }
+}
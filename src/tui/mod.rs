@@ -0,0 +1,296 @@
+//! Terminal dashboard of the `tui` command.
+//!
+//! [`Dashboard`] holds what is on screen and turns key presses into
+//! [`Action`]s, which the command applies through the running daemon or the
+//! device. [`draw`] renders it with ratatui on the plain terminal, so the
+//! dashboard also works over SSH.
+
+mod ui;
+
+pub use ui::draw;
+
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+
+use crate::cooling::{ChannelExpr, TempSource, Temperatures};
+use crate::error::Result;
+use crate::protocol::{BucketInfo, Channel};
+use crate::storage;
+use crate::telemetry::{Sample, TelemetryHistory};
+
+/// Samples kept for the graphs.
+pub const HISTORY_CAPACITY: usize = 300;
+
+/// Duty change of one key press (%).
+pub const DUTY_STEP: u8 = 5;
+
+/// Brightness change of one key press (%).
+pub const BRIGHTNESS_STEP: u8 = 10;
+
+/// Pump and fan curves of a profile.
+fn profile_curves(name: &str) -> Result<(Option<ChannelExpr>, Option<ChannelExpr>)> {
+    let profile = storage::resolve_profile(name)?;
+    Ok((
+        profile.channel_expr(Channel::Pump)?,
+        profile.channel_expr(Channel::Fan)?,
+    ))
+}
+
+/// What a key press asks for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    Quit,
+    SetProfile(String),
+    /// Hold a channel at a fixed duty
+    SetDuty(Channel, u8),
+    SetBrightness(u8),
+}
+
+/// State of the dashboard.
+pub struct Dashboard {
+    pub history: TelemetryHistory,
+    /// Ids of the profiles the keys cycle through
+    pub profiles: Vec<String>,
+    /// Active profile, `None` while a duty is held
+    pub profile: Option<String>,
+    pump: Option<ChannelExpr>,
+    fan: Option<ChannelExpr>,
+    pub buckets: Vec<BucketInfo>,
+    /// Channel the duty keys act on
+    pub channel: Channel,
+    pub brightness: Option<u8>,
+    /// Where readings come from, shown in the title
+    pub source: String,
+    /// Result of the last action
+    pub message: Option<String>,
+    held: (Option<u8>, Option<u8>),
+}
+
+impl Dashboard {
+    pub fn new(source: impl Into<String>, profiles: Vec<String>) -> Self {
+        Self {
+            history: TelemetryHistory::new(HISTORY_CAPACITY),
+            profiles,
+            profile: None,
+            pump: None,
+            fan: None,
+            buckets: Vec::new(),
+            channel: Channel::Pump,
+            brightness: None,
+            source: source.into(),
+            message: None,
+            held: (None, None),
+        }
+    }
+
+    /// Record a sample, with the profile it was read under.
+    pub fn push(&mut self, sample: Sample, profile: Option<String>) {
+        self.history.push(sample);
+        if profile.is_some() && profile != self.profile {
+            self.set_profile(profile);
+        }
+    }
+
+    /// Show a profile and its curves.
+    pub fn set_profile(&mut self, profile: Option<String>) {
+        (self.pump, self.fan) = match profile.as_deref().map(profile_curves) {
+            Some(Ok(curves)) => curves,
+            Some(Err(e)) => {
+                self.message = Some(e.to_string());
+                (None, None)
+            }
+            None => (None, None),
+        };
+        if profile.is_some() {
+            self.held = (None, None);
+        }
+        self.profile = profile;
+    }
+
+    /// Curve of a channel under the active profile.
+    pub fn curve(&self, channel: Channel) -> Option<&ChannelExpr> {
+        match channel {
+            Channel::Pump => self.pump.as_ref(),
+            Channel::Fan => self.fan.as_ref(),
+        }
+    }
+
+    /// Temperature the curve of a channel reads now.
+    pub fn curve_input(&self, channel: Channel) -> Option<f32> {
+        let latest = self.history.latest()?;
+        let temps = Temperatures {
+            liquid: Some(latest.liquid_temp),
+            cpu: latest.cpu_temp,
+        };
+        self.curve(channel)?
+            .input_temperature(&temps, TempSource::Liquid)
+    }
+
+    /// Duty the channel runs at: the held duty, else the last reading.
+    fn duty(&self, channel: Channel) -> Option<u8> {
+        let held = match channel {
+            Channel::Pump => self.held.0,
+            Channel::Fan => self.held.1,
+        };
+        held.or_else(|| {
+            let latest = self.history.latest()?;
+            Some(match channel {
+                Channel::Pump => latest.pump_duty,
+                Channel::Fan => latest.fan_duty,
+            })
+        })
+    }
+
+    fn nudge_duty(&mut self, up: bool) -> Option<Action> {
+        let channel = self.channel;
+        let duty = self.duty(channel)?;
+        let duty = if up {
+            duty.saturating_add(DUTY_STEP).min(100)
+        } else {
+            duty.saturating_sub(DUTY_STEP).max(channel.min_duty())
+        };
+        match channel {
+            Channel::Pump => self.held.0 = Some(duty),
+            Channel::Fan => self.held.1 = Some(duty),
+        }
+        Some(Action::SetDuty(channel, duty))
+    }
+
+    fn cycle_profile(&self, forward: bool) -> Option<Action> {
+        let count = self.profiles.len();
+        if count == 0 {
+            return None;
+        }
+        let next = match self.profile.as_ref().and_then(|p| {
+            self.profiles
+                .iter()
+                .position(|id| id.eq_ignore_ascii_case(p))
+        }) {
+            Some(i) if forward => (i + 1) % count,
+            Some(i) => (i + count - 1) % count,
+            None => 0,
+        };
+        Some(Action::SetProfile(self.profiles[next].clone()))
+    }
+
+    fn nudge_brightness(&self, up: bool) -> Option<Action> {
+        let brightness = self.brightness?;
+        Some(Action::SetBrightness(if up {
+            brightness.saturating_add(BRIGHTNESS_STEP).min(100)
+        } else {
+            brightness.saturating_sub(BRIGHTNESS_STEP)
+        }))
+    }
+
+    /// Handle a key press.
+    ///
+    /// | Key | Action |
+    /// |-----|--------|
+    /// | `q`, `Esc`, `Ctrl+C` | quit |
+    /// | `Tab` | switch the channel of the duty keys |
+    /// | `+`/`Up`, `-`/`Down` | hold the channel at ±[`DUTY_STEP`] |
+    /// | `p`/`Right`, `P`/`Left` | next / previous profile |
+    /// | `]`, `[` | brightness ±[`BRIGHTNESS_STEP`] |
+    pub fn handle_key(&mut self, key: KeyEvent) -> Option<Action> {
+        if key.kind == KeyEventKind::Release {
+            return None;
+        }
+        match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                Some(Action::Quit)
+            }
+            KeyCode::Char('q') | KeyCode::Esc => Some(Action::Quit),
+            KeyCode::Tab | KeyCode::BackTab => {
+                self.channel = match self.channel {
+                    Channel::Pump => Channel::Fan,
+                    Channel::Fan => Channel::Pump,
+                };
+                None
+            }
+            KeyCode::Char('+' | '=') | KeyCode::Up => self.nudge_duty(true),
+            KeyCode::Char('-') | KeyCode::Down => self.nudge_duty(false),
+            KeyCode::Char('p') | KeyCode::Right => self.cycle_profile(true),
+            KeyCode::Char('P') | KeyCode::Left => self.cycle_profile(false),
+            KeyCode::Char(']') => self.nudge_brightness(true),
+            KeyCode::Char('[') => self.nudge_brightness(false),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::DeviceStatus;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn dashboard() -> Dashboard {
+        let mut dashboard = Dashboard::new("device", vec!["silent".into(), "performance".into()]);
+        dashboard.history.push(Sample::new(
+            0,
+            &DeviceStatus {
+                liquid_temp_c: 30.0,
+                pump_rpm: 2000,
+                pump_duty: 22,
+                fan_rpm: 800,
+                fan_duty: 98,
+            },
+        ));
+        dashboard
+    }
+
+    #[test]
+    fn test_duty_keys() {
+        let mut dashboard = dashboard();
+
+        // The pump never goes below its minimum duty
+        assert_eq!(
+            dashboard.handle_key(key(KeyCode::Down)),
+            Some(Action::SetDuty(Channel::Pump, Channel::Pump.min_duty()))
+        );
+        assert_eq!(
+            dashboard.handle_key(key(KeyCode::Char('+'))),
+            Some(Action::SetDuty(Channel::Pump, Channel::Pump.min_duty() + 5))
+        );
+
+        assert_eq!(dashboard.handle_key(key(KeyCode::Tab)), None);
+        assert_eq!(
+            dashboard.handle_key(key(KeyCode::Up)),
+            Some(Action::SetDuty(Channel::Fan, 100))
+        );
+        assert_eq!(
+            dashboard.handle_key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)),
+            Some(Action::Quit)
+        );
+    }
+
+    #[test]
+    fn test_profile_and_brightness_keys() {
+        let mut dashboard = dashboard();
+        assert_eq!(
+            dashboard.handle_key(key(KeyCode::Char('p'))),
+            Some(Action::SetProfile("silent".into()))
+        );
+
+        // Daemons report the profile name as it was asked for
+        dashboard.profile = Some("Silent".into());
+        assert_eq!(
+            dashboard.handle_key(key(KeyCode::Left)),
+            Some(Action::SetProfile("performance".into()))
+        );
+
+        // Unknown brightness: nothing to nudge from
+        assert_eq!(dashboard.handle_key(key(KeyCode::Char(']'))), None);
+        dashboard.brightness = Some(95);
+        assert_eq!(
+            dashboard.handle_key(key(KeyCode::Char(']'))),
+            Some(Action::SetBrightness(100))
+        );
+        assert_eq!(
+            dashboard.handle_key(key(KeyCode::Char('['))),
+            Some(Action::SetBrightness(85))
+        );
+    }
+}
@@ -0,0 +1,309 @@
+//! Layout and widgets of the dashboard.
+
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::symbols::Marker;
+use ratatui::text::Line;
+use ratatui::widgets::{Axis, Block, Chart, Dataset, Gauge, GraphType, List, ListItem, Paragraph};
+
+use super::Dashboard;
+use crate::protocol::{CRITICAL_TEMPERATURE, Channel, MIN_CURVE_TEMP};
+use crate::telemetry::Sample;
+
+const PUMP_COLOR: Color = Color::Cyan;
+const FAN_COLOR: Color = Color::Magenta;
+
+/// Draw the whole dashboard.
+pub fn draw(frame: &mut Frame, dashboard: &Dashboard) {
+    let [title, graphs, gauges, bottom, footer] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Min(8),
+        Constraint::Length(3),
+        Constraint::Min(10),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    frame.render_widget(title_line(dashboard), title);
+
+    let [temps, rpms] = Layout::horizontal([Constraint::Fill(1); 2]).areas(graphs);
+    draw_temperatures(frame, dashboard, temps);
+    draw_rpms(frame, dashboard, rpms);
+
+    let [pump, fan] = Layout::horizontal([Constraint::Fill(1); 2]).areas(gauges);
+    draw_gauge(frame, dashboard, Channel::Pump, pump);
+    draw_gauge(frame, dashboard, Channel::Fan, fan);
+
+    let [curve, buckets] =
+        Layout::horizontal([Constraint::Fill(1), Constraint::Length(30)]).areas(bottom);
+    draw_curve(frame, dashboard, curve);
+    draw_buckets(frame, dashboard, buckets);
+
+    frame.render_widget(footer_line(dashboard), footer);
+}
+
+fn channel_color(channel: Channel) -> Color {
+    match channel {
+        Channel::Pump => PUMP_COLOR,
+        Channel::Fan => FAN_COLOR,
+    }
+}
+
+fn title_line(dashboard: &Dashboard) -> Line<'static> {
+    let mut spans = vec![
+        " NZXT Kraken ".bold().reversed(),
+        format!(" via {}", dashboard.source).into(),
+        format!(
+            "  📋 {}",
+            dashboard.profile.as_deref().unwrap_or("(no profile)")
+        )
+        .into(),
+    ];
+    if let Some(brightness) = dashboard.brightness {
+        spans.push(format!("  💡 {}%", brightness).into());
+    }
+    Line::from(spans)
+}
+
+fn footer_line(dashboard: &Dashboard) -> Line<'_> {
+    let help = " q quit · Tab channel · +/- duty · p/P profile · [/] brightness ";
+    match &dashboard.message {
+        Some(message) => Line::from(vec![help.dim(), message.as_str().yellow()]),
+        None => Line::from(help.dim()),
+    }
+}
+
+/// Points of a reading over time, x in seconds before the latest sample.
+fn series(dashboard: &Dashboard, value: impl Fn(&Sample) -> Option<f64>) -> Vec<(f64, f64)> {
+    let Some(latest) = dashboard.history.latest().map(|s| s.time_ms) else {
+        return Vec::new();
+    };
+    dashboard
+        .history
+        .window(None)
+        .filter_map(|s| Some(((s.time_ms - latest) as f64 / 1000.0, value(s)?)))
+        .collect()
+}
+
+/// Bounds of the values, padded so the lines do not touch the border.
+fn value_bounds(series: &[&[(f64, f64)]], min_span: f64) -> [f64; 2] {
+    let values = series.iter().flat_map(|s| s.iter().map(|&(_, y)| y));
+    let (min, max) = values.fold((f64::MAX, f64::MIN), |(lo, hi), y| (lo.min(y), hi.max(y)));
+    if min > max {
+        return [0.0, min_span];
+    }
+    let pad = ((max - min).max(min_span) - (max - min)) / 2.0 + (max - min) * 0.05;
+    [(min - pad).max(0.0), max + pad]
+}
+
+fn time_axis(dashboard: &Dashboard) -> Axis<'static> {
+    let span = dashboard.history.span().as_secs_f64().max(10.0);
+    Axis::default()
+        .bounds([-span, 0.0])
+        .labels([format!("-{:.0}s", span), "now".to_string()])
+}
+
+fn value_axis(bounds: [f64; 2], unit: &str) -> Axis<'static> {
+    Axis::default().bounds(bounds).labels([
+        format!("{:.0}{}", bounds[0], unit),
+        format!("{:.0}{}", bounds[1], unit),
+    ])
+}
+
+fn line<'a>(name: &'a str, color: Color, data: &'a [(f64, f64)]) -> Dataset<'a> {
+    Dataset::default()
+        .name(name)
+        .marker(Marker::Braille)
+        .graph_type(GraphType::Line)
+        .style(Style::default().fg(color))
+        .data(data)
+}
+
+fn draw_temperatures(frame: &mut Frame, dashboard: &Dashboard, area: Rect) {
+    let liquid = series(dashboard, |s| Some(s.liquid_temp.into()));
+    let cpu = series(dashboard, |s| s.cpu_temp.map(f64::from));
+    let bounds = value_bounds(&[&liquid, &cpu], 10.0);
+
+    let chart = Chart::new(vec![
+        line("Liquid", Color::Blue, &liquid),
+        line("CPU", Color::Red, &cpu),
+    ])
+    .block(Block::bordered().title(" Temperature "))
+    .x_axis(time_axis(dashboard))
+    .y_axis(value_axis(bounds, "°C"));
+    frame.render_widget(chart, area);
+}
+
+fn draw_rpms(frame: &mut Frame, dashboard: &Dashboard, area: Rect) {
+    let pump = series(dashboard, |s| Some(s.pump_rpm.into()));
+    let fan = series(dashboard, |s| Some(s.fan_rpm.into()));
+    let bounds = value_bounds(&[&pump, &fan], 500.0);
+
+    let chart = Chart::new(vec![
+        line("Pump", PUMP_COLOR, &pump),
+        line("Fan", FAN_COLOR, &fan),
+    ])
+    .block(Block::bordered().title(" Speed "))
+    .x_axis(time_axis(dashboard))
+    .y_axis(value_axis(bounds, ""));
+    frame.render_widget(chart, area);
+}
+
+fn draw_gauge(frame: &mut Frame, dashboard: &Dashboard, channel: Channel, area: Rect) {
+    let latest = dashboard.history.latest();
+    let (duty, rpm) = match (channel, latest) {
+        (Channel::Pump, Some(s)) => (s.pump_duty, s.pump_rpm),
+        (Channel::Fan, Some(s)) => (s.fan_duty, s.fan_rpm),
+        (_, None) => (0, 0),
+    };
+
+    let mut block = Block::bordered().title(format!(" {} duty ", channel));
+    if channel == dashboard.channel {
+        block = block
+            .title(" ◀ +/- ")
+            .border_style(Style::default().add_modifier(Modifier::BOLD));
+    }
+    let gauge = Gauge::default()
+        .block(block)
+        .gauge_style(Style::default().fg(channel_color(channel)))
+        .percent(duty.min(100).into())
+        .label(format!("{}% · {} RPM", duty, rpm));
+    frame.render_widget(gauge, area);
+}
+
+fn draw_curve(frame: &mut Frame, dashboard: &Dashboard, area: Rect) {
+    let curve = |channel| -> Vec<(f64, f64)> {
+        let Some(expr) = dashboard.curve(channel) else {
+            return Vec::new();
+        };
+        (MIN_CURVE_TEMP..=CRITICAL_TEMPERATURE)
+            .zip(expr.table(channel))
+            .map(|(temp, duty)| (temp.into(), duty.into()))
+            .collect()
+    };
+    // The live operating point: the temperature the curve reads, the duty read
+    let point = |channel| -> Vec<(f64, f64)> {
+        let latest = dashboard.history.latest();
+        let duty = match channel {
+            Channel::Pump => latest.map(|s| s.pump_duty),
+            Channel::Fan => latest.map(|s| s.fan_duty),
+        };
+        match (dashboard.curve_input(channel), duty) {
+            (Some(temp), Some(duty)) => vec![(temp.into(), duty.into())],
+            _ => Vec::new(),
+        }
+    };
+    let (pump, fan) = (curve(Channel::Pump), curve(Channel::Fan));
+    let (pump_now, fan_now) = (point(Channel::Pump), point(Channel::Fan));
+
+    let mut datasets = vec![
+        line("Pump", PUMP_COLOR, &pump),
+        line("Fan", FAN_COLOR, &fan),
+    ];
+    for (data, color) in [(&pump_now, PUMP_COLOR), (&fan_now, FAN_COLOR)] {
+        datasets.push(
+            Dataset::default()
+                .marker(Marker::Block)
+                .graph_type(GraphType::Scatter)
+                .style(Style::default().fg(color).add_modifier(Modifier::BOLD))
+                .data(data),
+        );
+    }
+
+    let title = match &dashboard.profile {
+        Some(profile) => format!(" Curve: {} ", profile),
+        None => " Curve ".to_string(),
+    };
+    let chart = Chart::new(datasets)
+        .block(Block::bordered().title(title))
+        .x_axis(value_axis(
+            [MIN_CURVE_TEMP.into(), CRITICAL_TEMPERATURE.into()],
+            "°C",
+        ))
+        .y_axis(value_axis([0.0, 100.0], "%"));
+    frame.render_widget(chart, area);
+}
+
+fn draw_buckets(frame: &mut Frame, dashboard: &Dashboard, area: Rect) {
+    let items: Vec<ListItem> = dashboard
+        .buckets
+        .iter()
+        .map(|bucket| {
+            let text = format!(
+                "#{:<2} {} {:>4} KB @ {}",
+                bucket.index,
+                if bucket.occupied { "●" } else { "○" },
+                bucket.size_pages,
+                bucket.start_page
+            );
+            if bucket.occupied {
+                ListItem::new(text)
+            } else {
+                ListItem::new(text).dim()
+            }
+        })
+        .collect();
+
+    let block = Block::bordered().title(" LCD buckets ");
+    if items.is_empty() {
+        frame.render_widget(Paragraph::new("(not read yet)").dim().block(block), area);
+    } else {
+        frame.render_widget(List::new(items).block(block), area);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::{BucketInfo, DeviceStatus};
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+
+    #[test]
+    fn test_draw() {
+        let mut dashboard = Dashboard::new("device", Vec::new());
+        for i in 0..20 {
+            dashboard.history.push(Sample {
+                cpu_temp: Some(50.0 + i as f32),
+                ..Sample::new(
+                    i * 1000,
+                    &DeviceStatus {
+                        liquid_temp_c: 30.0 + i as f32 / 10.0,
+                        pump_rpm: 2000,
+                        pump_duty: 60,
+                        fan_rpm: 900,
+                        fan_duty: 40,
+                    },
+                )
+            });
+        }
+        dashboard.buckets = vec![BucketInfo {
+            index: 0,
+            occupied: true,
+            start_page: 0,
+            size_pages: 400,
+        }];
+        dashboard.brightness = Some(80);
+
+        let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+        terminal.draw(|frame| draw(frame, &dashboard)).unwrap();
+
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        for text in [
+            "Temperature",
+            "60% · 2000 RPM",
+            "40% · 900 RPM",
+            "#0",
+            "80%",
+        ] {
+            assert!(screen.contains(text), "{:?} not on screen", text);
+        }
+    }
+}
@@ -0,0 +1,435 @@
+//! Speed curve charts.
+//!
+//! Renders a profile's pump and fan curves, as the 40-point tables the device
+//! receives, either to a PNG image or as a Unicode plot for the terminal.
+
+use image::RgbaImage;
+use imageproc::drawing::{
+    draw_filled_circle_mut, draw_hollow_circle_mut, draw_line_segment_mut, draw_text_mut,
+};
+use rusttype::Scale;
+
+use super::stats_image::load_font;
+use crate::cooling::expression::ChannelExpr;
+use crate::error::Result;
+use crate::protocol::{
+    CRITICAL_TEMPERATURE, CURVE_POINTS, Channel, MIN_CURVE_TEMP, interpolate_profile,
+};
+
+/// Terminal plot height in rows (one row per 5% duty).
+const TERMINAL_ROWS: usize = 21;
+
+/// Colors for the PNG chart
+pub mod colors {
+    use image::Rgba;
+
+    pub const BACKGROUND: Rgba<u8> = Rgba([18, 18, 24, 255]);
+    pub const GRID: Rgba<u8> = Rgba([50, 50, 60, 255]);
+    pub const AXIS: Rgba<u8> = Rgba([160, 160, 170, 255]);
+    pub const TEXT: Rgba<u8> = Rgba([220, 220, 220, 255]);
+    pub const PUMP: Rgba<u8> = Rgba([64, 160, 255, 255]);
+    pub const FAN: Rgba<u8> = Rgba([255, 150, 40, 255]);
+    pub const OPERATING_POINT: Rgba<u8> = Rgba([255, 60, 60, 255]);
+}
+
+/// Current temperature and duties, marked on the chart.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OperatingPoint {
+    /// Temperature the curves are evaluated at (°C)
+    pub temperature: f32,
+    /// Current pump duty (%)
+    pub pump_duty: u8,
+    /// Current fan duty (%)
+    pub fan_duty: u8,
+}
+
+#[derive(Debug, Clone)]
+struct PlotCurve {
+    points: Vec<(u8, u8)>,
+    table: [u8; CURVE_POINTS],
+}
+
+impl PlotCurve {
+    fn new(points: &[(u8, u8)]) -> Result<Option<Self>> {
+        if points.is_empty() {
+            return Ok(None);
+        }
+        let mut sorted = points.to_vec();
+        sorted.sort_by_key(|(temp, _)| *temp);
+        Ok(Some(Self {
+            table: interpolate_profile(&sorted)?,
+            points: sorted,
+        }))
+    }
+
+    fn from_expr(expr: &ChannelExpr, channel: Channel) -> Self {
+        // Points are at curve input temperatures; plot them at source temperatures
+        let points = expr
+            .points(channel)
+            .into_iter()
+            .filter_map(|(temp, _)| {
+                let temp = temp as i16 - expr.offset;
+                let on_curve =
+                    (MIN_CURVE_TEMP as i16..=CRITICAL_TEMPERATURE as i16).contains(&temp);
+                on_curve.then(|| {
+                    (
+                        temp as u8,
+                        expr.duty_at(channel, (temp + expr.offset) as u8),
+                    )
+                })
+            })
+            .collect();
+        Self {
+            points,
+            table: expr.table(channel),
+        }
+    }
+}
+
+/// Pump and fan curves prepared for plotting.
+#[derive(Debug, Clone)]
+pub struct CurvePlot {
+    pump: Option<PlotCurve>,
+    fan: Option<PlotCurve>,
+    operating_point: Option<OperatingPoint>,
+}
+
+impl CurvePlot {
+    /// Prepare sparse (temperature, duty) curves for plotting.
+    ///
+    /// An empty curve leaves that channel out of the chart.
+    ///
+    /// # Errors
+    /// Returns `InvalidTemperature` if a point lies outside 20-59°C.
+    pub fn new(pump: &[(u8, u8)], fan: &[(u8, u8)]) -> Result<Self> {
+        Ok(Self {
+            pump: PlotCurve::new(pump)?,
+            fan: PlotCurve::new(fan)?,
+            operating_point: None,
+        })
+    }
+
+    /// Prepare channel expressions for plotting, against the source temperature.
+    ///
+    /// A channel without an expression is left out of the chart.
+    pub fn from_exprs(pump: Option<&ChannelExpr>, fan: Option<&ChannelExpr>) -> Self {
+        Self {
+            pump: pump.map(|e| PlotCurve::from_expr(e, Channel::Pump)),
+            fan: fan.map(|e| PlotCurve::from_expr(e, Channel::Fan)),
+            operating_point: None,
+        }
+    }
+
+    /// Mark the current operating point on the chart.
+    pub fn with_operating_point(mut self, point: OperatingPoint) -> Self {
+        self.operating_point = Some(point);
+        self
+    }
+
+    /// The 40-point device table for a channel (20-59°C), if it is plotted.
+    pub fn table(&self, channel: Channel) -> Option<&[u8; CURVE_POINTS]> {
+        self.curve(channel).map(|c| &c.table)
+    }
+
+    fn curve(&self, channel: Channel) -> Option<&PlotCurve> {
+        match channel {
+            Channel::Pump => self.pump.as_ref(),
+            Channel::Fan => self.fan.as_ref(),
+        }
+    }
+
+    // ========================================================================
+    // Terminal
+    // ========================================================================
+
+    /// Render the chart as Unicode text, one column per degree.
+    ///
+    /// `●` marks the pump, `■` the fan and `◆` both. The operating point is
+    /// drawn as a dotted column with `◉` at the current duties.
+    pub fn render_terminal(&self) -> String {
+        let mut grid = vec![[' '; CURVE_POINTS]; TERMINAL_ROWS];
+        let row = |duty: u8| TERMINAL_ROWS - 1 - (duty.min(100) as usize + 2) / 5;
+
+        if let Some(point) = self.operating_point
+            && let Some(col) = temperature_column(point.temperature)
+        {
+            for line in grid.iter_mut() {
+                line[col] = '┊';
+            }
+            grid[row(point.pump_duty)][col] = '◉';
+            grid[row(point.fan_duty)][col] = '◉';
+        }
+
+        for (channel, marker) in [(Channel::Pump, '●'), (Channel::Fan, '■')] {
+            let Some(curve) = self.curve(channel) else {
+                continue;
+            };
+            for (col, &duty) in curve.table.iter().enumerate() {
+                let cell = &mut grid[row(duty)][col];
+                *cell = match *cell {
+                    '◉' => '◉',
+                    '●' => '◆',
+                    _ => marker,
+                };
+            }
+        }
+
+        let mut out = String::new();
+        for (i, line) in grid.iter().enumerate() {
+            let duty = (TERMINAL_ROWS - 1 - i) * 5;
+            if duty.is_multiple_of(20) {
+                out.push_str(&format!("{:>4}% ┤", duty));
+            } else {
+                out.push_str("      │");
+            }
+            out.extend(line.iter());
+            out.push('\n');
+        }
+
+        out.push_str("      └");
+        out.push_str(&"─".repeat(CURVE_POINTS));
+        out.push('\n');
+
+        let mut labels = [' '; CURVE_POINTS + 1];
+        for temp in [20u8, 30, 40, 50, CRITICAL_TEMPERATURE] {
+            let col = (temp - MIN_CURVE_TEMP) as usize;
+            for (i, c) in temp.to_string().chars().enumerate() {
+                labels[col + i] = c;
+            }
+        }
+        out.push_str("       ");
+        out.extend(labels.iter());
+        out.push_str("°C\n");
+
+        let mut legend = Vec::new();
+        if self.pump.is_some() {
+            legend.push("● Pump");
+        }
+        if self.fan.is_some() {
+            legend.push("■ Fan");
+        }
+        if self.pump.is_some() && self.fan.is_some() {
+            legend.push("◆ Both");
+        }
+        if self.operating_point.is_some() {
+            legend.push("◉ Now");
+        }
+        out.push_str(&format!("       {}\n", legend.join("  ")));
+        out
+    }
+
+    // ========================================================================
+    // PNG
+    // ========================================================================
+
+    /// Render the chart as an RGBA image.
+    ///
+    /// Axis labels and the legend are drawn only if a system font is found.
+    pub fn render_png(&self, width: u32, height: u32) -> RgbaImage {
+        let mut img = RgbaImage::from_pixel(width, height, colors::BACKGROUND);
+        let font = load_font();
+
+        let left = 56.0;
+        let right = width as f32 - 32.0;
+        let top = 36.0;
+        let bottom = height as f32 - 40.0;
+
+        let x = |temp: f32| {
+            left + (temp - MIN_CURVE_TEMP as f32) / (CRITICAL_TEMPERATURE - MIN_CURVE_TEMP) as f32
+                * (right - left)
+        };
+        let y = |duty: f32| bottom - duty / 100.0 * (bottom - top);
+
+        // Grid every 5°C and 10%
+        for temp in (MIN_CURVE_TEMP..=CRITICAL_TEMPERATURE).step_by(5) {
+            draw_line_segment_mut(
+                &mut img,
+                (x(temp as f32), top),
+                (x(temp as f32), bottom),
+                colors::GRID,
+            );
+        }
+        for duty in (0..=100).step_by(10) {
+            draw_line_segment_mut(
+                &mut img,
+                (left, y(duty as f32)),
+                (right, y(duty as f32)),
+                colors::GRID,
+            );
+        }
+        draw_line_segment_mut(&mut img, (left, top), (left, bottom), colors::AXIS);
+        draw_line_segment_mut(&mut img, (left, bottom), (right, bottom), colors::AXIS);
+
+        if let Some(font) = &font {
+            let scale = Scale::uniform(14.0);
+            for duty in (0..=100).step_by(20) {
+                let label = format!("{}%", duty);
+                draw_text_mut(
+                    &mut img,
+                    colors::TEXT,
+                    8,
+                    y(duty as f32) as i32 - 7,
+                    scale,
+                    font,
+                    &label,
+                );
+            }
+            for temp in [20u8, 30, 40, 50, CRITICAL_TEMPERATURE] {
+                let label = format!("{}°C", temp);
+                draw_text_mut(
+                    &mut img,
+                    colors::TEXT,
+                    x(temp as f32) as i32 - 12,
+                    bottom as i32 + 10,
+                    scale,
+                    font,
+                    &label,
+                );
+            }
+
+            let mut legend_x = left as i32;
+            for (channel, color) in [(Channel::Pump, colors::PUMP), (Channel::Fan, colors::FAN)] {
+                if self.curve(channel).is_some() {
+                    draw_filled_circle_mut(&mut img, (legend_x + 5, 18), 5, color);
+                    draw_text_mut(
+                        &mut img,
+                        colors::TEXT,
+                        legend_x + 14,
+                        10,
+                        scale,
+                        font,
+                        &channel.to_string(),
+                    );
+                    legend_x += 70;
+                }
+            }
+            if let Some(point) = self.operating_point {
+                draw_hollow_circle_mut(&mut img, (legend_x + 5, 18), 5, colors::OPERATING_POINT);
+                let label = format!(
+                    "Now {:.1}°C: pump {}%, fan {}%",
+                    point.temperature, point.pump_duty, point.fan_duty
+                );
+                draw_text_mut(
+                    &mut img,
+                    colors::TEXT,
+                    legend_x + 14,
+                    10,
+                    scale,
+                    font,
+                    &label,
+                );
+            }
+        }
+
+        // Device tables as lines, the stored points as dots
+        for (channel, color) in [(Channel::Pump, colors::PUMP), (Channel::Fan, colors::FAN)] {
+            let Some(curve) = self.curve(channel) else {
+                continue;
+            };
+            for (i, pair) in curve.table.windows(2).enumerate() {
+                let t = (MIN_CURVE_TEMP as usize + i) as f32;
+                let (x1, y1) = (x(t), y(pair[0] as f32));
+                let (x2, y2) = (x(t + 1.0), y(pair[1] as f32));
+                for offset in [-1.0, 0.0, 1.0] {
+                    draw_line_segment_mut(&mut img, (x1, y1 + offset), (x2, y2 + offset), color);
+                }
+            }
+            for &(temp, duty) in &curve.points {
+                draw_filled_circle_mut(
+                    &mut img,
+                    (x(temp as f32) as i32, y(duty as f32) as i32),
+                    4,
+                    color,
+                );
+            }
+        }
+
+        if let Some(point) = self.operating_point {
+            let temp = point
+                .temperature
+                .clamp(MIN_CURVE_TEMP as f32, CRITICAL_TEMPERATURE as f32);
+            draw_line_segment_mut(
+                &mut img,
+                (x(temp), top),
+                (x(temp), bottom),
+                colors::OPERATING_POINT,
+            );
+            for duty in [point.pump_duty, point.fan_duty] {
+                let center = (x(temp) as i32, y(duty.min(100) as f32) as i32);
+                draw_hollow_circle_mut(&mut img, center, 7, colors::OPERATING_POINT);
+                draw_hollow_circle_mut(&mut img, center, 6, colors::OPERATING_POINT);
+            }
+        }
+
+        img
+    }
+}
+
+/// Column of a temperature in the terminal plot, if it lies on the curve.
+fn temperature_column(temperature: f32) -> Option<usize> {
+    let temp = temperature.round();
+    if temp < MIN_CURVE_TEMP as f32 || temp > CRITICAL_TEMPERATURE as f32 {
+        return None;
+    }
+    Some((temp as u8 - MIN_CURVE_TEMP) as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_terminal_plot() {
+        let plot = CurvePlot::new(&[(20, 60), (59, 100)], &[(20, 60)])
+            .unwrap()
+            .with_operating_point(OperatingPoint {
+                temperature: 40.0,
+                pump_duty: 80,
+                fan_duty: 30,
+            });
+
+        assert_eq!(plot.table(Channel::Fan).unwrap(), &[60; CURVE_POINTS]);
+        assert_eq!(plot.table(Channel::Pump).unwrap()[CURVE_POINTS - 1], 100);
+
+        let text = plot.render_terminal();
+        let lines: Vec<&str> = text.lines().collect();
+        let cell = |line: &str, col: usize| line.chars().nth(7 + col).unwrap();
+
+        // 100% row: pump ends at the last column
+        assert!(lines[0].starts_with(" 100% ┤"));
+        assert_eq!(cell(lines[0], CURVE_POINTS - 1), '●');
+        // 60% row: both curves start at 20°C
+        assert_eq!(cell(lines[8], 0), '◆');
+        // Operating point at 40°C, 30% fan
+        assert_eq!(cell(lines[14], 20), '◉');
+        assert_eq!(cell(lines[20], 20), '┊');
+        assert!(text.contains("◉ Now"));
+    }
+
+    #[test]
+    fn test_png_plot() {
+        let plot = CurvePlot::new(&[(20, 50), (59, 50)], &[]).unwrap();
+        assert!(plot.table(Channel::Fan).is_none());
+
+        let img = plot.render_png(400, 300);
+        assert_eq!(img.dimensions(), (400, 300));
+        assert!(img.pixels().any(|p| *p == colors::PUMP));
+        assert!(!img.pixels().any(|p| *p == colors::FAN));
+    }
+
+    #[test]
+    fn test_expression_plot() {
+        let fan = crate::cooling::parse_channel_expr("offset(5)+step(20:30,50:80)").unwrap();
+        let plot = CurvePlot::from_exprs(None, Some(&fan));
+        let table = plot.table(Channel::Fan).unwrap();
+        // The 50°C step is reached at 45°C source temperature
+        assert_eq!(table[24], 30);
+        assert_eq!(table[25], 80);
+        assert_eq!(plot.fan.as_ref().unwrap().points, vec![(45, 80)]);
+    }
+
+    #[test]
+    fn test_out_of_range_point_rejected() {
+        assert!(CurvePlot::new(&[(70, 100)], &[]).is_err());
+    }
+}
@@ -1,8 +1,11 @@
+pub mod curve_plot;
 pub mod image_processing;
+pub mod output;
 pub mod parsing;
 pub mod radial_gauge;
 pub mod sensors;
 pub mod stats_image;
 
 // Re-export commonly used items
+pub use output::OutputFormat;
 pub use sensors::SystemSensors;
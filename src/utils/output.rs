@@ -0,0 +1,149 @@
+//! Machine-readable CLI output.
+//!
+//! Commands print a human-readable table by default. With `--format json`,
+//! `yaml` or `jsonl` they print a serialized document instead, so scripts can
+//! parse the output without scraping text.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::Serialize;
+
+use crate::error::{KrakenError, Result};
+
+/// Output format of the CLI.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Human-readable text (default)
+    #[default]
+    Table,
+    /// Pretty-printed JSON
+    Json,
+    /// YAML
+    Yaml,
+    /// One compact JSON object per line
+    Jsonl,
+}
+
+impl OutputFormat {
+    /// True for the human-readable format.
+    pub fn is_table(self) -> bool {
+        self == OutputFormat::Table
+    }
+
+    /// Serialize `value` as one document, or `None` for [`OutputFormat::Table`].
+    ///
+    /// The result ends with a newline.
+    pub fn render<T: Serialize>(self, value: &T) -> Result<Option<String>> {
+        let text = match self {
+            OutputFormat::Table => return Ok(None),
+            OutputFormat::Json => serde_json::to_string_pretty(value).map_err(json_error)? + "\n",
+            OutputFormat::Jsonl => serde_json::to_string(value).map_err(json_error)? + "\n",
+            OutputFormat::Yaml => serde_yaml::to_string(value).map_err(|e| {
+                KrakenError::InvalidInput(format!("Cannot serialize output: {}", e))
+            })?,
+        };
+        Ok(Some(text))
+    }
+
+    /// Serialize one sample of a stream, or `None` for [`OutputFormat::Table`].
+    ///
+    /// JSON and JSON Lines both print one compact object per line; YAML
+    /// starts each sample as a new document (`---`).
+    pub fn render_sample<T: Serialize>(self, value: &T) -> Result<Option<String>> {
+        match self {
+            OutputFormat::Json => OutputFormat::Jsonl.render(value),
+            OutputFormat::Yaml => Ok(self.render(value)?.map(|doc| format!("---\n{}", doc))),
+            _ => self.render(value),
+        }
+    }
+}
+
+fn json_error(e: serde_json::Error) -> KrakenError {
+    KrakenError::InvalidInput(format!("Cannot serialize output: {}", e))
+}
+
+impl FromStr for OutputFormat {
+    type Err = KrakenError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "table" | "text" => Ok(OutputFormat::Table),
+            "json" => Ok(OutputFormat::Json),
+            "yaml" | "yml" => Ok(OutputFormat::Yaml),
+            "jsonl" | "ndjson" => Ok(OutputFormat::Jsonl),
+            _ => Err(KrakenError::InvalidInput(format!(
+                "Unknown output format '{}'. Use table, json, yaml or jsonl",
+                s
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            OutputFormat::Table => "table",
+            OutputFormat::Json => "json",
+            OutputFormat::Yaml => "yaml",
+            OutputFormat::Jsonl => "jsonl",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Sample {
+        name: &'static str,
+        value: u8,
+    }
+
+    const SAMPLE: Sample = Sample {
+        name: "pump",
+        value: 70,
+    };
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("JSON".parse::<OutputFormat>().unwrap(), OutputFormat::Json);
+        assert_eq!("yml".parse::<OutputFormat>().unwrap(), OutputFormat::Yaml);
+        assert_eq!(
+            "jsonl".parse::<OutputFormat>().unwrap().to_string(),
+            "jsonl"
+        );
+        assert!("xml".parse::<OutputFormat>().is_err());
+    }
+
+    #[test]
+    fn test_render() {
+        assert_eq!(OutputFormat::Table.render(&SAMPLE).unwrap(), None);
+        assert_eq!(
+            OutputFormat::Json.render(&SAMPLE).unwrap().unwrap(),
+            "{\n  \"name\": \"pump\",\n  \"value\": 70\n}\n"
+        );
+        assert_eq!(
+            OutputFormat::Jsonl.render(&SAMPLE).unwrap().unwrap(),
+            "{\"name\":\"pump\",\"value\":70}\n"
+        );
+        assert_eq!(
+            OutputFormat::Yaml.render(&SAMPLE).unwrap().unwrap(),
+            "name: pump\nvalue: 70\n"
+        );
+    }
+
+    #[test]
+    fn test_render_sample() {
+        assert_eq!(
+            OutputFormat::Json.render_sample(&SAMPLE).unwrap().unwrap(),
+            "{\"name\":\"pump\",\"value\":70}\n"
+        );
+        assert_eq!(
+            OutputFormat::Yaml.render_sample(&SAMPLE).unwrap().unwrap(),
+            "---\nname: pump\nvalue: 70\n"
+        );
+    }
+}
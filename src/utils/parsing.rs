@@ -4,8 +4,11 @@
 //! used throughout the application.
 
 use crate::config::SpeedProfile;
+use crate::cooling::curve::Interpolation;
+use crate::cooling::expression::{ChannelExpr, CurveExpr, is_expression, parse_channel_expr};
 use crate::error::{KrakenError, Result};
 use crate::protocol::Channel;
+use std::time::Duration;
 
 // =============================================================================
 // Color Parsing
@@ -86,7 +89,11 @@ pub fn parse_lcd_mode_string(mode: &str) -> u8 {
 /// Parse a speed profile name into a SpeedProfile enum.
 ///
 /// # Arguments
-/// * `name` - Profile name: "silent", "performance", or "fixed:XX"
+/// * `name` - Profile name: "silent", "performance", "fixed:XX", or a
+///   profile expression such as "spline(20:30,59:100)+clamp(min=30)"
+///
+/// Expressions that are a plain `curve(...)` or `fixed(...)` map to
+/// `Custom`/`Fixed`; anything else becomes `Expression`.
 ///
 /// # Returns
 /// The corresponding SpeedProfile variant
@@ -101,8 +108,27 @@ pub fn parse_lcd_mode_string(mode: &str) -> u8 {
 ///
 /// let fixed = parse_speed_profile("fixed:75").unwrap();
 /// assert!(matches!(fixed, SpeedProfile::Fixed(75)));
+///
+/// let custom = parse_speed_profile("curve(20:30,59:100)").unwrap();
+/// assert_eq!(custom, SpeedProfile::Custom(vec![(20, 30), (59, 100)]));
 /// ```
 pub fn parse_speed_profile(name: &str) -> Result<SpeedProfile> {
+    if is_expression(name) {
+        let expr = parse_channel_expr(name)?;
+        if !expr.is_curve_only() {
+            return Ok(SpeedProfile::Expression(expr));
+        }
+        return Ok(match expr.curve {
+            CurveExpr::Curve(curve) if curve.interpolation() == Interpolation::Linear => {
+                SpeedProfile::Custom(curve.points().to_vec())
+            }
+            CurveExpr::Silent => SpeedProfile::Silent,
+            CurveExpr::Performance => SpeedProfile::Performance,
+            CurveExpr::Fixed(duty) => SpeedProfile::Fixed(duty),
+            curve => SpeedProfile::Expression(ChannelExpr::from_curve(curve)),
+        });
+    }
+
     let lower = name.to_lowercase();
 
     if lower == "silent" {
@@ -121,7 +147,7 @@ pub fn parse_speed_profile(name: &str) -> Result<SpeedProfile> {
     }
 
     Err(KrakenError::InvalidInput(format!(
-        "Unknown profile '{}'. Use: silent, performance, or fixed:XX",
+        "Unknown profile '{}'. Use: silent, performance, fixed:XX or an expression like curve(20:30,59:100)",
         name
     )))
 }
@@ -148,6 +174,123 @@ pub fn parse_channel(name: &str) -> Result<Channel> {
     }
 }
 
+// =============================================================================
+// Curve Parsing
+// =============================================================================
+
+/// Parse an inline curve into (temperature, duty) points.
+///
+/// # Arguments
+/// * `spec` - Comma-separated `TEMP:DUTY` pairs, e.g. "20:70,45:80,59:100"
+///
+/// # Returns
+/// The points in the order given (not validated against channel limits)
+pub fn parse_curve_points(spec: &str) -> Result<Vec<(u8, u8)>> {
+    spec.split(',')
+        .map(|pair| {
+            let (temp, duty) = pair.trim().split_once(':').ok_or_else(|| {
+                KrakenError::InvalidInput(format!(
+                    "Invalid curve point '{}'. Use TEMP:DUTY, e.g. 45:80",
+                    pair
+                ))
+            })?;
+            let parse = |value: &str, what: &str| {
+                value.trim().parse::<u8>().map_err(|_| {
+                    KrakenError::InvalidInput(format!(
+                        "Invalid {} '{}' in curve point '{}'",
+                        what, value, pair
+                    ))
+                })
+            };
+            Ok((parse(temp, "temperature")?, parse(duty, "duty")?))
+        })
+        .collect()
+}
+
+/// Parse a per-channel curve argument.
+///
+/// # Arguments
+/// * `arg` - `CHANNEL=CURVE`, where CURVE is `TEMP:DUTY,...`, `fixed:XX`,
+///   `silent`, `performance` or a profile expression (e.g. "pump=20:70,59:100",
+///   "fan=fixed:40", "fan=spline(20:30,59:100)+clamp(min=30)")
+///
+/// # Returns
+/// The channel and its speed profile
+pub fn parse_channel_curve(arg: &str) -> Result<(Channel, SpeedProfile)> {
+    let (channel, spec) = arg.split_once('=').ok_or_else(|| {
+        KrakenError::InvalidInput(format!(
+            "Invalid curve '{}'. Use CHANNEL=CURVE, e.g. pump=20:70,59:100",
+            arg
+        ))
+    })?;
+    let channel = parse_channel(channel.trim())?;
+
+    let profile = if is_expression(spec) {
+        parse_speed_profile(spec.trim())?
+    } else if spec.contains(',') || spec.chars().next().is_some_and(|c| c.is_ascii_digit()) {
+        SpeedProfile::Custom(parse_curve_points(spec)?)
+    } else {
+        parse_speed_profile(spec.trim())?
+    };
+
+    Ok((channel, profile))
+}
+
+// =============================================================================
+// Duration Parsing
+// =============================================================================
+
+/// Parse a duration such as "90s", "5m", "1h30m" or "2d".
+///
+/// # Arguments
+/// * `spec` - One or more `NUMBER[UNIT]` parts, unit `s`, `m`, `h` or `d`
+///   (a bare number is seconds)
+///
+/// # Example
+/// ```
+/// use nzxt_rust_devices::utils::parsing::parse_duration;
+/// use std::time::Duration;
+///
+/// assert_eq!(parse_duration("1h30m").unwrap(), Duration::from_secs(5400));
+/// ```
+pub fn parse_duration(spec: &str) -> Result<Duration> {
+    let invalid = || {
+        KrakenError::InvalidInput(format!(
+            "Invalid duration '{}'. Use e.g. 30s, 5m, 1h30m or 2d",
+            spec
+        ))
+    };
+
+    let spec = spec.trim();
+    let mut total: u64 = 0;
+    let mut rest = spec;
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let value: u64 = rest[..digits].parse().map_err(|_| invalid())?;
+        rest = &rest[digits..];
+        let unit = rest.chars().next();
+        let seconds = match unit {
+            None | Some('s') => 1,
+            Some('m') => 60,
+            Some('h') => 3600,
+            Some('d') => 86400,
+            Some(_) => return Err(invalid()),
+        };
+        rest = unit.map_or(rest, |u| &rest[u.len_utf8()..]);
+        total = value
+            .checked_mul(seconds)
+            .and_then(|s| total.checked_add(s))
+            .ok_or_else(invalid)?;
+    }
+
+    if spec.is_empty() {
+        return Err(invalid());
+    }
+    Ok(Duration::from_secs(total))
+}
+
 // =============================================================================
 // Tests
 // =============================================================================
@@ -204,4 +347,38 @@ mod tests {
         assert!(matches!(parse_channel("PUMP").unwrap(), Channel::Pump));
         assert!(parse_channel("invalid").is_err());
     }
+
+    #[test]
+    fn test_parse_channel_curve() {
+        let (channel, profile) = parse_channel_curve("pump=20:70,45:80,59:100").unwrap();
+        assert_eq!(channel, Channel::Pump);
+        assert_eq!(
+            profile,
+            SpeedProfile::Custom(vec![(20, 70), (45, 80), (59, 100)])
+        );
+
+        let (channel, profile) = parse_channel_curve("fan=fixed:40").unwrap();
+        assert_eq!(channel, Channel::Fan);
+        assert_eq!(profile, SpeedProfile::Fixed(40));
+
+        let (_, profile) = parse_channel_curve("fan=spline(20:30,59:100)").unwrap();
+        assert!(matches!(profile, SpeedProfile::Expression(_)));
+        let (_, profile) = parse_channel_curve("pump=curve(20:70,59:100)").unwrap();
+        assert_eq!(profile, SpeedProfile::Custom(vec![(20, 70), (59, 100)]));
+
+        assert!(parse_channel_curve("fan").is_err());
+        assert!(parse_channel_curve("fan=20-30").is_err());
+        assert!(parse_channel_curve("gpu=20:30").is_err());
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_duration("5m").unwrap(), Duration::from_secs(300));
+        assert_eq!(parse_duration("1h30m").unwrap(), Duration::from_secs(5400));
+        assert_eq!(parse_duration("2d").unwrap(), Duration::from_secs(172800));
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("m").is_err());
+        assert!(parse_duration("5x").is_err());
+    }
 }
@@ -2,15 +2,25 @@
 //!
 //! This module provides a wrapper around `sysinfo` for detecting and reading
 //! system sensor values, with specific focus on CPU and GPU temperature sensors.
+//!
+//! The CPU and GPU sensors are the ones selected in `config.json`
+//! ([`SensorConfig`]), or else the first sensor with a typical label.
+
+use std::fmt;
+
+use regex::Regex;
+use serde::Serialize;
+use sysinfo::{Component, Components};
 
-use sysinfo::Components;
+use crate::error::{KrakenError, Result};
+use crate::storage::{SensorConfig, SensorSelector};
 
 // =============================================================================
 // Sensor Info
 // =============================================================================
 
 /// Information about a detected sensor.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct SensorInfo {
     /// Sensor label/name.
     pub label: String,
@@ -20,6 +30,110 @@ pub struct SensorInfo {
     pub critical: Option<f32>,
 }
 
+impl From<&Component> for SensorInfo {
+    fn from(component: &Component) -> Self {
+        Self {
+            label: component.label().to_string(),
+            temperature: component.temperature().unwrap_or(0.0),
+            critical: component.critical(),
+        }
+    }
+}
+
+// =============================================================================
+// Sensor Selection
+// =============================================================================
+
+/// Logical temperature read from a system sensor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SensorKind {
+    Cpu,
+    Gpu,
+}
+
+impl fmt::Display for SensorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SensorKind::Cpu => write!(f, "CPU"),
+            SensorKind::Gpu => write!(f, "GPU"),
+        }
+    }
+}
+
+impl SensorKind {
+    /// Whether a label looks like this kind of sensor, for automatic
+    /// selection.
+    ///
+    /// - CPU: "cpu", "package", "core", "tdie", "computer"
+    /// - GPU: "gpu", "nvidia", "amd", "edge"
+    fn matches_label(self, label: &str) -> bool {
+        let label = label.to_lowercase();
+        let patterns: &[&str] = match self {
+            // "computer" is a fallback for some Windows systems
+            SensorKind::Cpu => &["cpu", "package", "core", "tdie", "computer"],
+            SensorKind::Gpu => &["gpu", "nvidia", "amd", "edge"],
+        };
+        patterns.iter().any(|p| label.contains(p))
+    }
+}
+
+/// A configured [`SensorSelector`], with its regex compiled.
+#[derive(Debug, Clone)]
+struct Selection {
+    selector: SensorSelector,
+    regex: Option<Regex>,
+}
+
+impl Selection {
+    fn new(kind: SensorKind, selector: &SensorSelector) -> Result<Self> {
+        let regex =
+            match selector {
+                SensorSelector::Regex(pattern) => Some(Regex::new(pattern).map_err(|e| {
+                    KrakenError::InvalidInput(format!("{} sensor regex: {}", kind, e))
+                })?),
+                _ => None,
+            };
+        Ok(Self {
+            selector: selector.clone(),
+            regex,
+        })
+    }
+
+    fn matches(&self, index: usize, label: &str) -> bool {
+        match &self.selector {
+            SensorSelector::Label(expected) => label == expected,
+            SensorSelector::Regex(_) => self.regex.as_ref().is_some_and(|r| r.is_match(label)),
+            SensorSelector::Index(expected) => index == *expected,
+        }
+    }
+}
+
+/// Index of the sensor read for `kind`: the selected one, else the first
+/// with a matching label.
+fn find_index<'a>(
+    labels: impl IntoIterator<Item = &'a str>,
+    kind: SensorKind,
+    selection: Option<&Selection>,
+) -> Option<usize> {
+    let mut labels = labels.into_iter().enumerate();
+    match selection {
+        Some(selection) => labels.find(|&(i, label)| selection.matches(i, label)),
+        None => labels.find(|&(_, label)| kind.matches_label(label)),
+    }
+    .map(|(i, _)| i)
+}
+
+/// Selector that picks the sensor at `index` of a `sensors` list: its label,
+/// or its index if another sensor has the same label.
+pub fn selector_for(sensors: &[SensorInfo], index: usize) -> Option<SensorSelector> {
+    let label = &sensors.get(index)?.label;
+    if sensors.iter().filter(|s| &s.label == label).count() == 1 {
+        Some(SensorSelector::Label(label.clone()))
+    } else {
+        Some(SensorSelector::Index(index))
+    }
+}
+
 // =============================================================================
 // System Sensors
 // =============================================================================
@@ -27,6 +141,8 @@ pub struct SensorInfo {
 /// Wrapper for system sensor access with caching.
 pub struct SystemSensors {
     components: Components,
+    cpu: Option<Selection>,
+    gpu: Option<Selection>,
 }
 
 impl SystemSensors {
@@ -34,9 +150,37 @@ impl SystemSensors {
     pub fn new() -> Self {
         Self {
             components: Components::new_with_refreshed_list(),
+            cpu: None,
+            gpu: None,
         }
     }
 
+    /// Create an instance reading the sensors selected in `config`.
+    ///
+    /// # Errors
+    /// Returns `InvalidInput` if a regex selector does not compile.
+    pub fn with_config(config: &SensorConfig) -> Result<Self> {
+        let mut sensors = Self::new();
+        sensors.select(config)?;
+        Ok(sensors)
+    }
+
+    /// Replace the sensor selection.
+    ///
+    /// # Errors
+    /// Returns `InvalidInput` if a regex selector does not compile.
+    pub fn select(&mut self, config: &SensorConfig) -> Result<()> {
+        let selection = |kind, selector: &Option<SensorSelector>| {
+            selector
+                .as_ref()
+                .map(|s| Selection::new(kind, s))
+                .transpose()
+        };
+        self.cpu = selection(SensorKind::Cpu, &config.cpu)?;
+        self.gpu = selection(SensorKind::Gpu, &config.gpu)?;
+        Ok(())
+    }
+
     /// Refresh all sensor values.
     pub fn refresh(&mut self) {
         self.components.refresh(true);
@@ -47,75 +191,76 @@ impl SystemSensors {
         self.components.len()
     }
 
-    /// Find CPU temperature using common sensor label patterns.
-    ///
-    /// Searches for sensors with labels containing:
-    /// - "cpu", "package", "core", "tdie", "computer"
+    fn selection(&self, kind: SensorKind) -> Option<&Selection> {
+        match kind {
+            SensorKind::Cpu => self.cpu.as_ref(),
+            SensorKind::Gpu => self.gpu.as_ref(),
+        }
+    }
+
+    /// Index of the sensor read for `kind`, if any.
+    pub fn find_index(&self, kind: SensorKind) -> Option<usize> {
+        find_index(
+            self.components.iter().map(|c| c.label()),
+            kind,
+            self.selection(kind),
+        )
+    }
+
+    /// Find the sensor read for `kind`.
     ///
-    /// Returns the temperature of the first matching sensor.
+    /// This is the configured sensor if there is one (and `None` if it is
+    /// missing), else the first sensor whose label looks like `kind`.
+    pub fn find_sensor(&self, kind: SensorKind) -> Option<SensorInfo> {
+        let index = self.find_index(kind)?;
+        self.components.get(index).map(SensorInfo::from)
+    }
+
+    /// Find CPU temperature (see [`Self::find_sensor`]).
     pub fn find_cpu_temp(&self) -> Option<f32> {
-        self.components
-            .iter()
-            .find(|c| {
-                let label = c.label().to_lowercase();
-                label.contains("cpu")
-                    || label.contains("package")
-                    || label.contains("core")
-                    || label.contains("tdie")
-                    || label.contains("computer") // Fallback for some Windows systems
-            })
-            .and_then(|c| c.temperature())
-    }
-
-    /// Find GPU temperature using common sensor label patterns.
-    ///
-    /// Searches for sensors with labels containing:
-    /// - "gpu", "nvidia", "amd", "edge"
-    ///
-    /// Returns the temperature of the first matching sensor.
+        self.temperature(SensorKind::Cpu)
+    }
+
+    /// Find GPU temperature (see [`Self::find_sensor`]).
     pub fn find_gpu_temp(&self) -> Option<f32> {
-        self.components
-            .iter()
-            .find(|c| {
-                let label = c.label().to_lowercase();
-                label.contains("gpu")
-                    || label.contains("nvidia")
-                    || label.contains("amd")
-                    || label.contains("edge")
-            })
-            .and_then(|c| c.temperature())
+        self.temperature(SensorKind::Gpu)
+    }
+
+    fn temperature(&self, kind: SensorKind) -> Option<f32> {
+        let index = self.find_index(kind)?;
+        self.components.get(index)?.temperature()
     }
 
     /// Get all detected sensors as a list of SensorInfo.
     pub fn list_all(&self) -> Vec<SensorInfo> {
-        self.components
-            .iter()
-            .map(|c| SensorInfo {
-                label: c.label().to_string(),
-                temperature: c.temperature().unwrap_or(0.0),
-                critical: c.critical(),
-            })
-            .collect()
+        self.components.iter().map(SensorInfo::from).collect()
     }
 
-    /// Find the first sensor that matches one of the CPU patterns.
-    /// Returns both the sensor info and whether it was found.
+    /// Find the sensor read as the CPU temperature.
     pub fn find_cpu_sensor(&self) -> Option<SensorInfo> {
-        self.components
-            .iter()
-            .find(|c| {
-                let label = c.label().to_lowercase();
-                label.contains("cpu")
-                    || label.contains("package")
-                    || label.contains("core")
-                    || label.contains("tdie")
-                    || label.contains("computer")
-            })
-            .map(|c| SensorInfo {
-                label: c.label().to_string(),
-                temperature: c.temperature().unwrap_or(0.0),
-                critical: c.critical(),
-            })
+        self.find_sensor(SensorKind::Cpu)
+    }
+
+    /// Configured selector of `kind`, if any.
+    pub fn selector(&self, kind: SensorKind) -> Option<&SensorSelector> {
+        self.selection(kind).map(|s| &s.selector)
+    }
+
+    /// Why no sensor of `kind` is read, e.g.
+    /// `CPU sensor label "k10temp Tctl" not found`.
+    pub fn describe_missing(&self, kind: SensorKind) -> String {
+        match self.selector(kind) {
+            Some(selector) => format!("{} sensor {} not found", kind, selector),
+            None => format!("No {} sensor detected", kind),
+        }
+    }
+
+    /// Kinds whose configured sensor matches no detected sensor.
+    pub fn missing(&self) -> Vec<SensorKind> {
+        [SensorKind::Cpu, SensorKind::Gpu]
+            .into_iter()
+            .filter(|&kind| self.selection(kind).is_some() && self.find_index(kind).is_none())
+            .collect()
     }
 }
 
@@ -184,4 +329,60 @@ mod tests {
         let debug_str = format!("{:?}", info);
         assert!(debug_str.contains("Test"));
     }
+
+    #[test]
+    fn test_find_index() {
+        let labels = [
+            "nvme Composite",
+            "amdgpu edge",
+            "k10temp Tctl",
+            "k10temp Tccd1",
+        ];
+        let find = |kind, selector: Option<SensorSelector>| {
+            let selection = selector.map(|s| Selection::new(kind, &s).unwrap());
+            find_index(labels, kind, selection.as_ref())
+        };
+
+        // Automatic selection misses the k10temp CPU sensor
+        assert_eq!(find(SensorKind::Cpu, None), None);
+        assert_eq!(find(SensorKind::Gpu, None), Some(1));
+
+        let label = SensorSelector::Label("k10temp Tctl".into());
+        assert_eq!(find(SensorKind::Cpu, Some(label)), Some(2));
+        let regex = SensorSelector::Regex("^k10temp T(ctl|die)$".into());
+        assert_eq!(find(SensorKind::Cpu, Some(regex)), Some(2));
+        assert_eq!(
+            find(SensorKind::Cpu, Some(SensorSelector::Index(3))),
+            Some(3)
+        );
+
+        // A selected sensor that is gone is not replaced by another one
+        let gone = SensorSelector::Label("coretemp Package id 0".into());
+        assert_eq!(find(SensorKind::Cpu, Some(gone)), None);
+        assert_eq!(find(SensorKind::Gpu, Some(SensorSelector::Index(9))), None);
+
+        assert!(Selection::new(SensorKind::Cpu, &SensorSelector::Regex("(".into())).is_err());
+    }
+
+    #[test]
+    fn test_selector_for() {
+        let sensor = |label: &str| SensorInfo {
+            label: label.into(),
+            temperature: 40.0,
+            critical: None,
+        };
+        let sensors = [
+            sensor("nvme Composite"),
+            sensor("k10temp Tctl"),
+            sensor("nvme Composite"),
+        ];
+
+        assert_eq!(
+            selector_for(&sensors, 1),
+            Some(SensorSelector::Label("k10temp Tctl".into()))
+        );
+        // Duplicate labels are told apart by position
+        assert_eq!(selector_for(&sensors, 2), Some(SensorSelector::Index(2)));
+        assert_eq!(selector_for(&sensors, 3), None);
+    }
 }
@@ -3,7 +3,8 @@
 //! Generates 320x320 RGBA images with temperature and RPM data.
 
 use image::{Rgba, RgbaImage};
-use imageproc::drawing::draw_text_mut;
+use imageproc::drawing::{draw_polygon_mut, draw_text_mut};
+use imageproc::point::Point;
 use rusttype::{Font, Scale};
 use std::path::Path;
 
@@ -14,6 +15,9 @@ use super::radial_gauge::{
 /// LCD dimensions
 pub const LCD_SIZE: u32 = 320;
 
+/// Trends smaller than this (°C/min) are shown as steady, without an arrow.
+pub const TREND_DEADBAND: f32 = 0.2;
+
 /// Colors for the stats display
 pub mod colors {
     use image::Rgba;
@@ -38,7 +42,7 @@ fn temp_color(temp: f32) -> Rgba<u8> {
 }
 
 /// Try to load a font from common system paths
-fn load_font() -> Option<Font<'static>> {
+pub(crate) fn load_font() -> Option<Font<'static>> {
     let font_paths = [
         "C:\\Windows\\Fonts\\arialbd.ttf",  // Arial Bold
         "C:\\Windows\\Fonts\\segoeuib.ttf", // Segoe UI Bold
@@ -170,6 +174,21 @@ pub fn generate_radial_stats_image(
     label: &str,
     pump_rpm: u16,
     config: Option<&RadialGaugeConfig>,
+) -> Option<RgbaImage> {
+    generate_radial_trend_image(temp, label, pump_rpm, None, config)
+}
+
+/// Generate a radial gauge stats image with a temperature trend.
+///
+/// Same as [`generate_radial_stats_image`], with the trend (°C/min, e.g. from
+/// `TelemetryHistory::rate_of_change`) shown below the pump RPM as an arrow
+/// and its rate. `None` leaves the trend out.
+pub fn generate_radial_trend_image(
+    temp: f32,
+    label: &str,
+    pump_rpm: u16,
+    trend: Option<f32>,
+    config: Option<&RadialGaugeConfig>,
 ) -> Option<RgbaImage> {
     let font = load_font()?;
 
@@ -256,9 +275,50 @@ pub fn generate_radial_stats_image(
         &rpm_text,
     );
 
+    if let Some(trend) = trend {
+        draw_trend(&mut img, &font, rpm_y + 28, trend);
+    }
+
     Some(img)
 }
 
+/// Draw a trend (°C/min) centered at `y`: an arrow up or down and the rate.
+fn draw_trend(img: &mut RgbaImage, font: &Font, y: i32, trend: f32) {
+    let text = if trend.abs() < TREND_DEADBAND {
+        "steady".to_string()
+    } else {
+        format!("{:+.1}°/min", trend)
+    };
+    let text_width = text.len() as i32 * 8;
+    let arrow_width = if trend.abs() < TREND_DEADBAND { 0 } else { 18 };
+    let x = (LCD_SIZE as i32 - text_width - arrow_width) / 2;
+
+    if arrow_width > 0 {
+        // Triangle pointing up when warming, down when cooling
+        let (tip, base) = if trend > 0.0 {
+            (y, y + 12)
+        } else {
+            (y + 12, y)
+        };
+        let arrow = [
+            Point::new(x, base),
+            Point::new(x + 12, base),
+            Point::new(x + 6, tip),
+        ];
+        draw_polygon_mut(img, &arrow, colors::TEXT_SECONDARY);
+    }
+
+    draw_text_mut(
+        img,
+        colors::TEXT_SECONDARY,
+        x + arrow_width,
+        y - 2,
+        Scale::uniform(18.0),
+        font,
+        &text,
+    );
+}
+
 /// Generate a radial gauge with full stats (temperature, pump RPM, fan RPM).
 pub fn generate_radial_full_stats_image(
     liquid_temp: f32,